@@ -7,9 +7,35 @@ pub mod v1 {
     //!
     //! [spec]: https://spec.matrix.org/v1.2/application-service-api/#put_matrixappv1transactionstxnid
 
+    #[cfg(feature = "unstable-msc3202")]
+    use std::collections::BTreeMap;
+    #[cfg(feature = "helper")]
+    use std::collections::{BTreeSet, VecDeque};
+
+    #[cfg(feature = "unstable-msc3202")]
+    use js_int::UInt;
+    #[cfg(feature = "unstable-msc2409")]
+    use ruma_common::events::{presence::PresenceEvent, AnyEphemeralRoomEvent, AnyToDeviceEvent};
+    #[cfg(feature = "helper")]
+    use ruma_common::events::{AnyMessageLikeEvent, AnyStateEvent};
+    #[cfg(feature = "unstable-msc3202")]
+    use ruma_common::DeviceKeyAlgorithm;
     use ruma_common::{
         api::ruma_api, events::AnyRoomEvent, serde::Raw, OwnedTransactionId, TransactionId,
     };
+    #[cfg(any(
+        feature = "unstable-msc3202",
+        all(feature = "helper", feature = "unstable-msc2409")
+    ))]
+    use ruma_common::{OwnedDeviceId, OwnedUserId};
+    #[cfg(any(feature = "unstable-msc2409", feature = "unstable-msc3202"))]
+    use serde::Deserialize;
+    #[cfg(feature = "unstable-msc3202")]
+    use serde::Serialize;
+    #[cfg(feature = "unstable-msc2409")]
+    use serde::{de, Deserializer};
+    #[cfg(feature = "unstable-msc2409")]
+    use serde_json::value::RawValue as RawJsonValue;
 
     ruma_api! {
         metadata: {
@@ -31,6 +57,78 @@ pub mod v1 {
 
             /// A list of events.
             pub events: &'a [Raw<AnyRoomEvent>],
+
+            /// A list of ephemeral data, i.e. presence, typing notifications and receipts.
+            ///
+            /// This is the `de.sorunome.msc2409.ephemeral` field of [MSC2409], the `ephemeral`
+            /// field is also accepted.
+            ///
+            /// [MSC2409]: https://github.com/matrix-org/matrix-spec-proposals/pull/2409
+            #[cfg(feature = "unstable-msc2409")]
+            #[serde(
+                default,
+                skip_serializing_if = "<[_]>::is_empty",
+                rename = "de.sorunome.msc2409.ephemeral",
+                alias = "ephemeral"
+            )]
+            pub ephemeral: &'a [Raw<EphemeralData>],
+
+            /// A list of to-device messages.
+            ///
+            /// This is the `de.sorunome.msc2409.to_device` field of [MSC2409].
+            ///
+            /// [MSC2409]: https://github.com/matrix-org/matrix-spec-proposals/pull/2409
+            #[cfg(feature = "unstable-msc2409")]
+            #[serde(
+                default,
+                skip_serializing_if = "<[_]>::is_empty",
+                rename = "de.sorunome.msc2409.to_device"
+            )]
+            pub to_device: &'a [Raw<AnyToDeviceEvent>],
+
+            /// Information on E2E device updates.
+            ///
+            /// This is the `org.matrix.msc3202.device_lists` field of [MSC3202].
+            ///
+            /// [MSC3202]: https://github.com/matrix-org/matrix-spec-proposals/pull/3202
+            #[cfg(feature = "unstable-msc3202")]
+            #[serde(
+                default,
+                skip_serializing_if = "DeviceLists::is_empty",
+                rename = "org.matrix.msc3202.device_lists"
+            )]
+            pub device_lists: DeviceLists,
+
+            /// The number of unclaimed one-time keys currently held on the server for this device,
+            /// for each user and device of the application service.
+            ///
+            /// This is the `org.matrix.msc3202.device_one_time_keys_count` field of [MSC3202].
+            ///
+            /// [MSC3202]: https://github.com/matrix-org/matrix-spec-proposals/pull/3202
+            #[cfg(feature = "unstable-msc3202")]
+            #[serde(
+                default,
+                skip_serializing_if = "BTreeMap::is_empty",
+                rename = "org.matrix.msc3202.device_one_time_keys_count"
+            )]
+            pub device_one_time_keys_count:
+                BTreeMap<OwnedUserId, BTreeMap<OwnedDeviceId, BTreeMap<DeviceKeyAlgorithm, UInt>>>,
+
+            /// The unused fallback key algorithms, for each user and device of the application
+            /// service.
+            ///
+            /// This is the `org.matrix.msc3202.device_unused_fallback_key_types` field of
+            /// [MSC3202].
+            ///
+            /// [MSC3202]: https://github.com/matrix-org/matrix-spec-proposals/pull/3202
+            #[cfg(feature = "unstable-msc3202")]
+            #[serde(
+                default,
+                skip_serializing_if = "BTreeMap::is_empty",
+                rename = "org.matrix.msc3202.device_unused_fallback_key_types"
+            )]
+            pub device_unused_fallback_key_types:
+                BTreeMap<OwnedUserId, BTreeMap<OwnedDeviceId, Vec<DeviceKeyAlgorithm>>>,
         }
 
         #[derive(Default)]
@@ -40,14 +138,40 @@ pub mod v1 {
     impl<'a> Request<'a> {
         /// Creates a new `Request` with the given transaction ID and list of events.
         pub fn new(txn_id: &'a TransactionId, events: &'a [Raw<AnyRoomEvent>]) -> Self {
-            Self { txn_id, events }
+            Self {
+                txn_id,
+                events,
+                #[cfg(feature = "unstable-msc2409")]
+                ephemeral: &[],
+                #[cfg(feature = "unstable-msc2409")]
+                to_device: &[],
+                #[cfg(feature = "unstable-msc3202")]
+                device_lists: DeviceLists::new(),
+                #[cfg(feature = "unstable-msc3202")]
+                device_one_time_keys_count: BTreeMap::new(),
+                #[cfg(feature = "unstable-msc3202")]
+                device_unused_fallback_key_types: BTreeMap::new(),
+            }
         }
     }
 
     impl IncomingRequest {
         /// Creates an `IncomingRequest` with the given transaction ID and list of events.
         pub fn new(txn_id: OwnedTransactionId, events: Vec<Raw<AnyRoomEvent>>) -> IncomingRequest {
-            IncomingRequest { txn_id, events }
+            IncomingRequest {
+                txn_id,
+                events,
+                #[cfg(feature = "unstable-msc2409")]
+                ephemeral: Vec::new(),
+                #[cfg(feature = "unstable-msc2409")]
+                to_device: Vec::new(),
+                #[cfg(feature = "unstable-msc3202")]
+                device_lists: DeviceLists::new(),
+                #[cfg(feature = "unstable-msc3202")]
+                device_one_time_keys_count: BTreeMap::new(),
+                #[cfg(feature = "unstable-msc3202")]
+                device_unused_fallback_key_types: BTreeMap::new(),
+            }
         }
 
         /// Consumes the `IncomingRequest` and tries to convert it to a `sync_events::Response`
@@ -60,9 +184,9 @@ pub mod v1 {
         /// a multitude of namespaces, and as such the Appservice basically only "observes joined
         /// rooms".
         ///
-        /// Note: Currently homeservers only push PDUs to appservices, no EDUs. There's the open
-        /// [MSC2409] regarding supporting EDUs in the future, though it seems to be planned to put
-        /// EDUs into a different JSON key than `events` to stay backwards compatible.
+        /// Note: Only the PDUs in `events` are converted. The EDUs of [MSC2409] are ignored, use
+        /// a [`TransactionHandler`](crate::event::push_events::v1::TransactionHandler) to access
+        /// them.
         ///
         /// [MSC2409]: https://github.com/matrix-org/matrix-spec-proposals/pull/2409
         #[cfg(feature = "helper")]
@@ -105,6 +229,270 @@ pub mod v1 {
         }
     }
 
+    /// Ephemeral data pushed to an application service.
+    ///
+    /// From [MSC2409].
+    ///
+    /// [MSC2409]: https://github.com/matrix-org/matrix-spec-proposals/pull/2409
+    #[cfg(feature = "unstable-msc2409")]
+    #[derive(Clone, Debug)]
+    #[allow(clippy::large_enum_variant)]
+    #[non_exhaustive]
+    pub enum EphemeralData {
+        /// A presence update.
+        Presence(PresenceEvent),
+
+        /// An ephemeral room event, i.e. a receipt or a typing notification.
+        Room(AnyEphemeralRoomEvent),
+    }
+
+    #[cfg(feature = "unstable-msc2409")]
+    impl<'de> Deserialize<'de> for EphemeralData {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct EphemeralDataDeHelper<'a> {
+                #[serde(rename = "type", borrow)]
+                ev_type: std::borrow::Cow<'a, str>,
+            }
+
+            let json = Box::<RawJsonValue>::deserialize(deserializer)?;
+            let EphemeralDataDeHelper { ev_type } =
+                serde_json::from_str(json.get()).map_err(de::Error::custom)?;
+
+            Ok(match &*ev_type {
+                "m.presence" => {
+                    Self::Presence(serde_json::from_str(json.get()).map_err(de::Error::custom)?)
+                }
+                _ => Self::Room(serde_json::from_str(json.get()).map_err(de::Error::custom)?),
+            })
+        }
+    }
+
+    /// Information on E2E device updates.
+    ///
+    /// From [MSC3202].
+    ///
+    /// [MSC3202]: https://github.com/matrix-org/matrix-spec-proposals/pull/3202
+    #[cfg(feature = "unstable-msc3202")]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    #[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+    pub struct DeviceLists {
+        /// List of users who have updated their device identity keys or who now
+        /// share an encrypted room with the application service.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub changed: Vec<OwnedUserId>,
+
+        /// List of users who no longer share encrypted rooms with the application service.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub left: Vec<OwnedUserId>,
+    }
+
+    #[cfg(feature = "unstable-msc3202")]
+    impl DeviceLists {
+        /// Creates an empty `DeviceLists`.
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        /// Returns true if there are no device list updates.
+        pub fn is_empty(&self) -> bool {
+            self.changed.is_empty() && self.left.is_empty()
+        }
+    }
+
+    /// The data of a transaction pushed to an application service, split by kind.
+    ///
+    /// Created by a [`TransactionHandler`].
+    #[cfg(feature = "helper")]
+    #[derive(Clone, Debug)]
+    #[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+    pub struct Transaction {
+        /// The transaction ID.
+        pub txn_id: OwnedTransactionId,
+
+        /// The message-like events of the transaction.
+        pub room_events: Vec<AnyMessageLikeEvent>,
+
+        /// The state events of the transaction.
+        pub state_events: Vec<AnyStateEvent>,
+
+        /// The ephemeral data of the transaction.
+        #[cfg(feature = "unstable-msc2409")]
+        pub ephemeral: Vec<EphemeralData>,
+
+        /// The to-device messages of the transaction.
+        #[cfg(feature = "unstable-msc2409")]
+        pub to_device: Vec<ToDeviceEvent>,
+
+        /// Information on E2E device updates.
+        #[cfg(feature = "unstable-msc3202")]
+        pub device_lists: DeviceLists,
+
+        /// The number of unclaimed one-time keys, for each user and device of the application
+        /// service.
+        #[cfg(feature = "unstable-msc3202")]
+        pub device_one_time_keys_count:
+            BTreeMap<OwnedUserId, BTreeMap<OwnedDeviceId, BTreeMap<DeviceKeyAlgorithm, UInt>>>,
+
+        /// The unused fallback key algorithms, for each user and device of the application
+        /// service.
+        #[cfg(feature = "unstable-msc3202")]
+        pub device_unused_fallback_key_types:
+            BTreeMap<OwnedUserId, BTreeMap<OwnedDeviceId, Vec<DeviceKeyAlgorithm>>>,
+    }
+
+    #[cfg(feature = "helper")]
+    impl Transaction {
+        /// Deserializes and splits the data of the given incoming request.
+        ///
+        /// The events that fail to deserialize are skipped, with a warning.
+        pub fn from_request(request: IncomingRequest) -> Self {
+            use tracing::warn;
+
+            let mut room_events = Vec::new();
+            let mut state_events = Vec::new();
+
+            for raw_event in request.events {
+                match raw_event.deserialize() {
+                    Ok(AnyRoomEvent::MessageLike(event)) => room_events.push(event),
+                    Ok(AnyRoomEvent::State(event)) => state_events.push(event),
+                    Err(error) => warn!(%error, "Skipping invalid event in transaction"),
+                }
+            }
+
+            #[cfg(feature = "unstable-msc2409")]
+            let ephemeral = request
+                .ephemeral
+                .into_iter()
+                .filter_map(|raw| match raw.deserialize() {
+                    Ok(data) => Some(data),
+                    Err(error) => {
+                        warn!(%error, "Skipping invalid ephemeral data in transaction");
+                        None
+                    }
+                })
+                .collect();
+
+            #[cfg(feature = "unstable-msc2409")]
+            let to_device = request
+                .to_device
+                .into_iter()
+                .filter_map(|raw| match ToDeviceEvent::from_raw(&raw) {
+                    Ok(event) => Some(event),
+                    Err(error) => {
+                        warn!(%error, "Skipping invalid to-device message in transaction");
+                        None
+                    }
+                })
+                .collect();
+
+            Self {
+                txn_id: request.txn_id,
+                room_events,
+                state_events,
+                #[cfg(feature = "unstable-msc2409")]
+                ephemeral,
+                #[cfg(feature = "unstable-msc2409")]
+                to_device,
+                #[cfg(feature = "unstable-msc3202")]
+                device_lists: request.device_lists,
+                #[cfg(feature = "unstable-msc3202")]
+                device_one_time_keys_count: request.device_one_time_keys_count,
+                #[cfg(feature = "unstable-msc3202")]
+                device_unused_fallback_key_types: request.device_unused_fallback_key_types,
+            }
+        }
+    }
+
+    /// A to-device message pushed to an application service, with its recipient.
+    ///
+    /// From [MSC2409].
+    ///
+    /// [MSC2409]: https://github.com/matrix-org/matrix-spec-proposals/pull/2409
+    #[cfg(all(feature = "helper", feature = "unstable-msc2409"))]
+    #[derive(Clone, Debug)]
+    #[allow(clippy::exhaustive_structs)]
+    pub struct ToDeviceEvent {
+        /// The user that should receive the message.
+        pub to_user_id: OwnedUserId,
+
+        /// The device that should receive the message.
+        pub to_device_id: OwnedDeviceId,
+
+        /// The message.
+        pub event: AnyToDeviceEvent,
+    }
+
+    #[cfg(all(feature = "helper", feature = "unstable-msc2409"))]
+    impl ToDeviceEvent {
+        fn from_raw(raw: &Raw<AnyToDeviceEvent>) -> serde_json::Result<Self> {
+            #[derive(Deserialize)]
+            struct RecipientDeHelper {
+                to_user_id: OwnedUserId,
+                to_device_id: OwnedDeviceId,
+            }
+
+            let RecipientDeHelper { to_user_id, to_device_id } = raw.deserialize_as()?;
+            Ok(Self { to_user_id, to_device_id, event: raw.deserialize()? })
+        }
+    }
+
+    /// Turns incoming requests into [`Transaction`]s, and ignores the transactions that were
+    /// already handled.
+    ///
+    /// The homeserver retries to push a transaction until the application service responds to it,
+    /// so the same transaction can be received several times.
+    #[cfg(feature = "helper")]
+    #[derive(Clone, Debug)]
+    pub struct TransactionHandler {
+        capacity: usize,
+        handled: BTreeSet<OwnedTransactionId>,
+        order: VecDeque<OwnedTransactionId>,
+    }
+
+    #[cfg(feature = "helper")]
+    impl TransactionHandler {
+        /// Creates a new `TransactionHandler` that remembers the IDs of the last `capacity`
+        /// transactions.
+        pub fn new(capacity: usize) -> Self {
+            Self { capacity, handled: BTreeSet::new(), order: VecDeque::new() }
+        }
+
+        /// Splits the given incoming request into a [`Transaction`].
+        ///
+        /// Returns `None` if a transaction with the same ID was already handled. The application
+        /// service should still respond successfully to the request in that case.
+        pub fn handle(&mut self, request: IncomingRequest) -> Option<Transaction> {
+            if self.capacity == 0 {
+                return Some(Transaction::from_request(request));
+            }
+
+            if !self.handled.insert(request.txn_id.clone()) {
+                return None;
+            }
+
+            self.order.push_back(request.txn_id.clone());
+            if self.order.len() > self.capacity {
+                if let Some(txn_id) = self.order.pop_front() {
+                    self.handled.remove(&txn_id);
+                }
+            }
+
+            Some(Transaction::from_request(request))
+        }
+    }
+
+    #[cfg(feature = "helper")]
+    impl Default for TransactionHandler {
+        /// Creates a `TransactionHandler` that remembers the IDs of the last 1000 transactions.
+        fn default() -> Self {
+            Self::new(1000)
+        }
+    }
+
     #[cfg(feature = "helper")]
     #[cfg(test)]
     mod helper_tests {
@@ -112,7 +500,7 @@ pub mod v1 {
         use ruma_common::{room_id, TransactionId};
         use serde_json::{json, value::to_raw_value as to_raw_json_value};
 
-        use super::{IncomingRequest, Raw};
+        use super::{IncomingRequest, Raw, TransactionHandler};
 
         #[test]
         fn convert_incoming_request_to_sync_response() {
@@ -146,7 +534,7 @@ pub mod v1 {
             );
 
             let events = vec![state_event, message_event];
-            let incoming_request = IncomingRequest { txn_id: txn_id.into(), events };
+            let incoming_request = IncomingRequest::new(txn_id.into(), events);
 
             let response: sync_events::v3::Response =
                 incoming_request.try_into_sync_response("token").unwrap();
@@ -159,6 +547,106 @@ pub mod v1 {
 
             assert_eq!(response_rooms_join.timeline.events.len(), 2);
         }
+
+        #[test]
+        fn transaction_handler_splits_and_deduplicates() {
+            let events = vec![
+                Raw::from_json(
+                    to_raw_json_value(&json!({
+                        "content": { "name": "Room" },
+                        "event_id": "$h29iv0s8:example.com",
+                        "origin_server_ts": 1,
+                        "room_id": "!roomid:room.com",
+                        "sender": "@carl:example.com",
+                        "state_key": "",
+                        "type": "m.room.name"
+                    }))
+                    .unwrap(),
+                ),
+                Raw::from_json(
+                    to_raw_json_value(&json!({
+                        "content": { "body": "test", "msgtype": "m.text" },
+                        "event_id": "$143273582443PhrSn:example.com",
+                        "origin_server_ts": 1,
+                        "room_id": "!roomid:room.com",
+                        "sender": "@user:example.com",
+                        "type": "m.room.message"
+                    }))
+                    .unwrap(),
+                ),
+                Raw::from_json(to_raw_json_value(&json!({ "type": "m.room.message" })).unwrap()),
+            ];
+
+            let mut handler = TransactionHandler::new(1);
+
+            let request = IncomingRequest::new("txn_1".into(), events.clone());
+            let transaction = handler.handle(request).unwrap();
+            assert_eq!(transaction.txn_id, "txn_1");
+            assert_eq!(transaction.room_events.len(), 1);
+            assert_eq!(transaction.state_events.len(), 1);
+
+            assert!(handler.handle(IncomingRequest::new("txn_1".into(), events.clone())).is_none());
+            assert!(handler.handle(IncomingRequest::new("txn_2".into(), Vec::new())).is_some());
+
+            // Only the last transaction is remembered.
+            assert!(handler.handle(IncomingRequest::new("txn_1".into(), events)).is_some());
+        }
+    }
+
+    #[cfg(all(feature = "helper", feature = "unstable-msc2409"))]
+    #[cfg(test)]
+    mod msc2409_tests {
+        use matches::assert_matches;
+        use ruma_common::{api::IncomingRequest as _, events::AnyEphemeralRoomEvent};
+        use serde_json::{json, to_vec as to_json_vec};
+
+        use super::{EphemeralData, IncomingRequest, TransactionHandler};
+
+        #[test]
+        fn deserialize_ephemeral_and_to_device() {
+            let body = json!({
+                "events": [],
+                "ephemeral": [
+                    {
+                        "type": "m.typing",
+                        "room_id": "!roomid:room.com",
+                        "content": { "user_ids": ["@alice:example.com"] }
+                    },
+                    {
+                        "type": "m.presence",
+                        "sender": "@alice:example.com",
+                        "content": { "presence": "online" }
+                    }
+                ],
+                "de.sorunome.msc2409.to_device": [
+                    {
+                        "type": "m.dummy",
+                        "sender": "@alice:example.com",
+                        "to_user_id": "@_bridge_bob:example.com",
+                        "to_device_id": "DEVICE",
+                        "content": {}
+                    }
+                ]
+            });
+            let http_request = http::Request::builder()
+                .method("PUT")
+                .uri("https://appservice.tld/_matrix/app/v1/transactions/txn_1?access_token=hs_token")
+                .body(to_json_vec(&body).unwrap())
+                .unwrap();
+
+            let request = IncomingRequest::try_from_http_request(http_request, &["txn_1"]).unwrap();
+            assert_eq!(request.ephemeral.len(), 2);
+            assert_eq!(request.to_device.len(), 1);
+
+            let transaction = TransactionHandler::default().handle(request).unwrap();
+            assert_matches!(
+                transaction.ephemeral.as_slice(),
+                [EphemeralData::Room(AnyEphemeralRoomEvent::Typing(_)), EphemeralData::Presence(_)]
+            );
+            assert_eq!(transaction.to_device.len(), 1);
+            assert_eq!(transaction.to_device[0].to_user_id, "@_bridge_bob:example.com");
+            assert_eq!(transaction.to_device[0].to_device_id, "DEVICE");
+        }
     }
 
     #[cfg(feature = "server")]
@@ -185,7 +673,7 @@ pub mod v1 {
             .unwrap();
             let events = vec![dummy_event];
 
-            let req = Request { events: &events, txn_id: "any_txn_id".into() }
+            let req = Request::new("any_txn_id".into(), &events)
                 .try_into_http_request::<Vec<u8>>(
                     "https://homeserver.tld",
                     SendAccessToken::IfRequired("auth_tok"),
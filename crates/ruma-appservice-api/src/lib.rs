@@ -10,9 +10,16 @@
 use serde::{Deserialize, Serialize};
 
 pub mod event;
+#[cfg(feature = "unstable-msc2659")]
+pub mod ping;
 pub mod query;
+#[cfg(feature = "registration")]
+mod registration;
 pub mod thirdparty;
 
+#[cfg(feature = "registration")]
+pub use registration::{NamespaceRegex, RegistrationError, RegistrationMatcher};
+
 /// A namespace defined by an application service.
 ///
 /// Used for [appservice registration](https://spec.matrix.org/v1.2/application-service-api/#registration).
@@ -63,7 +70,11 @@ impl Namespaces {
 /// Information required in the registration yaml file that a homeserver needs.
 ///
 /// To create an instance of this type, first create a `RegistrationInit` and convert it via
-/// `Registration::from` / `.into()`.
+/// `Registration::from` / `.into()`, or load a registration file with `Registration::from_yaml`
+/// (available with the `registration` feature).
+///
+/// To know whether an identifier is in the namespaces of the application service, use
+/// `Registration::matcher` (available with the `registration` feature).
 ///
 /// Used for [appservice registration](https://spec.matrix.org/v1.2/application-service-api/#registration).
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -73,7 +84,9 @@ pub struct Registration {
     pub id: String,
 
     /// The URL for the application service.
-    pub url: String,
+    ///
+    /// `None` if the application service doesn't want to receive any traffic from the homeserver.
+    pub url: Option<String>,
 
     /// A unique token for application services to use to authenticate requests to Homeservers.
     pub as_token: String,
@@ -111,7 +124,9 @@ pub struct RegistrationInit {
     pub id: String,
 
     /// The URL for the application service.
-    pub url: String,
+    ///
+    /// `None` if the application service doesn't want to receive any traffic from the homeserver.
+    pub url: Option<String>,
 
     /// A unique token for application services to use to authenticate requests to Homeservers.
     pub as_token: String,
@@ -0,0 +1,7 @@
+//! Endpoint for pinging the application service.
+//!
+//! See [MSC2659] for more details.
+//!
+//! [MSC2659]: https://github.com/matrix-org/matrix-spec-proposals/pull/2659
+
+pub mod send_ping;
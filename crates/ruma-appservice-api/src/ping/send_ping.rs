@@ -0,0 +1,45 @@
+//! `POST /_matrix/app/*/ping`
+
+pub mod unstable {
+    //! `/unstable/` ([MSC])
+    //!
+    //! [MSC]: https://github.com/matrix-org/matrix-spec-proposals/pull/2659
+
+    use ruma_common::{api::ruma_api, OwnedTransactionId};
+
+    ruma_api! {
+        metadata: {
+            description: "Endpoint to ping the application service.",
+            method: POST,
+            name: "send_ping",
+            unstable_path: "/_matrix/app/unstable/fi.mau.msc2659/ping",
+            rate_limited: false,
+            authentication: AccessToken,
+        }
+
+        #[derive(Default)]
+        request: {
+            /// A transaction ID for the ping, copied directly from the client-server API request
+            /// that triggered it.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub transaction_id: Option<OwnedTransactionId>,
+        }
+
+        #[derive(Default)]
+        response: {}
+    }
+
+    impl Request {
+        /// Creates a new empty `Request`.
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    impl Response {
+        /// Creates an empty `Response`.
+        pub fn new() -> Self {
+            Self {}
+        }
+    }
+}
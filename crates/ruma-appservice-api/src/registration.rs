@@ -0,0 +1,295 @@
+//! Matching of identifiers against the namespaces of an application service, and loading of
+//! registration files.
+
+use std::{error::Error as StdError, fmt};
+
+use regex::RegexSet;
+use ruma_common::{RoomAliasId, RoomId, UserId};
+
+use crate::{Namespace, Registration};
+
+/// The compiled regular expressions of a list of [`Namespace`]s.
+///
+/// The regular expressions are anchored, i.e. they must match the whole value and not only a part
+/// of it, like `^(?:regex)$`.
+#[derive(Clone, Debug)]
+pub struct NamespaceRegex {
+    exclusive: Option<RegexSet>,
+    non_exclusive: Option<RegexSet>,
+}
+
+impl NamespaceRegex {
+    /// Compiles the regular expressions of the given namespaces.
+    ///
+    /// Returns an error if one of the regular expressions is invalid.
+    pub fn new(namespaces: &[Namespace]) -> Result<Self, regex::Error> {
+        let mut exclusive = Vec::new();
+        let mut non_exclusive = Vec::new();
+
+        for namespace in namespaces {
+            if namespace.exclusive {
+                exclusive.push(&namespace.regex);
+            } else {
+                non_exclusive.push(&namespace.regex);
+            }
+        }
+
+        Ok(Self { exclusive: regex_set(exclusive)?, non_exclusive: regex_set(non_exclusive)? })
+    }
+
+    /// Whether the given value matches any of the namespaces.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.is_exclusive_match(value)
+            || self.non_exclusive.as_ref().map_or(false, |set| set.is_match(value))
+    }
+
+    /// Whether the given value matches any of the exclusive namespaces.
+    pub fn is_exclusive_match(&self, value: &str) -> bool {
+        self.exclusive.as_ref().map_or(false, |set| set.is_match(value))
+    }
+}
+
+fn regex_set(patterns: Vec<&String>) -> Result<Option<RegexSet>, regex::Error> {
+    if patterns.is_empty() {
+        Ok(None)
+    } else {
+        RegexSet::new(patterns.into_iter().map(|pattern| format!("^(?:{})$", pattern))).map(Some)
+    }
+}
+
+/// A [`Registration`] with the compiled regular expressions of its namespaces.
+///
+/// This is what a homeserver or an application service uses to know whether a user, a room alias
+/// or a room is in the namespaces of the application service.
+#[derive(Clone, Debug)]
+pub struct RegistrationMatcher {
+    registration: Registration,
+    users: NamespaceRegex,
+    aliases: NamespaceRegex,
+    rooms: NamespaceRegex,
+}
+
+impl RegistrationMatcher {
+    /// Compiles the regular expressions of the namespaces of the given registration.
+    ///
+    /// Returns an error if one of the regular expressions is invalid.
+    pub fn new(registration: Registration) -> Result<Self, regex::Error> {
+        let users = NamespaceRegex::new(&registration.namespaces.users)?;
+        let aliases = NamespaceRegex::new(&registration.namespaces.aliases)?;
+        let rooms = NamespaceRegex::new(&registration.namespaces.rooms)?;
+
+        Ok(Self { registration, users, aliases, rooms })
+    }
+
+    /// The registration of the application service.
+    pub fn registration(&self) -> &Registration {
+        &self.registration
+    }
+
+    /// Consumes the `RegistrationMatcher` and returns the registration of the application service.
+    pub fn into_registration(self) -> Registration {
+        self.registration
+    }
+
+    /// Whether the given user ID is in the `users` namespaces.
+    pub fn is_user_match(&self, user_id: &UserId) -> bool {
+        self.users.is_match(user_id.as_str())
+    }
+
+    /// Whether the given user ID is in an exclusive `users` namespace.
+    pub fn is_exclusive_user(&self, user_id: &UserId) -> bool {
+        self.users.is_exclusive_match(user_id.as_str())
+    }
+
+    /// Whether the given room alias is in the `aliases` namespaces.
+    pub fn is_alias_match(&self, alias: &RoomAliasId) -> bool {
+        self.aliases.is_match(alias.as_str())
+    }
+
+    /// Whether the given room alias is in an exclusive `aliases` namespace.
+    pub fn is_exclusive_alias(&self, alias: &RoomAliasId) -> bool {
+        self.aliases.is_exclusive_match(alias.as_str())
+    }
+
+    /// Whether the given room ID is in the `rooms` namespaces.
+    pub fn is_room_match(&self, room_id: &RoomId) -> bool {
+        self.rooms.is_match(room_id.as_str())
+    }
+
+    /// Whether the given room ID is in an exclusive `rooms` namespace.
+    pub fn is_exclusive_room(&self, room_id: &RoomId) -> bool {
+        self.rooms.is_exclusive_match(room_id.as_str())
+    }
+}
+
+impl Registration {
+    /// Creates a [`RegistrationMatcher`] to match identifiers against the namespaces of this
+    /// registration.
+    ///
+    /// Returns an error if one of the regular expressions of the namespaces is invalid.
+    pub fn matcher(self) -> Result<RegistrationMatcher, regex::Error> {
+        RegistrationMatcher::new(self)
+    }
+
+    /// Checks that the regular expressions of the namespaces of this registration are valid.
+    pub fn validate(&self) -> Result<(), regex::Error> {
+        let namespaces = &self.namespaces;
+        for namespace in namespaces.users.iter().chain(&namespaces.aliases).chain(&namespaces.rooms)
+        {
+            regex::Regex::new(&namespace.regex)?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads a registration from the contents of a YAML registration file.
+    ///
+    /// Returns an error if the YAML is invalid, or if one of the regular expressions of the
+    /// namespaces is invalid.
+    pub fn from_yaml(yaml: &str) -> Result<Self, RegistrationError> {
+        let registration: Self = serde_yaml::from_str(yaml).map_err(RegistrationError::Yaml)?;
+        registration.validate().map_err(RegistrationError::Regex)?;
+
+        Ok(registration)
+    }
+
+    /// Serializes this registration to the contents of a YAML registration file.
+    pub fn to_yaml(&self) -> Result<String, RegistrationError> {
+        serde_yaml::to_string(self).map_err(RegistrationError::Yaml)
+    }
+}
+
+/// An error when loading or saving a registration file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RegistrationError {
+    /// The YAML registration file could not be (de)serialized.
+    Yaml(serde_yaml::Error),
+
+    /// A regular expression of a namespace is invalid.
+    Regex(regex::Error),
+}
+
+impl fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml(e) => write!(f, "invalid YAML registration: {}", e),
+            Self::Regex(e) => write!(f, "invalid namespace regex: {}", e),
+        }
+    }
+}
+
+impl StdError for RegistrationError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Yaml(e) => Some(e),
+            Self::Regex(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma_common::{room_alias_id, room_id, user_id};
+
+    use super::RegistrationError;
+    use crate::{Namespace, Namespaces, Registration, RegistrationInit};
+
+    fn registration(namespaces: Namespaces) -> Registration {
+        RegistrationInit {
+            id: "IRC Bridge".to_owned(),
+            url: None,
+            as_token: "as_token".to_owned(),
+            hs_token: "hs_token".to_owned(),
+            sender_localpart: "_irc_bot".to_owned(),
+            namespaces,
+            rate_limited: None,
+            protocols: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn namespace_matching() {
+        let mut namespaces = Namespaces::new();
+        namespaces.users = vec![
+            Namespace::new(true, "@_irc_bridge_.*:example\\.org".to_owned()),
+            Namespace::new(false, "@irc_.*".to_owned()),
+        ];
+        namespaces.aliases = vec![Namespace::new(false, "#_irc_.*".to_owned())];
+
+        let matcher = registration(namespaces).matcher().unwrap();
+
+        assert!(matcher.is_user_match(user_id!("@_irc_bridge_alice:example.org")));
+        assert!(matcher.is_exclusive_user(user_id!("@_irc_bridge_alice:example.org")));
+        assert!(matcher.is_user_match(user_id!("@irc_bob:example.org")));
+        assert!(!matcher.is_exclusive_user(user_id!("@irc_bob:example.org")));
+        assert!(!matcher.is_user_match(user_id!("@carl:example.org")));
+
+        assert!(matcher.is_alias_match(room_alias_id!("#_irc_matrix:example.org")));
+        assert!(!matcher.is_exclusive_alias(room_alias_id!("#_irc_matrix:example.org")));
+        assert!(!matcher.is_alias_match(room_alias_id!("#matrix:example.org")));
+
+        assert!(!matcher.is_room_match(room_id!("!room:example.org")));
+        assert!(!matcher.is_exclusive_room(room_id!("!room:example.org")));
+    }
+
+    #[test]
+    fn namespace_matching_is_anchored() {
+        let mut namespaces = Namespaces::new();
+        namespaces.users = vec![Namespace::new(true, "@bot:example\\.org".to_owned())];
+        namespaces.rooms =
+            vec![Namespace::new(false, "!room:example\\.org|!other:example\\.org".to_owned())];
+
+        let matcher = registration(namespaces).matcher().unwrap();
+
+        assert!(matcher.is_exclusive_user(user_id!("@bot:example.org")));
+        assert!(!matcher.is_user_match(user_id!("@bot:example.org.evil.com")));
+
+        assert!(matcher.is_room_match(room_id!("!room:example.org")));
+        assert!(matcher.is_room_match(room_id!("!other:example.org")));
+        assert!(!matcher.is_room_match(room_id!("!room:example.org.evil.com")));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let mut namespaces = Namespaces::new();
+        namespaces.rooms = vec![Namespace::new(true, "!(unclosed".to_owned())];
+        let registration = registration(namespaces);
+
+        assert!(registration.validate().is_err());
+        assert!(registration.matcher().is_err());
+    }
+
+    #[test]
+    fn yaml_roundtrip() {
+        let mut namespaces = Namespaces::new();
+        namespaces.users = vec![Namespace::new(true, "@_irc_bridge_.*".to_owned())];
+        let registration = registration(namespaces);
+
+        let yaml = registration.to_yaml().unwrap();
+        assert!(yaml.contains("url: ~"));
+
+        let loaded = Registration::from_yaml(&yaml).unwrap();
+        assert_eq!(loaded.id, "IRC Bridge");
+        assert_eq!(loaded.url, None);
+        assert_eq!(loaded.namespaces.users, registration.namespaces.users);
+    }
+
+    #[test]
+    fn yaml_invalid_regex() {
+        let yaml = r#"
+            id: "IRC Bridge"
+            url: "http://127.0.0.1:1234"
+            as_token: "as_token"
+            hs_token: "hs_token"
+            sender_localpart: "_irc_bot"
+            namespaces:
+              users:
+                - exclusive: true
+                  regex: "@_irc_bridge_[.*"
+            "#;
+
+        assert!(matches!(Registration::from_yaml(yaml), Err(RegistrationError::Regex(_))));
+    }
+}
@@ -33,7 +33,7 @@ fn registration_deserialization() {
             ..
         }
         if id == "IRC Bridge"
-            && url == "http://127.0.0.1:1234"
+            && url.as_deref() == Some("http://127.0.0.1:1234")
             && as_token == "30c05ae90a248a4188e620216fa72e349803310ec83e2a77b34fe90be6081f46"
             && hs_token == "312df522183efd404ec1cd22d2ffa4bbc76a8c1ccf541dd692eef281356bb74e"
             && sender_localpart == "_irc_bot"
@@ -60,6 +60,6 @@ fn config_with_optional_url() {
         "#;
     assert_matches!(
         serde_yaml::from_str(registration_config).unwrap(),
-        Registration { url, .. } if url == "null"
+        Registration { url: None, .. }
     );
 }
@@ -1,3 +1,5 @@
 //! Endpoints part of the application service extension of the client-server API
 
+#[cfg(feature = "unstable-msc2659")]
+pub mod request_ping;
 pub mod set_room_visibility;
@@ -0,0 +1,58 @@
+//! `POST /_matrix/client/*/appservice/{appserviceId}/ping`
+
+pub mod unstable {
+    //! `/unstable/` ([MSC])
+    //!
+    //! [MSC]: https://github.com/matrix-org/matrix-spec-proposals/pull/2659
+
+    use std::time::Duration;
+
+    use ruma_common::{api::ruma_api, TransactionId};
+
+    ruma_api! {
+        metadata: {
+            description: "Ask the homeserver to ping the application service to ensure the connection works.",
+            method: POST,
+            name: "request_ping",
+            unstable_path: "/_matrix/client/unstable/fi.mau.msc2659/appservice/:appservice_id/ping",
+            rate_limited: false,
+            authentication: AccessToken,
+        }
+
+        request: {
+            /// The appservice ID of the appservice to ping.
+            ///
+            /// This must be the same as the appservice whose `as_token` is being used to
+            /// authenticate the request.
+            #[ruma_api(path)]
+            pub appservice_id: &'a str,
+
+            /// An optional transaction ID that is passed through to the `/_matrix/app/*/ping` call.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub transaction_id: Option<&'a TransactionId>,
+        }
+
+        response: {
+            /// The duration in milliseconds that the `/_matrix/app/*/ping` request took from the
+            /// homeserver's point of view.
+            #[serde(with = "ruma_common::serde::duration::ms", rename = "duration_ms")]
+            pub duration: Duration,
+        }
+
+        error: crate::Error
+    }
+
+    impl<'a> Request<'a> {
+        /// Creates a new `Request` with the given appservice ID.
+        pub fn new(appservice_id: &'a str) -> Self {
+            Self { appservice_id, transaction_id: None }
+        }
+    }
+
+    impl Response {
+        /// Creates an `Response` with the given duration.
+        pub fn new(duration: Duration) -> Self {
+            Self { duration }
+        }
+    }
+}
@@ -52,7 +52,7 @@ impl Default for EventFormat {
 /// string with `::from() / .into()`. To check for formats that are not available as a documented
 /// variant here, use its string representation, obtained through `.as_str()`.
 #[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
-#[cfg(feature = "unstable-msc3440")]
+#[cfg(any(feature = "unstable-msc2675", feature = "unstable-msc3440"))]
 #[non_exhaustive]
 pub enum RelationType {
     /// `m.annotation`, an annotation, principally used by reactions.
@@ -73,7 +73,7 @@ pub enum RelationType {
     _Custom(PrivOwnedStr),
 }
 
-#[cfg(feature = "unstable-msc3440")]
+#[cfg(any(feature = "unstable-msc2675", feature = "unstable-msc3440"))]
 impl RelationType {
     /// Creates a string slice from this `RelationType`.
     pub fn as_str(&self) -> &str {
@@ -30,6 +30,8 @@ pub mod push;
 pub mod read_marker;
 pub mod receipt;
 pub mod redact;
+#[cfg(feature = "unstable-msc2675")]
+pub mod relations;
 pub mod room;
 pub mod search;
 pub mod server;
@@ -39,6 +41,8 @@ pub mod state;
 pub mod sync;
 pub mod tag;
 pub mod thirdparty;
+#[cfg(feature = "unstable-msc3440")]
+pub mod threads;
 pub mod to_device;
 pub mod typing;
 pub mod uiaa;
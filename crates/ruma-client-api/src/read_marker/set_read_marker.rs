@@ -36,6 +36,21 @@ pub mod v3 {
             /// save that extra call.
             #[serde(rename = "m.read", skip_serializing_if = "Option::is_none")]
             pub read_receipt: Option<&'a EventId>,
+
+            /// The event ID to set the private read receipt location at.
+            ///
+            /// This is equivalent to calling the create_read_receipt endpoint with the
+            /// `m.read.private` receipt type, and is provided here to save that extra call.
+            ///
+            /// This uses the unstable prefix in
+            /// [MSC2285](https://github.com/matrix-org/matrix-spec-proposals/pull/2285).
+            #[cfg(feature = "unstable-msc2285")]
+            #[serde(
+                rename = "org.matrix.msc2285.read.private",
+                alias = "m.read.private",
+                skip_serializing_if = "Option::is_none"
+            )]
+            pub private_read_receipt: Option<&'a EventId>,
         }
 
         #[derive(Default)]
@@ -47,7 +62,13 @@ pub mod v3 {
     impl<'a> Request<'a> {
         /// Creates a new `Request` with the given room ID and fully read event ID.
         pub fn new(room_id: &'a RoomId, fully_read: &'a EventId) -> Self {
-            Self { room_id, fully_read, read_receipt: None }
+            Self {
+                room_id,
+                fully_read,
+                read_receipt: None,
+                #[cfg(feature = "unstable-msc2285")]
+                private_read_receipt: None,
+            }
         }
     }
 
@@ -57,4 +78,68 @@ pub mod v3 {
             Self {}
         }
     }
+
+    #[cfg(all(test, feature = "client", feature = "server", feature = "unstable-msc2285"))]
+    mod tests {
+        use ruma_common::{
+            api::{IncomingRequest as _, MatrixVersion, OutgoingRequest, SendAccessToken},
+            event_id, room_id,
+        };
+        use serde_json::{from_slice as from_json_slice, json, Value as JsonValue};
+
+        use super::{IncomingRequest, Request};
+
+        #[test]
+        fn private_read_receipt_roundtrip() {
+            let mut req =
+                Request::new(room_id!("!room:example.org"), event_id!("$fully_read:example.org"));
+            req.private_read_receipt = Some(event_id!("$private:example.org"));
+
+            let http_req = req
+                .try_into_http_request::<Vec<u8>>(
+                    "https://homeserver.tld",
+                    SendAccessToken::IfRequired("auth_tok"),
+                    &[MatrixVersion::V1_1],
+                )
+                .unwrap();
+            assert_eq!(
+                from_json_slice::<JsonValue>(http_req.body()).unwrap(),
+                json!({
+                    "m.fully_read": "$fully_read:example.org",
+                    "org.matrix.msc2285.read.private": "$private:example.org",
+                })
+            );
+
+            let incoming =
+                IncomingRequest::try_from_http_request(http_req, &["!room:example.org"]).unwrap();
+            assert_eq!(incoming.fully_read, "$fully_read:example.org");
+            assert_eq!(incoming.read_receipt, None);
+            assert_eq!(
+                incoming.private_read_receipt.as_deref(),
+                Some(event_id!("$private:example.org"))
+            );
+        }
+
+        #[test]
+        fn deserialize_stable_private_read_receipt() {
+            let body = json!({
+                "m.fully_read": "$fully_read:example.org",
+                "m.read.private": "$private:example.org",
+            });
+            let http_req = http::Request::builder()
+                .method("POST")
+                .uri(
+                    "https://homeserver.tld/_matrix/client/v3/rooms/!room:example.org/read_markers",
+                )
+                .body(serde_json::to_vec(&body).unwrap())
+                .unwrap();
+
+            let incoming =
+                IncomingRequest::try_from_http_request(http_req, &["!room:example.org"]).unwrap();
+            assert_eq!(
+                incoming.private_read_receipt.as_deref(),
+                Some(event_id!("$private:example.org"))
+            );
+        }
+    }
 }
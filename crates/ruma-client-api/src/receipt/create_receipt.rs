@@ -5,6 +5,8 @@ pub mod v3 {
     //!
     //! [spec]: https://spec.matrix.org/v1.2/client-server-api/#post_matrixclientv3roomsroomidreceiptreceipttypeeventid
 
+    #[cfg(feature = "unstable-msc3771")]
+    use ruma_common::receipt::ReceiptThread;
     use ruma_common::{api::ruma_api, receipt::ReceiptType, EventId, RoomId};
 
     ruma_api! {
@@ -31,6 +33,19 @@ pub mod v3 {
             /// The event ID to acknowledge up to.
             #[ruma_api(path)]
             pub event_id: &'a EventId,
+
+            /// The thread this receipt applies to.
+            ///
+            /// This is defined in [MSC3771].
+            ///
+            /// [MSC3771]: https://github.com/matrix-org/matrix-spec-proposals/pull/3771
+            #[cfg(feature = "unstable-msc3771")]
+            #[serde(
+                rename = "thread_id",
+                default,
+                skip_serializing_if = "ruma_common::serde::is_default"
+            )]
+            pub thread: ReceiptThread,
         }
 
         #[derive(Default)]
@@ -42,7 +57,13 @@ pub mod v3 {
     impl<'a> Request<'a> {
         /// Creates a new `Request` with the given room ID, receipt type and event ID.
         pub fn new(room_id: &'a RoomId, receipt_type: ReceiptType, event_id: &'a EventId) -> Self {
-            Self { room_id, receipt_type, event_id }
+            Self {
+                room_id,
+                receipt_type,
+                event_id,
+                #[cfg(feature = "unstable-msc3771")]
+                thread: ReceiptThread::default(),
+            }
         }
     }
 
@@ -52,4 +73,79 @@ pub mod v3 {
             Self {}
         }
     }
+
+    #[cfg(all(test, feature = "client", feature = "server", feature = "unstable-msc3771"))]
+    mod tests {
+        use ruma_common::{
+            api::{IncomingRequest as _, MatrixVersion, OutgoingRequest, SendAccessToken},
+            event_id,
+            receipt::{ReceiptThread, ReceiptType},
+            room_id,
+        };
+        use serde_json::{from_slice as from_json_slice, json, Value as JsonValue};
+
+        use super::{IncomingRequest, Request};
+
+        #[test]
+        fn threaded_receipt_roundtrip() {
+            let mut req = Request::new(
+                room_id!("!room:example.org"),
+                ReceiptType::Read,
+                event_id!("$event:example.org"),
+            );
+            req.thread = ReceiptThread::Thread(event_id!("$thread_root:example.org").to_owned());
+
+            let http_req = req
+                .try_into_http_request::<Vec<u8>>(
+                    "https://homeserver.tld",
+                    SendAccessToken::IfRequired("auth_tok"),
+                    &[MatrixVersion::V1_1],
+                )
+                .unwrap();
+            assert_eq!(
+                http_req.uri().path(),
+                "/_matrix/client/v3/rooms/%21room%3Aexample%2Eorg/receipt/m%2Eread/%24event%3Aexample%2Eorg"
+            );
+            assert_eq!(
+                from_json_slice::<JsonValue>(http_req.body()).unwrap(),
+                json!({ "thread_id": "$thread_root:example.org" })
+            );
+
+            let incoming = IncomingRequest::try_from_http_request(
+                http_req,
+                &["!room:example.org", "m.read", "$event:example.org"],
+            )
+            .unwrap();
+            assert_eq!(incoming.receipt_type, ReceiptType::Read);
+            assert_eq!(
+                incoming.thread,
+                ReceiptThread::Thread(event_id!("$thread_root:example.org").to_owned())
+            );
+        }
+
+        #[test]
+        fn unthreaded_receipt_roundtrip() {
+            let req = Request::new(
+                room_id!("!room:example.org"),
+                ReceiptType::Read,
+                event_id!("$event:example.org"),
+            );
+
+            let http_req = req
+                .try_into_http_request::<Vec<u8>>(
+                    "https://homeserver.tld",
+                    SendAccessToken::IfRequired("auth_tok"),
+                    &[MatrixVersion::V1_1],
+                )
+                .unwrap();
+            assert_eq!(from_json_slice::<JsonValue>(http_req.body()).unwrap(), json!({}));
+
+            let incoming = IncomingRequest::try_from_http_request(
+                http_req,
+                &["!room:example.org", "m.read", "$event:example.org"],
+            )
+            .unwrap();
+            assert_eq!(incoming.thread, ReceiptThread::Unthreaded);
+        }
+    }
 }
@@ -0,0 +1,9 @@
+//! Endpoints for event relationships.
+//!
+//! See [MSC2675] for more details about relations.
+//!
+//! [MSC2675]: https://github.com/matrix-org/matrix-spec-proposals/pull/2675
+
+pub mod get_relating_events;
+pub mod get_relating_events_with_rel_type;
+pub mod get_relating_events_with_rel_type_and_event_type;
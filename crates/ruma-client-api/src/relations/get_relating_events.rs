@@ -0,0 +1,177 @@
+//! `GET /_matrix/client/*/rooms/{roomId}/relations/{eventId}`
+
+pub mod unstable {
+    //! `/unstable/` ([MSC])
+    //!
+    //! [MSC]: https://github.com/matrix-org/matrix-spec-proposals/pull/2675
+
+    use js_int::UInt;
+    use ruma_common::{api::ruma_api, events::AnyMessageLikeEvent, serde::Raw, EventId, RoomId};
+
+    ruma_api! {
+        metadata: {
+            description: "Get the child events for a given parent event.",
+            method: GET,
+            name: "get_relating_events",
+            unstable_path: "/_matrix/client/unstable/rooms/:room_id/relations/:event_id",
+            rate_limited: false,
+            authentication: AccessToken,
+        }
+
+        request: {
+            /// The ID of the room containing the parent event.
+            #[ruma_api(path)]
+            pub room_id: &'a RoomId,
+
+            /// The ID of the parent event whose child events are to be returned.
+            #[ruma_api(path)]
+            pub event_id: &'a EventId,
+
+            /// The pagination token to start returning results from.
+            ///
+            /// If `None`, results start at the most recent topological event known to the server.
+            ///
+            /// Can be a `next_batch` token from a previous call, or a returned `start` token from
+            /// `/messages` or a `next_batch` token from `/sync`.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[ruma_api(query)]
+            pub from: Option<&'a str>,
+
+            /// The pagination token to stop returning results at.
+            ///
+            /// If `None`, results continue up to `limit` or until there are no more events.
+            ///
+            /// Like `from`, this can be a previous token from a prior call to this endpoint or
+            /// from `/messages` or `/sync`.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[ruma_api(query)]
+            pub to: Option<&'a str>,
+
+            /// The maximum number of results to return in a single `chunk`.
+            ///
+            /// The server can and should apply a maximum value to this parameter to avoid large
+            /// responses.
+            ///
+            /// Similarly, the server should apply a default value when not supplied.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[ruma_api(query)]
+            pub limit: Option<UInt>,
+        }
+
+        response: {
+            /// The paginated child events which point to the parent.
+            ///
+            /// The events returned are ordered topologically, most-recent first.
+            ///
+            /// If no events are related to the parent or the pagination yields no results, an
+            /// empty `chunk` is returned.
+            pub chunk: Vec<Raw<AnyMessageLikeEvent>>,
+
+            /// An opaque string representing a pagination token.
+            ///
+            /// If this is `None`, there are no more results to fetch and the client should stop
+            /// paginating.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub next_batch: Option<String>,
+
+            /// An opaque string representing a pagination token.
+            ///
+            /// If this is `None`, this is the start of the result set, i.e. this is the first
+            /// batch/page.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub prev_batch: Option<String>,
+        }
+
+        error: crate::Error
+    }
+
+    impl<'a> Request<'a> {
+        /// Creates a new `Request` with the given room ID and parent event ID.
+        pub fn new(room_id: &'a RoomId, event_id: &'a EventId) -> Self {
+            Self { room_id, event_id, from: None, to: None, limit: None }
+        }
+    }
+
+    impl Response {
+        /// Creates a new `Response` with the given chunk.
+        pub fn new(chunk: Vec<Raw<AnyMessageLikeEvent>>) -> Self {
+            Self { chunk, next_batch: None, prev_batch: None }
+        }
+    }
+
+    #[cfg(all(test, feature = "client", feature = "server"))]
+    mod tests {
+        use js_int::uint;
+        use ruma_common::{
+            api::{
+                IncomingRequest as _, IncomingResponse as _, MatrixVersion, OutgoingRequest as _,
+                OutgoingResponse as _, SendAccessToken,
+            },
+            event_id, room_id,
+            serde::Raw,
+        };
+        use serde_json::{json, value::to_raw_value as to_raw_json_value};
+
+        use super::{IncomingRequest, Request, Response};
+
+        #[test]
+        fn request_roundtrip() {
+            let mut req =
+                Request::new(room_id!("!room:example.org"), event_id!("$event:example.org"));
+            req.from = Some("from_token");
+            req.to = Some("to_token");
+            req.limit = Some(uint!(10));
+
+            let http_req = req
+                .try_into_http_request::<Vec<u8>>(
+                    "https://homeserver.tld",
+                    SendAccessToken::IfRequired("auth_tok"),
+                    &[MatrixVersion::V1_1],
+                )
+                .unwrap();
+            assert_eq!(
+                http_req.uri().path(),
+                "/_matrix/client/unstable/rooms/%21room%3Aexample%2Eorg/relations/%24event%3Aexample%2Eorg"
+            );
+            assert_eq!(http_req.uri().query(), Some("from=from_token&to=to_token&limit=10"));
+
+            let req = IncomingRequest::try_from_http_request(
+                http_req,
+                &["!room:example.org", "$event:example.org"],
+            )
+            .unwrap();
+            assert_eq!(req.room_id, "!room:example.org");
+            assert_eq!(req.event_id, "$event:example.org");
+            assert_eq!(req.from.as_deref(), Some("from_token"));
+            assert_eq!(req.to.as_deref(), Some("to_token"));
+            assert_eq!(req.limit, Some(uint!(10)));
+        }
+
+        #[test]
+        fn response_roundtrip() {
+            let event = to_raw_json_value(&json!({
+                "content": { "body": "Hello", "msgtype": "m.text" },
+                "event_id": "$reply:example.org",
+                "origin_server_ts": 1,
+                "room_id": "!room:example.org",
+                "sender": "@alice:example.org",
+                "type": "m.room.message",
+            }))
+            .unwrap();
+            let mut res = Response::new(vec![Raw::from_json(event)]);
+            res.next_batch = Some("next".to_owned());
+            res.prev_batch = Some("prev".to_owned());
+
+            let http_res = res.try_into_http_response::<Vec<u8>>().unwrap();
+            let res = Response::try_from_http_response(http_res).unwrap();
+
+            assert_eq!(res.chunk.len(), 1);
+            assert_eq!(
+                res.chunk[0].get_field::<String>("event_id").unwrap().unwrap(),
+                "$reply:example.org"
+            );
+            assert_eq!(res.next_batch.as_deref(), Some("next"));
+            assert_eq!(res.prev_batch.as_deref(), Some("prev"));
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Endpoints for querying threads in a room.
+//!
+//! See [MSC3440] for more details about threads.
+//!
+//! [MSC3440]: https://github.com/matrix-org/matrix-spec-proposals/pull/3440
+
+pub mod get_threads;
@@ -0,0 +1,204 @@
+//! `GET /_matrix/client/*/rooms/{roomId}/threads`
+
+pub mod unstable {
+    //! `/unstable/` ([MSC])
+    //!
+    //! [MSC]: https://github.com/matrix-org/matrix-spec-proposals/pull/3440
+
+    use js_int::UInt;
+    use ruma_common::{
+        api::ruma_api,
+        events::AnyRoomEvent,
+        serde::{Raw, StringEnum},
+        RoomId,
+    };
+
+    use crate::PrivOwnedStr;
+
+    ruma_api! {
+        metadata: {
+            description: "Retrieve a list of threads in a room, with optional filters.",
+            method: GET,
+            name: "get_threads",
+            unstable_path: "/_matrix/client/unstable/org.matrix.msc3440/rooms/:room_id/threads",
+            rate_limited: true,
+            authentication: AccessToken,
+        }
+
+        request: {
+            /// The room ID where the thread roots are located.
+            #[ruma_api(path)]
+            pub room_id: &'a RoomId,
+
+            /// The pagination token to start returning results from.
+            ///
+            /// If `None`, results start at the most recent topological event visible to the user.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[ruma_api(query)]
+            pub from: Option<&'a str>,
+
+            /// Which thread roots are of interest to the caller.
+            #[serde(default, skip_serializing_if = "ruma_common::serde::is_default")]
+            #[ruma_api(query)]
+            pub include: IncludeThreads,
+
+            /// The maximum number of results to return in a single `chunk`.
+            ///
+            /// Servers should apply a default value, and impose a maximum value to avoid resource
+            /// exhaustion.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[ruma_api(query)]
+            pub limit: Option<UInt>,
+        }
+
+        response: {
+            /// The thread roots, ordered by the `latest_event` in each event's aggregation bundle.
+            ///
+            /// All events returned include bundled aggregations.
+            pub chunk: Vec<Raw<AnyRoomEvent>>,
+
+            /// An opaque string to provide to `from` to keep paginating the responses.
+            ///
+            /// If this is `None`, there are no more results to fetch and the client should stop
+            /// paginating.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub next_batch: Option<String>,
+        }
+
+        error: crate::Error
+    }
+
+    impl<'a> Request<'a> {
+        /// Creates a new `Request` with the given room ID.
+        pub fn new(room_id: &'a RoomId) -> Self {
+            Self { room_id, from: None, include: IncludeThreads::default(), limit: None }
+        }
+    }
+
+    impl Response {
+        /// Creates a new `Response` with the given chunk.
+        pub fn new(chunk: Vec<Raw<AnyRoomEvent>>) -> Self {
+            Self { chunk, next_batch: None }
+        }
+    }
+
+    /// Which threads to include in the response.
+    #[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
+    #[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
+    #[ruma_enum(rename_all = "lowercase")]
+    #[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+    pub enum IncludeThreads {
+        /// `all`
+        ///
+        /// Include all thread roots found in the room.
+        ///
+        /// This is the default.
+        All,
+
+        /// `participated`
+        ///
+        /// Only include thread roots for threads where [`current_user_participated`] is `true`.
+        ///
+        /// [`current_user_participated`]: ruma_common::events::relation::BundledThread::current_user_participated
+        Participated,
+
+        #[doc(hidden)]
+        _Custom(PrivOwnedStr),
+    }
+
+    impl Default for IncludeThreads {
+        fn default() -> Self {
+            Self::All
+        }
+    }
+
+    #[cfg(all(test, feature = "client", feature = "server"))]
+    mod tests {
+        use js_int::uint;
+        use ruma_common::{
+            api::{
+                IncomingRequest as _, IncomingResponse as _, MatrixVersion, OutgoingRequest as _,
+                OutgoingResponse as _, SendAccessToken,
+            },
+            room_id,
+            serde::Raw,
+        };
+        use serde_json::{json, value::to_raw_value as to_raw_json_value};
+
+        use super::{IncludeThreads, IncomingRequest, Request, Response};
+
+        #[test]
+        fn request_roundtrip() {
+            let mut req = Request::new(room_id!("!room:example.org"));
+            req.from = Some("from_token");
+            req.include = IncludeThreads::Participated;
+            req.limit = Some(uint!(10));
+
+            let http_req = req
+                .try_into_http_request::<Vec<u8>>(
+                    "https://homeserver.tld",
+                    SendAccessToken::IfRequired("auth_tok"),
+                    &[MatrixVersion::V1_1],
+                )
+                .unwrap();
+            assert_eq!(
+                http_req.uri().path(),
+                "/_matrix/client/unstable/org.matrix.msc3440/rooms/%21room%3Aexample%2Eorg/threads"
+            );
+            assert_eq!(
+                http_req.uri().query(),
+                Some("from=from_token&include=participated&limit=10")
+            );
+
+            let req =
+                IncomingRequest::try_from_http_request(http_req, &["!room:example.org"]).unwrap();
+            assert_eq!(req.room_id, "!room:example.org");
+            assert_eq!(req.from.as_deref(), Some("from_token"));
+            assert_eq!(req.include, IncludeThreads::Participated);
+            assert_eq!(req.limit, Some(uint!(10)));
+        }
+
+        #[test]
+        fn request_default_include() {
+            let req = Request::new(room_id!("!room:example.org"));
+
+            let http_req = req
+                .try_into_http_request::<Vec<u8>>(
+                    "https://homeserver.tld",
+                    SendAccessToken::IfRequired("auth_tok"),
+                    &[MatrixVersion::V1_1],
+                )
+                .unwrap();
+            assert!(!http_req.uri().query().unwrap_or_default().contains("include"));
+
+            let req =
+                IncomingRequest::try_from_http_request(http_req, &["!room:example.org"]).unwrap();
+            assert_eq!(req.include, IncludeThreads::All);
+        }
+
+        #[test]
+        fn response_roundtrip() {
+            let event = to_raw_json_value(&json!({
+                "content": { "body": "Hello", "msgtype": "m.text" },
+                "event_id": "$reply:example.org",
+                "origin_server_ts": 1,
+                "room_id": "!room:example.org",
+                "sender": "@alice:example.org",
+                "type": "m.room.message",
+            }))
+            .unwrap();
+            let mut res = Response::new(vec![Raw::from_json(event)]);
+            res.next_batch = Some("next".to_owned());
+
+            let http_res = res.try_into_http_response::<Vec<u8>>().unwrap();
+            let res = Response::try_from_http_response(http_res).unwrap();
+
+            assert_eq!(res.chunk.len(), 1);
+            assert_eq!(
+                res.chunk[0].get_field::<String>("event_id").unwrap().unwrap(),
+                "$reply:example.org"
+            );
+            assert_eq!(res.next_batch.as_deref(), Some("next"));
+        }
+    }
+}
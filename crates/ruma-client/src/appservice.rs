@@ -0,0 +1,225 @@
+use std::any::type_name;
+
+use ruma_appservice_api::Registration;
+#[cfg(feature = "unstable-msc2659")]
+use ruma_client_api::appservice::request_ping;
+#[cfg(feature = "unstable-msc2659")]
+use ruma_common::TransactionId;
+use ruma_common::{
+    api::{
+        IncomingResponse, MatrixVersion, OutgoingRequest, OutgoingRequestAppserviceExt,
+        SendAccessToken,
+    },
+    UserId,
+};
+use tracing::{info_span, Instrument};
+
+use crate::{Error, HttpClient, ResponseResult};
+
+/// A client for an application service, to send requests to the homeserver.
+///
+/// The requests are authenticated with the `as_token` of the registration of the application
+/// service.
+#[derive(Clone, Debug)]
+pub struct AppserviceClient<C> {
+    /// The underlying HTTP client.
+    http_client: C,
+
+    /// The URL of the homeserver.
+    homeserver_url: String,
+
+    /// The ID of the application service.
+    #[cfg(feature = "unstable-msc2659")]
+    appservice_id: String,
+
+    /// The token used to authenticate requests to the homeserver.
+    as_token: String,
+
+    /// The (known) Matrix versions the homeserver supports.
+    supported_matrix_versions: Vec<MatrixVersion>,
+}
+
+impl<C> AppserviceClient<C> {
+    /// Creates a new `AppserviceClient` for the application service with the given registration.
+    ///
+    /// The homeserver is assumed to support Matrix 1.0, use
+    /// [`supported_matrix_versions`][Self::supported_matrix_versions] to change that.
+    pub fn new(http_client: C, homeserver_url: String, registration: &Registration) -> Self {
+        Self {
+            http_client,
+            homeserver_url,
+            #[cfg(feature = "unstable-msc2659")]
+            appservice_id: registration.id.clone(),
+            as_token: registration.as_token.clone(),
+            supported_matrix_versions: vec![MatrixVersion::V1_0],
+        }
+    }
+
+    /// Set the Matrix versions supported by the homeserver.
+    pub fn supported_matrix_versions(self, versions: Vec<MatrixVersion>) -> Self {
+        Self { supported_matrix_versions: versions, ..self }
+    }
+}
+
+impl<C: HttpClient> AppserviceClient<C> {
+    /// Makes a request to a Matrix API endpoint as the sender user of the application service.
+    pub async fn send_request<R: OutgoingRequest>(&self, request: R) -> ResponseResult<C, R> {
+        self.send(request, None).await
+    }
+
+    /// Makes a request to a Matrix API endpoint as a user in the namespace of the application
+    /// service.
+    ///
+    /// The request is sent with the `user_id` query parameter, to [assert the identity] of the
+    /// user.
+    ///
+    /// [assert the identity]: https://spec.matrix.org/v1.2/application-service-api/#identity-assertion
+    pub async fn send_request_as<R: OutgoingRequest>(
+        &self,
+        user_id: &UserId,
+        request: R,
+    ) -> ResponseResult<C, R> {
+        self.send(request, Some(user_id)).await
+    }
+
+    /// Asks the homeserver to ping the application service, to check that the homeserver can
+    /// reach it.
+    ///
+    /// Returns the duration of the ping request made by the homeserver.
+    ///
+    /// This uses the endpoint of [MSC2659].
+    ///
+    /// [MSC2659]: https://github.com/matrix-org/matrix-spec-proposals/pull/2659
+    #[cfg(feature = "unstable-msc2659")]
+    pub async fn ping(
+        &self,
+        transaction_id: Option<&TransactionId>,
+    ) -> ResponseResult<C, request_ping::unstable::Request<'_>> {
+        let mut request = request_ping::unstable::Request::new(&self.appservice_id);
+        request.transaction_id = transaction_id;
+
+        self.send_request(request).await
+    }
+
+    async fn send<R: OutgoingRequest>(
+        &self,
+        request: R,
+        user_id: Option<&UserId>,
+    ) -> ResponseResult<C, R> {
+        let send_access_token = SendAccessToken::Always(&self.as_token);
+
+        let http_req = info_span!("serialize_request", request_type = type_name::<R>()).in_scope(
+            move || match user_id {
+                Some(user_id) => request.try_into_http_request_with_user_id(
+                    &self.homeserver_url,
+                    send_access_token,
+                    user_id,
+                    &self.supported_matrix_versions,
+                ),
+                None => request.try_into_http_request(
+                    &self.homeserver_url,
+                    send_access_token,
+                    &self.supported_matrix_versions,
+                ),
+            },
+        )?;
+
+        let send_span = info_span!(
+            "send_request",
+            request_type = type_name::<R>(),
+            http_client = type_name::<C>(),
+            homeserver_url = self.homeserver_url.as_str(),
+        );
+
+        let http_res = self
+            .http_client
+            .send_http_request(http_req)
+            .instrument(send_span)
+            .await
+            .map_err(Error::Response)?;
+
+        let res =
+            info_span!("deserialize_response", response_type = type_name::<R::IncomingResponse>())
+                .in_scope(move || R::IncomingResponse::try_from_http_response(http_res))?;
+
+        Ok(res)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use futures_executor::block_on;
+    use ruma_appservice_api::{Namespaces, Registration, RegistrationInit};
+    use ruma_client_api::membership::forget_room;
+    use ruma_common::{room_id, user_id};
+
+    use super::AppserviceClient;
+    use crate::testing::MockClient;
+
+    fn registration() -> Registration {
+        RegistrationInit {
+            id: "bridge".to_owned(),
+            url: Some("http://localhost:1234".to_owned()),
+            as_token: "as_token".to_owned(),
+            hs_token: "hs_token".to_owned(),
+            sender_localpart: "_bridge_bot".to_owned(),
+            namespaces: Namespaces::new(),
+            rate_limited: None,
+            protocols: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn send_request_as_user() {
+        let mock = MockClient::new();
+        mock.expect::<forget_room::v3::IncomingRequest>()
+            .always()
+            .respond_with(forget_room::v3::Response::new());
+
+        let client =
+            AppserviceClient::new(mock.clone(), "https://example.org".to_owned(), &registration());
+        let room_id = room_id!("!room:example.org");
+
+        block_on(client.send_request(forget_room::v3::Request::new(room_id))).unwrap();
+        block_on(client.send_request_as(
+            user_id!("@_bridge_alice:example.org"),
+            forget_room::v3::Request::new(room_id),
+        ))
+        .unwrap();
+
+        let received = mock.received::<forget_room::v3::IncomingRequest>();
+        assert_eq!(received.len(), 2);
+        assert!(received.iter().all(|request| request.room_id == room_id));
+
+        let requests = mock.received_http_requests();
+        for request in &requests {
+            assert_eq!(request.headers()[http::header::AUTHORIZATION], "Bearer as_token");
+        }
+        assert_eq!(requests[0].uri().query(), None);
+        assert_eq!(requests[1].uri().query(), Some("user_id=%40_bridge_alice%3Aexample.org"));
+    }
+
+    #[cfg(feature = "unstable-msc2659")]
+    #[test]
+    fn ping() {
+        use std::time::Duration;
+
+        use ruma_client_api::appservice::request_ping;
+
+        let mock = MockClient::new();
+        mock.expect::<request_ping::unstable::IncomingRequest>()
+            .matching(|request| {
+                request.appservice_id == "bridge"
+                    && request.transaction_id.as_ref().map(|id| id.as_str()) == Some("txn")
+            })
+            .respond_with(request_ping::unstable::Response::new(Duration::from_millis(123)));
+
+        let client =
+            AppserviceClient::new(mock.clone(), "https://example.org".to_owned(), &registration());
+
+        let response = block_on(client.ping(Some("txn".into()))).unwrap();
+        assert_eq!(response.duration, Duration::from_millis(123));
+        mock.assert_all_expectations_met();
+    }
+}
@@ -19,7 +19,8 @@ use ruma_common::{
 };
 
 use crate::{
-    add_user_id_to_query, send_customized_request, Error, HttpClient, ResponseError, ResponseResult,
+    add_user_id_to_query, retry::send_customized_request_with_retries, send_customized_request,
+    Error, HttpClient, ResponseError, ResponseResult, RetryPolicy,
 };
 
 mod builder;
@@ -44,6 +45,9 @@ struct ClientData<C> {
 
     /// The (known) Matrix versions the homeserver supports.
     supported_matrix_versions: Vec<MatrixVersion>,
+
+    /// The policy used to retry failed requests, if any.
+    retry_policy: Option<RetryPolicy>,
 }
 
 impl Client<()> {
@@ -64,6 +68,9 @@ impl<C> Client<C> {
 
 impl<C: HttpClient> Client<C> {
     /// Makes a request to a Matrix API endpoint.
+    ///
+    /// If a [`RetryPolicy`] was set when building the client, failed requests are retried
+    /// according to it.
     pub async fn send_request<R: OutgoingRequest>(&self, request: R) -> ResponseResult<C, R> {
         self.send_customized_request(request, |_| Ok(())).await
     }
@@ -84,15 +91,31 @@ impl<C: HttpClient> Client<C> {
             None => SendAccessToken::None,
         };
 
-        send_customized_request(
-            &self.0.http_client,
-            &self.0.homeserver_url,
-            send_access_token,
-            &self.0.supported_matrix_versions,
-            request,
-            customize,
-        )
-        .await
+        match &self.0.retry_policy {
+            Some(retry_policy) => {
+                send_customized_request_with_retries(
+                    &self.0.http_client,
+                    &self.0.homeserver_url,
+                    send_access_token,
+                    &self.0.supported_matrix_versions,
+                    request,
+                    customize,
+                    retry_policy,
+                )
+                .await
+            }
+            None => {
+                send_customized_request(
+                    &self.0.http_client,
+                    &self.0.homeserver_url,
+                    send_access_token,
+                    &self.0.supported_matrix_versions,
+                    request,
+                    customize,
+                )
+                .await
+            }
+        }
     }
 
     /// Makes a request to a Matrix API endpoint as a virtual user.
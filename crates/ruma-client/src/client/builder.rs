@@ -4,7 +4,7 @@ use ruma_client_api::discovery::get_supported_versions;
 use ruma_common::api::{MatrixVersion, SendAccessToken};
 
 use super::{Client, ClientData};
-use crate::{DefaultConstructibleHttpClient, Error, HttpClient, HttpClientExt};
+use crate::{DefaultConstructibleHttpClient, Error, HttpClient, HttpClientExt, RetryPolicy};
 
 /// A [`Client`] builder.
 ///
@@ -13,11 +13,17 @@ pub struct ClientBuilder {
     homeserver_url: Option<String>,
     access_token: Option<String>,
     supported_matrix_versions: Option<Vec<MatrixVersion>>,
+    retry_policy: Option<RetryPolicy>,
 }
 
 impl ClientBuilder {
     pub(super) fn new() -> Self {
-        Self { homeserver_url: None, access_token: None, supported_matrix_versions: None }
+        Self {
+            homeserver_url: None,
+            access_token: None,
+            supported_matrix_versions: None,
+            retry_policy: None,
+        }
     }
 
     /// Set the homeserver URL.
@@ -42,6 +48,13 @@ impl ClientBuilder {
         Self { supported_matrix_versions: Some(versions), ..self }
     }
 
+    /// Set the policy used to retry failed requests.
+    ///
+    /// By default, failed requests are not retried.
+    pub fn retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self { retry_policy: Some(retry_policy), ..self }
+    }
+
     /// Finish building the [`Client`].
     ///
     /// Uses [`DefaultConstructibleHttpClient::default()`] to create an HTTP client instance.
@@ -90,6 +103,7 @@ impl ClientBuilder {
             http_client,
             access_token: Mutex::new(self.access_token),
             supported_matrix_versions,
+            retry_policy: self.retry_policy,
         })))
     }
 }
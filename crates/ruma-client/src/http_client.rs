@@ -16,8 +16,11 @@ use crate::{add_user_id_to_query, ResponseError, ResponseResult};
 mod hyper;
 #[cfg(feature = "isahc")]
 mod isahc;
+mod middleware;
 #[cfg(feature = "reqwest")]
 mod reqwest;
+#[cfg(feature = "tower")]
+mod tower;
 
 #[cfg(feature = "hyper")]
 pub use self::hyper::Hyper;
@@ -27,14 +30,17 @@ pub use self::hyper::HyperNativeTls;
 pub use self::hyper::HyperRustls;
 #[cfg(feature = "isahc")]
 pub use self::isahc::Isahc;
+pub use self::middleware::{Middleware, WithMiddleware};
 #[cfg(feature = "reqwest")]
 pub use self::reqwest::Reqwest;
+#[cfg(feature = "tower")]
+pub use self::tower::{HttpClientService, Tower};
 
 /// An HTTP client that can be used to send requests to a Matrix homeserver.
 #[async_trait]
 pub trait HttpClient: Sync {
     /// The type to use for `try_into_http_request`.
-    type RequestBody: Default + BufMut + Send;
+    type RequestBody: Default + BufMut + AsRef<[u8]> + Send;
 
     /// The type to use for `try_from_http_response`.
     type ResponseBody: AsRef<[u8]>;
@@ -124,6 +130,15 @@ pub trait HttpClientExt: HttpClient {
             add_user_id_to_query::<Self, R>(user_id),
         )
     }
+
+    /// Wrap this HTTP client in the given [`Middleware`].
+    fn with_middleware<M>(self, middleware: M) -> WithMiddleware<Self, M>
+    where
+        Self: Sized,
+        M: Middleware<Self>,
+    {
+        WithMiddleware::new(self, middleware)
+    }
 }
 
 #[async_trait]
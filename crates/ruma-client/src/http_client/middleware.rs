@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+
+use super::{DefaultConstructibleHttpClient, HttpClient};
+
+/// A layer around an [`HttpClient`], that can inspect and modify the requests sent with it and the
+/// responses it returns.
+///
+/// Middleware can be used for logging, metrics, adding custom headers, caching responses, etc.
+/// Use [`HttpClientExt::with_middleware`](crate::HttpClientExt::with_middleware) to wrap an
+/// `HttpClient` in a middleware. Several middleware can be stacked by calling it several times,
+/// the last one added is the outermost one: it sees the requests first and the responses last.
+///
+/// # Example
+///
+/// ```
+/// use async_trait::async_trait;
+/// use ruma_client::{http_client::Middleware, HttpClient};
+///
+/// /// Adds a header to all requests.
+/// struct AddHeader;
+///
+/// #[async_trait]
+/// impl<C: HttpClient> Middleware<C> for AddHeader {
+///     async fn handle(
+///         &self,
+///         mut req: http::Request<C::RequestBody>,
+///         next: &C,
+///     ) -> Result<http::Response<C::ResponseBody>, C::Error> {
+///         req.headers_mut().insert("x-client", http::HeaderValue::from_static("my-bot"));
+///         let res = next.send_http_request(req).await?;
+///         tracing::debug!("got response with status {}", res.status());
+///         Ok(res)
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait Middleware<C: HttpClient>: Sync {
+    /// Handle the given request.
+    ///
+    /// `next` is the wrapped `HttpClient`, it should be used to send the request, unless the
+    /// middleware produces the response itself.
+    async fn handle(
+        &self,
+        req: http::Request<C::RequestBody>,
+        next: &C,
+    ) -> Result<http::Response<C::ResponseBody>, C::Error>;
+}
+
+/// An [`HttpClient`] wrapped in a [`Middleware`].
+///
+/// This type is usually created with
+/// [`HttpClientExt::with_middleware`](crate::HttpClientExt::with_middleware).
+#[derive(Clone, Debug)]
+pub struct WithMiddleware<C, M> {
+    inner: C,
+    middleware: M,
+}
+
+impl<C, M> WithMiddleware<C, M> {
+    /// Wraps the given `HttpClient` in the given middleware.
+    pub fn new(inner: C, middleware: M) -> Self {
+        Self { inner, middleware }
+    }
+
+    /// Get a reference to the wrapped `HttpClient`.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Get a reference to the middleware.
+    pub fn middleware(&self) -> &M {
+        &self.middleware
+    }
+}
+
+#[async_trait]
+impl<C, M> HttpClient for WithMiddleware<C, M>
+where
+    C: HttpClient,
+    M: Middleware<C>,
+{
+    type RequestBody = C::RequestBody;
+    type ResponseBody = C::ResponseBody;
+    type Error = C::Error;
+
+    async fn send_http_request(
+        &self,
+        req: http::Request<C::RequestBody>,
+    ) -> Result<http::Response<C::ResponseBody>, C::Error> {
+        self.middleware.handle(req, &self.inner).await
+    }
+}
+
+impl<C, M> DefaultConstructibleHttpClient for WithMiddleware<C, M>
+where
+    C: DefaultConstructibleHttpClient,
+    M: Middleware<C> + Default,
+{
+    fn default() -> Self {
+        Self::new(C::default(), M::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use futures_executor::block_on;
+
+    use super::Middleware;
+    use crate::{HttpClient, HttpClientExt};
+
+    /// An `HttpClient` that responds with the value of the `x-trace` header of the request.
+    struct EchoClient;
+
+    #[async_trait]
+    impl HttpClient for EchoClient {
+        type RequestBody = Vec<u8>;
+        type ResponseBody = Vec<u8>;
+        type Error = ();
+
+        async fn send_http_request(
+            &self,
+            req: http::Request<Self::RequestBody>,
+        ) -> Result<http::Response<Self::ResponseBody>, Self::Error> {
+            let trace = req.headers().get("x-trace").map(|v| v.as_bytes().to_owned());
+            Ok(http::Response::new(trace.unwrap_or_default()))
+        }
+    }
+
+    /// Appends its name to the `x-trace` header of requests and records the responses it sees.
+    struct Trace {
+        name: &'static str,
+        responses: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Trace {
+        fn new(name: &'static str) -> Self {
+            Self { name, responses: Mutex::new(Vec::new()) }
+        }
+    }
+
+    #[async_trait]
+    impl<C: HttpClient<ResponseBody = Vec<u8>>> Middleware<C> for Trace {
+        async fn handle(
+            &self,
+            mut req: http::Request<C::RequestBody>,
+            next: &C,
+        ) -> Result<http::Response<C::ResponseBody>, C::Error> {
+            let trace = match req.headers().get("x-trace") {
+                Some(value) => format!("{},{}", value.to_str().unwrap(), self.name),
+                None => self.name.to_owned(),
+            };
+            req.headers_mut().insert("x-trace", trace.parse().unwrap());
+
+            let res = next.send_http_request(req).await?;
+            self.responses.lock().unwrap().push(res.body().clone());
+            Ok(res)
+        }
+    }
+
+    #[test]
+    fn middleware_stack() {
+        let client =
+            EchoClient.with_middleware(Trace::new("inner")).with_middleware(Trace::new("outer"));
+
+        let res = block_on(client.send_http_request(http::Request::new(Vec::new()))).unwrap();
+        assert_eq!(res.body(), b"outer,inner");
+
+        assert_eq!(*client.middleware().responses.lock().unwrap(), [b"outer,inner"]);
+        assert_eq!(*client.inner().middleware().responses.lock().unwrap(), [b"outer,inner"]);
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use bytes::BufMut;
+use futures_util::future::poll_fn;
+use tower_service::Service;
+
+use super::HttpClient;
+
+/// An [`HttpClient`] backed by a tower [`Service`].
+///
+/// This can be used to send requests with a stack of tower middleware. The service is cloned for
+/// every request, so it should be cheap to clone, like most tower services.
+///
+/// To use an existing `HttpClient` at the bottom of the stack, wrap it in a [`HttpClientService`].
+///
+/// `B` is the type of the body of the requests accepted by the service.
+///
+/// # Example
+///
+/// ```ignore
+/// # // HACK: "ignore" the doctest here because this needs the tower crate.
+/// use ruma_client::http_client::{HttpClientService, Reqwest, Tower};
+///
+/// let service = tower::ServiceBuilder::new()
+///     .timeout(std::time::Duration::from_secs(30))
+///     .service(HttpClientService::new(Reqwest::new()));
+///
+/// let client = ruma_client::Client::builder()
+///     .homeserver_url(homeserver_url)
+///     .http_client(Tower::new(service))
+///     .await?;
+/// ```
+pub struct Tower<S, B> {
+    service: S,
+    _request_body: PhantomData<fn(B)>,
+}
+
+impl<S, B> Tower<S, B> {
+    /// Creates a new `Tower` HTTP client with the given service.
+    pub fn new(service: S) -> Self {
+        Self { service, _request_body: PhantomData }
+    }
+
+    /// Get a reference to the wrapped service.
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S: Clone, B> Clone for Tower<S, B> {
+    fn clone(&self) -> Self {
+        Self::new(self.service.clone())
+    }
+}
+
+impl<S: Debug, B> Debug for Tower<S, B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tower").field("service", &self.service).finish()
+    }
+}
+
+#[async_trait]
+impl<S, ReqB, ResB> HttpClient for Tower<S, ReqB>
+where
+    S: Service<http::Request<ReqB>, Response = http::Response<ResB>> + Clone + Send + Sync,
+    S::Future: Send,
+    S::Error: Send + Unpin,
+    ReqB: Default + BufMut + AsRef<[u8]> + Send + 'static,
+    ResB: AsRef<[u8]>,
+{
+    type RequestBody = ReqB;
+    type ResponseBody = ResB;
+    type Error = S::Error;
+
+    async fn send_http_request(
+        &self,
+        req: http::Request<ReqB>,
+    ) -> Result<http::Response<ResB>, S::Error> {
+        let mut service = self.service.clone();
+        poll_fn(|cx| service.poll_ready(cx)).await?;
+        service.call(req).await
+    }
+}
+
+/// A tower [`Service`] backed by an [`HttpClient`].
+///
+/// This can be used as the innermost service of a stack of tower middleware, that can then be used
+/// as an `HttpClient` with [`Tower`].
+#[derive(Debug)]
+pub struct HttpClientService<C> {
+    http_client: Arc<C>,
+}
+
+impl<C> HttpClientService<C> {
+    /// Creates a new `HttpClientService` with the given HTTP client.
+    pub fn new(http_client: C) -> Self {
+        Self { http_client: Arc::new(http_client) }
+    }
+}
+
+impl<C> Clone for HttpClientService<C> {
+    fn clone(&self) -> Self {
+        Self { http_client: self.http_client.clone() }
+    }
+}
+
+impl<C> Service<http::Request<C::RequestBody>> for HttpClientService<C>
+where
+    C: HttpClient + Send + 'static,
+{
+    type Response = http::Response<C::ResponseBody>;
+    type Error = C::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<C::RequestBody>) -> Self::Future {
+        let http_client = self.http_client.clone();
+        Box::pin(async move { http_client.send_http_request(req).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use futures_executor::block_on;
+
+    use super::{HttpClientService, Tower};
+    use crate::HttpClient;
+
+    /// An `HttpClient` that responds with the body of the request.
+    struct EchoClient;
+
+    #[async_trait]
+    impl HttpClient for EchoClient {
+        type RequestBody = Vec<u8>;
+        type ResponseBody = Vec<u8>;
+        type Error = ();
+
+        async fn send_http_request(
+            &self,
+            req: http::Request<Self::RequestBody>,
+        ) -> Result<http::Response<Self::ResponseBody>, Self::Error> {
+            Ok(http::Response::new(req.into_body()))
+        }
+    }
+
+    #[test]
+    fn http_client_round_trip() {
+        let client = Tower::new(HttpClientService::new(EchoClient));
+
+        let res = block_on(client.send_http_request(http::Request::new(b"ping".to_vec()))).unwrap();
+        assert_eq!(res.body(), b"ping");
+    }
+}
@@ -97,6 +97,15 @@
 //!   * `reqwest-rustls-manual-roots`
 //!   * `reqwest-rustls-webpki-roots`
 //!   * `reqwest-rustls-native-roots`
+//! * `testing` – activates the [`testing`] module, with a mock HTTP client to test code using
+//!   [`Client`] without a homeserver
+//! * `tower` – activate this feature to use a [tower](https://docs.rs/tower) `Service` as an HTTP
+//!   client, or an HTTP client as a tower `Service`
+//!
+//! # Middleware
+//!
+//! Requests sent with an HTTP client can be inspected and modified by wrapping it in one or several
+//! [`Middleware`](http_client::Middleware) with [`HttpClientExt::with_middleware`].
 
 #![warn(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
@@ -116,13 +125,23 @@ extern crate hyper_rustls_crate as hyper_rustls;
 #[cfg(feature = "isahc")]
 extern crate isahc_crate as isahc;
 
+#[cfg(feature = "appservice-api")]
+mod appservice;
 #[cfg(feature = "client-api")]
 mod client;
 mod error;
 pub mod http_client;
+#[cfg(feature = "client-api")]
+mod retry;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+#[cfg(feature = "appservice-api")]
+pub use self::appservice::AppserviceClient;
 #[cfg(feature = "client-api")]
 pub use self::client::{Client, ClientBuilder};
+#[cfg(feature = "client-api")]
+pub use self::retry::RetryPolicy;
 pub use self::{
     error::Error,
     http_client::{DefaultConstructibleHttpClient, HttpClient, HttpClientExt},
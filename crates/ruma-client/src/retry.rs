@@ -0,0 +1,432 @@
+//! Retrying failed requests.
+
+use std::{
+    any::type_name,
+    cmp::min,
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::BufMut;
+use http::{header::HeaderMap, Method, StatusCode, Uri, Version};
+use ruma_common::api::{MatrixVersion, Metadata, OutgoingRequest, SendAccessToken};
+use serde::Deserialize;
+use tracing::{info_span, warn, Instrument};
+
+use crate::{Error, HttpClient, ResponseError, ResponseResult};
+
+type SleepFn = dyn Fn(Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+/// The policy used by a [`Client`](crate::Client) to retry failed requests.
+///
+/// Requests are retried in the following cases:
+///
+/// * The homeserver responded with a `429 Too Many Requests` status code and the endpoint is
+///   [rate-limited](Metadata::rate_limited). The `retry_after_ms` field of the `M_LIMIT_EXCEEDED`
+///   error is honoured if present, otherwise exponential backoff is used.
+/// * The homeserver responded with a `5xx` status code, or no response could be obtained (e.g. due
+///   to a connection error), and the request can be sent several times safely: it uses an
+///   idempotent HTTP method, or it has a transaction ID. Exponential backoff is used between these
+///   attempts.
+///
+/// Since `ruma-client` is agnostic of the async runtime, the function used to wait between
+/// attempts must be provided when constructing the policy, for example `tokio::time::sleep`.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    sleep: Arc<SleepFn>,
+}
+
+impl RetryPolicy {
+    /// Creates a new `RetryPolicy` that uses the given function to wait between attempts.
+    ///
+    /// By default, requests are retried at most 3 times, with a backoff starting at 500
+    /// milliseconds that doubles for each attempt, up to 30 seconds.
+    pub fn new<F, Fut>(sleep: F) -> Self
+    where
+        F: Fn(Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            sleep: Arc::new(move |duration| Box::pin(sleep(duration))),
+        }
+    }
+
+    /// Set the maximum number of times a request is retried.
+    pub fn max_retries(self, max_retries: u32) -> Self {
+        Self { max_retries, ..self }
+    }
+
+    /// Set the duration to wait before the first retry, when exponential backoff is used.
+    pub fn initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self { initial_backoff, ..self }
+    }
+
+    /// Set the maximum duration to wait between attempts, when exponential backoff is used.
+    pub fn max_backoff(self, max_backoff: Duration) -> Self {
+        Self { max_backoff, ..self }
+    }
+
+    /// The duration to wait before the given retry, starting at 0, with exponential backoff.
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(retry);
+        min(self.initial_backoff.saturating_mul(factor), self.max_backoff)
+    }
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether the request can be sent several times without changing its outcome.
+fn is_idempotent(metadata: &Metadata) -> bool {
+    let has_txn_id = [metadata.unstable_path, metadata.r0_path, metadata.stable_path]
+        .iter()
+        .flatten()
+        .any(|path| path.split('/').any(|segment| segment == ":txn_id"));
+
+    has_txn_id
+        || matches!(
+            metadata.method,
+            Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+        )
+}
+
+/// Extracts the `retry_after_ms` field of an `M_LIMIT_EXCEEDED` error response body.
+fn retry_after(body: &[u8]) -> Option<Duration> {
+    #[derive(Deserialize)]
+    struct LimitExceeded {
+        errcode: String,
+        retry_after_ms: Option<u64>,
+    }
+
+    let error: LimitExceeded = serde_json::from_slice(body).ok()?;
+    if error.errcode != "M_LIMIT_EXCEEDED" {
+        return None;
+    }
+
+    error.retry_after_ms.map(Duration::from_millis)
+}
+
+/// The parts of an HTTP request that are needed to send it again.
+struct RequestHead {
+    method: Method,
+    uri: Uri,
+    version: Version,
+    headers: HeaderMap,
+}
+
+impl RequestHead {
+    fn new<T>(req: &http::Request<T>) -> Self {
+        Self {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            version: req.version(),
+            headers: req.headers().clone(),
+        }
+    }
+
+    fn from_parts(parts: http::request::Parts) -> Self {
+        Self {
+            method: parts.method,
+            uri: parts.uri,
+            version: parts.version,
+            headers: parts.headers,
+        }
+    }
+
+    fn to_request<T: Default + BufMut>(&self, body: &[u8]) -> http::Request<T> {
+        let mut buf = T::default();
+        buf.put_slice(body);
+
+        let mut req = http::Request::new(buf);
+        *req.method_mut() = self.method.clone();
+        *req.uri_mut() = self.uri.clone();
+        *req.version_mut() = self.version;
+        *req.headers_mut() = self.headers.clone();
+        req
+    }
+}
+
+/// Like `send_customized_request`, but retries the request according to the given policy.
+///
+/// `customize` is only called once, the changes it makes to the headers, URI and body of the
+/// request are kept for the following attempts.
+pub(crate) async fn send_customized_request_with_retries<C, R, F>(
+    http_client: &C,
+    homeserver_url: &str,
+    send_access_token: SendAccessToken<'_>,
+    for_versions: &[MatrixVersion],
+    request: R,
+    customize: F,
+    policy: &RetryPolicy,
+) -> ResponseResult<C, R>
+where
+    C: HttpClient + ?Sized,
+    R: OutgoingRequest,
+    F: FnOnce(&mut http::Request<C::RequestBody>) -> Result<(), ResponseError<C, R>>,
+{
+    let (head, body) =
+        info_span!("serialize_request", request_type = type_name::<R>()).in_scope(move || {
+            let req = request.try_into_http_request::<Vec<u8>>(
+                homeserver_url,
+                send_access_token,
+                for_versions,
+            )?;
+
+            let mut customized_req = RequestHead::new(&req).to_request(req.body());
+            customize(&mut customized_req)?;
+
+            let (parts, body) = customized_req.into_parts();
+            Ok::<_, ResponseError<C, R>>((RequestHead::from_parts(parts), body.as_ref().to_vec()))
+        })?;
+
+    let idempotent = is_idempotent(&R::METADATA);
+    let mut retry = 0;
+
+    loop {
+        let can_retry = retry < policy.max_retries;
+
+        let send_span = info_span!(
+            "send_request",
+            request_type = type_name::<R>(),
+            http_client = type_name::<C>(),
+            homeserver_url,
+            retry,
+        );
+
+        let delay = match http_client
+            .send_http_request(head.to_request(&body))
+            .instrument(send_span)
+            .await
+        {
+            Ok(http_res) => {
+                let status = http_res.status();
+                if can_retry && status == StatusCode::TOO_MANY_REQUESTS && R::METADATA.rate_limited
+                {
+                    retry_after(http_res.body().as_ref()).unwrap_or_else(|| policy.backoff(retry))
+                } else if can_retry && status.is_server_error() && idempotent {
+                    policy.backoff(retry)
+                } else {
+                    let res = info_span!(
+                        "deserialize_response",
+                        response_type = type_name::<R::IncomingResponse>()
+                    )
+                    .in_scope(move || {
+                        ruma_common::api::IncomingResponse::try_from_http_response(http_res)
+                    })?;
+
+                    return Ok(res);
+                }
+            }
+            Err(_) if can_retry && idempotent => policy.backoff(retry),
+            Err(error) => return Err(Error::Response(error)),
+        };
+
+        warn!(
+            request_type = type_name::<R>(),
+            retry,
+            "request failed, retrying in {}ms",
+            delay.as_millis()
+        );
+        (policy.sleep)(delay).await;
+
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use futures_executor::block_on;
+    use ruma_client_api::{
+        discovery::{get_capabilities, get_supported_versions},
+        membership::forget_room,
+    };
+    use ruma_common::{
+        api::{MatrixVersion, OutgoingRequest, SendAccessToken},
+        room_id,
+    };
+    use serde_json::{json, to_vec as to_json_vec};
+
+    use super::{send_customized_request_with_retries, RetryPolicy};
+    use crate::{HttpClient, ResponseResult};
+
+    /// An `HttpClient` that returns the given responses in order.
+    struct MockClient {
+        responses: Mutex<VecDeque<Result<http::Response<Vec<u8>>, ()>>>,
+        request_bodies: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl MockClient {
+        fn new(responses: Vec<(u16, serde_json::Value)>) -> Self {
+            let responses = responses
+                .into_iter()
+                .map(|(status, body)| {
+                    Ok(http::Response::builder()
+                        .status(status)
+                        .body(to_json_vec(&body).unwrap())
+                        .unwrap())
+                })
+                .collect();
+
+            Self { responses: Mutex::new(responses), request_bodies: Mutex::new(Vec::new()) }
+        }
+
+        fn remaining(&self) -> usize {
+            self.responses.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl HttpClient for MockClient {
+        type RequestBody = Vec<u8>;
+        type ResponseBody = Vec<u8>;
+        type Error = ();
+
+        async fn send_http_request(
+            &self,
+            req: http::Request<Self::RequestBody>,
+        ) -> Result<http::Response<Self::ResponseBody>, Self::Error> {
+            self.request_bodies.lock().unwrap().push(req.into_body());
+            self.responses.lock().unwrap().pop_front().unwrap_or(Err(()))
+        }
+    }
+
+    fn policy() -> (RetryPolicy, Arc<Mutex<Vec<Duration>>>) {
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let delays_clone = delays.clone();
+        let policy = RetryPolicy::new(move |delay| {
+            delays_clone.lock().unwrap().push(delay);
+            async {}
+        })
+        .initial_backoff(Duration::from_millis(10));
+
+        (policy, delays)
+    }
+
+    fn send<R: OutgoingRequest>(
+        client: &MockClient,
+        request: R,
+        policy: &RetryPolicy,
+    ) -> ResponseResult<MockClient, R> {
+        block_on(send_customized_request_with_retries(
+            client,
+            "https://example.org",
+            SendAccessToken::IfRequired("token"),
+            &[MatrixVersion::V1_2],
+            request,
+            |_| Ok(()),
+            policy,
+        ))
+    }
+
+    #[test]
+    fn server_error_retried_with_backoff() {
+        let client = MockClient::new(vec![
+            (500, json!({})),
+            (502, json!({})),
+            (200, json!({ "capabilities": {} })),
+        ]);
+        let (policy, delays) = policy();
+
+        send(&client, get_capabilities::v3::Request::new(), &policy).unwrap();
+        assert_eq!(client.remaining(), 0);
+        assert_eq!(*delays.lock().unwrap(), [Duration::from_millis(10), Duration::from_millis(20)]);
+    }
+
+    #[test]
+    fn max_retries() {
+        let client = MockClient::new(vec![(500, json!({})); 4]);
+        let (policy, delays) = policy();
+        let policy = policy.max_retries(2);
+
+        send(&client, get_capabilities::v3::Request::new(), &policy).unwrap_err();
+        assert_eq!(client.remaining(), 1);
+        assert_eq!(delays.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rate_limit_honours_retry_after() {
+        let client = MockClient::new(vec![
+            (
+                429,
+                json!({ "errcode": "M_LIMIT_EXCEEDED", "error": "Too many requests", "retry_after_ms": 1234 }),
+            ),
+            (200, json!({})),
+        ]);
+        let (policy, delays) = policy();
+
+        send(&client, forget_room::v3::Request::new(room_id!("!room:example.org")), &policy)
+            .unwrap();
+        assert_eq!(client.remaining(), 0);
+        assert_eq!(*delays.lock().unwrap(), [Duration::from_millis(1234)]);
+    }
+
+    #[test]
+    fn rate_limit_ignored_for_endpoint_without_rate_limit() {
+        let client = MockClient::new(vec![
+            (429, json!({ "errcode": "M_LIMIT_EXCEEDED", "error": "Too many requests" })),
+            (200, json!({ "versions": [] })),
+        ]);
+        let (policy, delays) = policy();
+
+        send(&client, get_supported_versions::Request::new(), &policy).unwrap_err();
+        assert_eq!(client.remaining(), 1);
+        assert!(delays.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn non_idempotent_request_not_retried() {
+        let client = MockClient::new(vec![(500, json!({})), (200, json!({}))]);
+        let (policy, delays) = policy();
+
+        send(&client, forget_room::v3::Request::new(room_id!("!room:example.org")), &policy)
+            .unwrap_err();
+        assert_eq!(client.remaining(), 1);
+        assert!(delays.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn customized_body_kept_for_retries() {
+        let client = MockClient::new(vec![(500, json!({})), (200, json!({ "capabilities": {} }))]);
+        let (policy, _) = policy();
+
+        block_on(send_customized_request_with_retries(
+            &client,
+            "https://example.org",
+            SendAccessToken::IfRequired("token"),
+            &[MatrixVersion::V1_2],
+            get_capabilities::v3::Request::new(),
+            |req| {
+                *req.body_mut() = b"{\"custom\":true}".to_vec();
+                Ok(())
+            },
+            &policy,
+        ))
+        .unwrap();
+
+        let custom_body = b"{\"custom\":true}".to_vec();
+        assert_eq!(*client.request_bodies.lock().unwrap(), [custom_body.clone(), custom_body]);
+    }
+}
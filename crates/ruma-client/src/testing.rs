@@ -0,0 +1,425 @@
+//! Utilities to test code using a [`Client`](crate::Client) without a homeserver.
+//!
+//! The [`MockClient`] is an [`HttpClient`] that answers requests according to expectations set
+//! beforehand, and records the requests it receives.
+//!
+//! # Example
+//!
+//! ```
+//! use ruma_client::{testing::MockClient, Client};
+//! use ruma_client_api::message::send_message_event;
+//! use ruma_common::{
+//!     api::MatrixVersion, event_id, events::room::message::RoomMessageEventContent, room_id,
+//!     TransactionId,
+//! };
+//!
+//! # futures_executor::block_on(async {
+//! let room_id = room_id!("!room:example.org");
+//!
+//! let mock = MockClient::new().with_versions(&[MatrixVersion::V1_2]);
+//! mock.expect::<send_message_event::v3::IncomingRequest>()
+//!     .matching(move |req| req.room_id == room_id)
+//!     .respond_with(send_message_event::v3::Response::new(event_id!("$event").to_owned()));
+//!
+//! let client = Client::builder()
+//!     .homeserver_url("https://example.org".to_owned())
+//!     .access_token(Some("access_token".to_owned()))
+//!     .http_client(mock.clone())
+//!     .await
+//!     .unwrap();
+//!
+//! let content = RoomMessageEventContent::text_plain("Hello!");
+//! let txn_id = <&TransactionId>::from("txn");
+//! let request = send_message_event::v3::Request::new(room_id, txn_id, &content).unwrap();
+//! let response = client.send_request(request).await.unwrap();
+//! assert_eq!(response.event_id, event_id!("$event"));
+//!
+//! let requests = mock.received::<send_message_event::v3::IncomingRequest>();
+//! assert_eq!(requests.len(), 1);
+//! mock.assert_all_expectations_met();
+//! # });
+//! ```
+
+use std::{
+    error::Error as StdError,
+    fmt::{self, Debug, Display, Formatter},
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use http::{header::HeaderMap, Method, StatusCode, Uri};
+use ruma_client_api::{discovery::get_supported_versions, sync::sync_events};
+use ruma_common::api::{IncomingRequest, MatrixVersion, OutgoingResponse, Router};
+
+use crate::HttpClient;
+
+/// An [`HttpClient`] that answers requests according to expectations.
+///
+/// Expectations are set with [`expect`][Self::expect]. When a request is received, the first
+/// expectation that matches it is used to respond. Expectations only match a single request,
+/// unless they are set to match [`always`][ExpectationBuilder::always]. If no expectation matches
+/// a request, sending it fails with an [`UnexpectedRequest`] error.
+///
+/// Clones of a `MockClient` share the same expectations and recorded requests, so a clone can be
+/// given to a [`Client`](crate::Client) while keeping the original to set expectations and inspect
+/// the received requests.
+#[derive(Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    expectations: Vec<Expectation>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockClient {
+    /// Creates a new `MockClient` without any expectations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always respond to `GET /_matrix/client/versions` requests with the given versions.
+    pub fn with_versions(self, versions: &[MatrixVersion]) -> Self {
+        let versions = versions.iter().map(ToString::to_string).collect();
+        self.expect::<get_supported_versions::IncomingRequest>()
+            .always()
+            .respond_with(get_supported_versions::Response::new(versions));
+        self
+    }
+
+    /// Respond to the next `GET /_matrix/client/v3/sync` request with the given response.
+    ///
+    /// Several responses can be queued by calling this method several times, they are returned in
+    /// order.
+    pub fn push_sync_response(&self, response: sync_events::v3::Response) {
+        self.expect::<sync_events::v3::IncomingRequest>().respond_with(response);
+    }
+
+    /// Start setting an expectation for a request to the endpoint of `R`.
+    ///
+    /// The expectation is only added once the response is set, with
+    /// [`respond_with`][ExpectationBuilder::respond_with] or
+    /// [`respond_with_error`][ExpectationBuilder::respond_with_error].
+    pub fn expect<R: IncomingRequest + 'static>(&self) -> ExpectationBuilder<'_, R> {
+        ExpectationBuilder {
+            client: self,
+            matches: Box::new(|_| true),
+            always: false,
+            _request: PhantomData,
+        }
+    }
+
+    /// Get the requests to the endpoint of `R` that were received by this client, in order.
+    pub fn received<R: IncomingRequest + 'static>(&self) -> Vec<R> {
+        let state = self.state.lock().unwrap();
+        state.requests.iter().filter_map(|req| req.parse().ok()).collect()
+    }
+
+    /// Get all the HTTP requests that were received by this client, in order.
+    ///
+    /// This is useful to check the parts of the requests that are not in the request types, like
+    /// the headers.
+    pub fn received_http_requests(&self) -> Vec<http::Request<Vec<u8>>> {
+        let state = self.state.lock().unwrap();
+        state.requests.iter().map(|req| req.to_request(req.body.clone())).collect()
+    }
+
+    /// The number of requests that were received by this client.
+    pub fn request_count(&self) -> usize {
+        self.state.lock().unwrap().requests.len()
+    }
+
+    /// Checks that all the expectations that only match a single request were met.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some of these expectations didn't match any request.
+    pub fn assert_all_expectations_met(&self) {
+        let state = self.state.lock().unwrap();
+        let unmet: Vec<_> = state
+            .expectations
+            .iter()
+            .filter(|expectation| !expectation.always)
+            .map(|expectation| expectation.endpoint)
+            .collect();
+
+        assert!(unmet.is_empty(), "expected requests to these endpoints: {:?}", unmet);
+    }
+}
+
+impl Debug for MockClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("MockClient")
+            .field("expectations", &state.expectations.len())
+            .field("requests", &state.requests.len())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl HttpClient for MockClient {
+    type RequestBody = Vec<u8>;
+    type ResponseBody = Vec<u8>;
+    type Error = UnexpectedRequest;
+
+    async fn send_http_request(
+        &self,
+        req: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>, UnexpectedRequest> {
+        let req = RecordedRequest::new(req);
+        let mut state = self.state.lock().unwrap();
+
+        let position =
+            state.expectations.iter().position(|expectation| (expectation.matches)(&req));
+        let result = match position {
+            Some(position) if state.expectations[position].always => {
+                Ok(state.expectations[position].response.to_response())
+            }
+            Some(position) => Ok(state.expectations.remove(position).response.to_response()),
+            None => Err(UnexpectedRequest { method: req.method.clone(), uri: req.uri.clone() }),
+        };
+
+        state.requests.push(req);
+        result
+    }
+}
+
+/// A builder for an expectation of a [`MockClient`].
+///
+/// Created with [`MockClient::expect`].
+pub struct ExpectationBuilder<'a, R> {
+    client: &'a MockClient,
+    matches: Box<dyn Fn(&R) -> bool + Send>,
+    always: bool,
+    _request: PhantomData<R>,
+}
+
+impl<'a, R: IncomingRequest + 'static> ExpectationBuilder<'a, R> {
+    /// Only match requests for which the given function returns `true`.
+    pub fn matching(self, matches: impl Fn(&R) -> bool + Send + 'static) -> Self {
+        Self { matches: Box::new(matches), ..self }
+    }
+
+    /// Match any number of requests, instead of a single one.
+    pub fn always(self) -> Self {
+        Self { always: true, ..self }
+    }
+
+    /// Respond to the matching request with the given response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the response can't be converted to an HTTP response.
+    pub fn respond_with(self, response: R::OutgoingResponse) {
+        self.add(response.try_into_http_response().expect("response should be serializable"));
+    }
+
+    /// Respond to the matching request with the given error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the error can't be converted to an HTTP response.
+    pub fn respond_with_error(self, error: R::EndpointError) {
+        self.add(error.try_into_http_response().expect("error should be serializable"));
+    }
+
+    fn add(self, response: http::Response<Vec<u8>>) {
+        let matches = self.matches;
+        let expectation = Expectation {
+            endpoint: R::METADATA.name,
+            matches: Box::new(move |req| match req.parse::<R>() {
+                Ok(req) => matches(&req),
+                Err(_) => false,
+            }),
+            always: self.always,
+            response: RecordedResponse::new(response),
+        };
+
+        self.client.state.lock().unwrap().expectations.push(expectation);
+    }
+}
+
+impl<'a, R> Debug for ExpectationBuilder<'a, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpectationBuilder").field("always", &self.always).finish_non_exhaustive()
+    }
+}
+
+/// The error returned by a [`MockClient`] when no expectation matches a request.
+#[derive(Debug)]
+#[allow(clippy::exhaustive_structs)]
+pub struct UnexpectedRequest {
+    /// The HTTP method of the request.
+    pub method: Method,
+
+    /// The URI of the request.
+    pub uri: Uri,
+}
+
+impl Display for UnexpectedRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unexpected request: {} {}", self.method, self.uri)
+    }
+}
+
+impl StdError for UnexpectedRequest {}
+
+struct Expectation {
+    endpoint: &'static str,
+    matches: Box<dyn Fn(&RecordedRequest) -> bool + Send>,
+    always: bool,
+    response: RecordedResponse,
+}
+
+/// A request received by a `MockClient`.
+///
+/// `http::Request` is not `Clone`, so the parts are stored to be able to parse it several times.
+struct RecordedRequest {
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    fn new(req: http::Request<Vec<u8>>) -> Self {
+        let (parts, body) = req.into_parts();
+        Self { method: parts.method, uri: parts.uri, headers: parts.headers, body }
+    }
+
+    /// Parses this request as a request to the endpoint of `R`.
+    fn parse<R: IncomingRequest + 'static>(&self) -> Result<R, ()> {
+        let req = self.to_request(self.body.as_slice());
+        Router::new().route(|req: R| req).handle(req).map_err(|_| ())
+    }
+
+    /// Creates an `http::Request` with the parts of this request and the given body.
+    fn to_request<B>(&self, body: B) -> http::Request<B> {
+        let mut req = http::Request::new(body);
+        *req.method_mut() = self.method.clone();
+        *req.uri_mut() = self.uri.clone();
+        *req.headers_mut() = self.headers.clone();
+        req
+    }
+}
+
+/// The parts of an HTTP response, to be able to return it several times.
+struct RecordedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl RecordedResponse {
+    fn new(res: http::Response<Vec<u8>>) -> Self {
+        let (parts, body) = res.into_parts();
+        Self { status: parts.status, headers: parts.headers, body }
+    }
+
+    fn to_response(&self) -> http::Response<Vec<u8>> {
+        let mut res = http::Response::new(self.body.clone());
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_executor::block_on;
+    use http::StatusCode;
+    use ruma_client_api::{
+        error::{Error as ApiError, ErrorKind},
+        membership::forget_room,
+        sync::sync_events,
+    };
+    use ruma_common::{
+        api::{
+            error::{FromHttpResponseError, ServerError},
+            MatrixVersion,
+        },
+        room_id,
+    };
+
+    use super::MockClient;
+    use crate::{Client, Error};
+
+    fn client(mock: &MockClient) -> Client<MockClient> {
+        block_on(
+            Client::builder()
+                .homeserver_url("https://example.org".to_owned())
+                .access_token(Some("access_token".to_owned()))
+                .http_client(mock.clone()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn sync_responses_in_order() {
+        let mock = MockClient::new().with_versions(&[MatrixVersion::V1_2]);
+        mock.push_sync_response(sync_events::v3::Response::new("batch_1".to_owned()));
+        mock.push_sync_response(sync_events::v3::Response::new("batch_2".to_owned()));
+        let client = client(&mock);
+
+        for expected in ["batch_1", "batch_2"] {
+            let response = block_on(client.send_request(sync_events::v3::Request::new())).unwrap();
+            assert_eq!(response.next_batch, expected);
+        }
+
+        mock.assert_all_expectations_met();
+        assert_eq!(mock.received::<sync_events::v3::IncomingRequest>().len(), 2);
+        assert_eq!(mock.request_count(), 3);
+    }
+
+    #[test]
+    fn unexpected_request() {
+        let mock = MockClient::new().with_versions(&[MatrixVersion::V1_2]);
+        mock.expect::<forget_room::v3::IncomingRequest>()
+            .matching(|req| req.room_id == "!other:example.org")
+            .respond_with(forget_room::v3::Response::new());
+        let client = client(&mock);
+
+        let request = forget_room::v3::Request::new(room_id!("!room:example.org"));
+        let error = block_on(client.send_request(request)).unwrap_err();
+        assert!(matches!(error, Error::Response(error) if error.uri.path().ends_with("/forget")));
+
+        let received = mock.received::<forget_room::v3::IncomingRequest>();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].room_id, "!room:example.org");
+    }
+
+    #[test]
+    #[should_panic = "expected requests to these endpoints"]
+    fn unmet_expectation() {
+        let mock = MockClient::new();
+        mock.expect::<forget_room::v3::IncomingRequest>()
+            .respond_with(forget_room::v3::Response::new());
+
+        mock.assert_all_expectations_met();
+    }
+
+    #[test]
+    fn respond_with_error() {
+        let mock = MockClient::new().with_versions(&[MatrixVersion::V1_2]);
+        mock.expect::<forget_room::v3::IncomingRequest>().respond_with_error(ApiError {
+            kind: ErrorKind::Forbidden,
+            message: "You are still in the room".to_owned(),
+            status_code: StatusCode::FORBIDDEN,
+        });
+        let client = client(&mock);
+
+        let request = forget_room::v3::Request::new(room_id!("!room:example.org"));
+        let error = block_on(client.send_request(request)).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::FromHttpResponse(FromHttpResponseError::Server(ServerError::Known(ApiError {
+                kind: ErrorKind::Forbidden,
+                ..
+            })))
+        ));
+    }
+}
@@ -195,8 +195,10 @@ pub use ruma_macros::ruma_api;
 
 pub mod error;
 mod metadata;
+mod router;
 
 pub use metadata::{MatrixVersion, Metadata, VersioningDecision};
+pub use router::{RouteError, Router};
 
 use error::{FromHttpRequestError, FromHttpResponseError, IntoHttpError};
 
@@ -0,0 +1,309 @@
+//! A server-side request router built from the [`Metadata`] of [`IncomingRequest`] types.
+
+use std::fmt;
+
+use bytes::BufMut;
+use http::{header, Method, StatusCode};
+use percent_encoding::percent_decode_str;
+use serde_json::json;
+use thiserror::Error;
+
+use super::{
+    error::{FromHttpRequestError, IntoHttpError},
+    IncomingRequest, Metadata, OutgoingResponse,
+};
+
+type Handler<B, T> =
+    Box<dyn Fn(http::Request<B>, &[String]) -> Result<T, FromHttpRequestError> + Send + Sync>;
+
+/// A router that dispatches `http::Request`s to typed handlers.
+///
+/// Every endpoint added with [`Router::route`] is matched against all of the paths in its
+/// [`Metadata`] (`unstable_path`, `r0_path` and `stable_path`) and its HTTP method. The
+/// `:param` segments of the matched path are percent-decoded and passed to
+/// [`IncomingRequest::try_from_http_request`] in order, before the resulting request is handed
+/// to the handler registered for the endpoint.
+///
+/// `B` is the body type of incoming requests and `T` is the output of the handlers, for example
+/// an `http::Response` or a boxed future resolving to one.
+///
+/// # Examples
+///
+/// ```
+/// # mod get_thing {
+/// #     use ruma_common::api::ruma_api;
+/// #
+/// #     ruma_api! {
+/// #         metadata: {
+/// #             description: "Gets a thing.",
+/// #             method: GET,
+/// #             name: "get_thing",
+/// #             stable_path: "/_matrix/thing/:id",
+/// #             rate_limited: false,
+/// #             authentication: None,
+/// #             added: 1.1,
+/// #         }
+/// #
+/// #         request: {
+/// #             #[ruma_api(path)]
+/// #             pub id: String,
+/// #         }
+/// #
+/// #         response: {}
+/// #     }
+/// # }
+/// use ruma_common::api::{OutgoingResponse, Router};
+///
+/// let router = Router::new().route(|request: get_thing::Request| {
+///     assert_eq!(request.id, "abc");
+///     get_thing::Response {}.try_into_http_response::<Vec<u8>>().unwrap()
+/// });
+///
+/// let request = http::Request::get("/_matrix/thing/abc").body(Vec::new()).unwrap();
+/// let response = router.handle(request).unwrap();
+/// assert_eq!(response.status(), http::StatusCode::OK);
+/// ```
+pub struct Router<B, T> {
+    routes: Vec<Route<B, T>>,
+}
+
+struct Route<B, T> {
+    name: &'static str,
+    method: Method,
+    paths: Vec<PathTemplate>,
+    handler: Handler<B, T>,
+}
+
+impl<B, T> Router<B, T>
+where
+    B: AsRef<[u8]>,
+{
+    /// Creates an empty `Router`.
+    pub fn new() -> Self {
+        Self { routes: Vec::new() }
+    }
+
+    /// Adds the endpoint `R` to this router, with the given handler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `R::METADATA` contains no path at all.
+    pub fn route<R, F>(mut self, handler: F) -> Self
+    where
+        R: IncomingRequest,
+        F: Fn(R) -> T + Send + Sync + 'static,
+    {
+        let metadata = &R::METADATA;
+        let paths: Vec<_> = metadata_paths(metadata).map(PathTemplate::parse).collect();
+        assert!(!paths.is_empty(), "endpoint {} has no path", metadata.name);
+
+        self.routes.push(Route {
+            name: metadata.name,
+            method: metadata.method.clone(),
+            paths,
+            handler: Box::new(move |request, path_args| {
+                R::try_from_http_request(request, path_args).map(&handler)
+            }),
+        });
+
+        self
+    }
+
+    /// Finds the endpoint matching the given request and calls its handler.
+    ///
+    /// If several endpoints match the path of the request, the one with the most literal (i.e.
+    /// non-parameter) segments wins.
+    ///
+    /// Returns an error if no endpoint matches the path of the request, if the path matches but
+    /// the HTTP method doesn't, or if the request could not be converted to the endpoint's request
+    /// type. The error can be turned into a proper Matrix error response with
+    /// [`OutgoingResponse::try_into_http_response`].
+    pub fn handle(&self, request: http::Request<B>) -> Result<T, RouteError> {
+        let path = request.uri().path();
+        let mut allowed = Vec::new();
+        let mut best: Option<(&Route<B, T>, usize, Vec<String>)> = None;
+
+        for route in &self.routes {
+            for template in &route.paths {
+                let path_args = match template.matches(path) {
+                    Some(args) => args,
+                    None => continue,
+                };
+
+                if route.method != request.method() {
+                    if !allowed.contains(&route.method) {
+                        allowed.push(route.method.clone());
+                    }
+                    continue;
+                }
+
+                let literals = template.literal_count();
+                if best.as_ref().map_or(true, |(_, best_literals, _)| literals > *best_literals) {
+                    best = Some((route, literals, path_args?));
+                }
+            }
+        }
+
+        match best {
+            Some((route, _, path_args)) => (route.handler)(request, &path_args)
+                .map_err(|error| RouteError::Request { endpoint: route.name, error }),
+            None if allowed.is_empty() => Err(RouteError::NotFound),
+            None => Err(RouteError::MethodNotAllowed { allowed }),
+        }
+    }
+}
+
+impl<B, T> Default for Router<B, T>
+where
+    B: AsRef<[u8]>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B, T> fmt::Debug for Router<B, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.routes.iter().map(|route| (&route.method, route.name))).finish()
+    }
+}
+
+/// An error returned by [`Router::handle`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RouteError {
+    /// No endpoint matches the path of the request.
+    #[error("no endpoint matches the request path")]
+    NotFound,
+
+    /// At least one endpoint matches the path of the request, but none of them for its HTTP
+    /// method.
+    #[error("http method not allowed, expected one of {allowed:?}")]
+    MethodNotAllowed {
+        /// The HTTP methods of the endpoints matching the path of the request.
+        allowed: Vec<Method>,
+    },
+
+    /// A path parameter was not valid percent-encoded UTF-8.
+    #[error("invalid path parameter: {0}")]
+    InvalidPathParameter(#[from] std::str::Utf8Error),
+
+    /// The request could not be converted to the request type of the matched endpoint.
+    #[error("invalid request for endpoint {endpoint}: {error}")]
+    Request {
+        /// The name of the matched endpoint.
+        endpoint: &'static str,
+
+        /// The conversion error.
+        error: FromHttpRequestError,
+    },
+}
+
+impl RouteError {
+    /// The HTTP status code of the response for this error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
+            Self::InvalidPathParameter(_) | Self::Request { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// The Matrix error code of the response for this error.
+    pub fn errcode(&self) -> &'static str {
+        match self {
+            Self::NotFound | Self::MethodNotAllowed { .. } => "M_UNRECOGNIZED",
+            Self::InvalidPathParameter(_) => "M_INVALID_PARAM",
+            Self::Request { .. } => "M_BAD_JSON",
+        }
+    }
+}
+
+impl OutgoingResponse for RouteError {
+    fn try_into_http_response<T: Default + BufMut>(
+        self,
+    ) -> Result<http::Response<T>, IntoHttpError> {
+        let body = json!({
+            "errcode": self.errcode(),
+            "error": self.to_string(),
+        });
+
+        let mut response = http::Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .status(self.status_code());
+
+        if let Self::MethodNotAllowed { allowed } = &self {
+            let allowed: Vec<_> = allowed.iter().map(Method::as_str).collect();
+            response = response.header(header::ALLOW, allowed.join(", "));
+        }
+
+        response.body(crate::serde::json_to_buf(&body)?).map_err(Into::into)
+    }
+}
+
+fn metadata_paths(metadata: &Metadata) -> impl Iterator<Item = &'static str> {
+    IntoIterator::into_iter([metadata.unstable_path, metadata.r0_path, metadata.stable_path])
+        .flatten()
+}
+
+/// A path of an endpoint, split into segments.
+struct PathTemplate {
+    segments: Vec<Segment>,
+}
+
+enum Segment {
+    Literal(&'static str),
+    Parameter,
+}
+
+impl PathTemplate {
+    fn parse(path: &'static str) -> Self {
+        let segments = path
+            .split('/')
+            .map(|s| if s.starts_with(':') { Segment::Parameter } else { Segment::Literal(s) })
+            .collect();
+
+        Self { segments }
+    }
+
+    fn literal_count(&self) -> usize {
+        self.segments.iter().filter(|s| matches!(s, Segment::Literal(_))).count()
+    }
+
+    /// Matches the given request path against this template.
+    ///
+    /// Returns `None` if the path doesn't match, otherwise the percent-decoded values of the
+    /// path parameters in order.
+    fn matches(&self, path: &str) -> Option<Result<Vec<String>, std::str::Utf8Error>> {
+        let mut request_segments = path.split('/');
+        let mut raw_args = Vec::new();
+
+        for segment in &self.segments {
+            let request_segment = request_segments.next()?;
+            match segment {
+                Segment::Literal(literal) => {
+                    if *literal != request_segment {
+                        return None;
+                    }
+                }
+                Segment::Parameter => {
+                    if request_segment.is_empty() {
+                        return None;
+                    }
+                    raw_args.push(request_segment);
+                }
+            }
+        }
+
+        if request_segments.next().is_some() {
+            return None;
+        }
+
+        Some(
+            raw_args
+                .into_iter()
+                .map(|arg| percent_decode_str(arg).decode_utf8().map(Into::into))
+                .collect(),
+        )
+    }
+}
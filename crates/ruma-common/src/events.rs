@@ -123,6 +123,10 @@ pub mod macros {
 
 #[cfg(feature = "unstable-msc3246")]
 pub mod audio;
+#[cfg(feature = "unstable-msc3489")]
+pub mod beacon;
+#[cfg(feature = "unstable-msc3489")]
+pub mod beacon_info;
 pub mod call;
 pub mod direct;
 pub mod dummy;
@@ -146,12 +150,18 @@ pub mod notice;
 #[cfg(feature = "unstable-pdu")]
 pub mod pdu;
 pub mod policy;
+#[cfg(feature = "unstable-msc3381")]
+pub mod poll;
 pub mod presence;
 pub mod push_rules;
 #[cfg(feature = "unstable-msc2677")]
 pub mod reaction;
 pub mod receipt;
-#[cfg(feature = "unstable-msc2675")]
+#[cfg(any(
+    feature = "unstable-msc2675",
+    feature = "unstable-msc3381",
+    feature = "unstable-msc3489"
+))]
 pub mod relation;
 pub mod room;
 pub mod room_key;
@@ -0,0 +1,48 @@
+//! Types for the `m.beacon` event ([MSC3489]).
+//!
+//! [MSC3489]: https://github.com/matrix-org/matrix-spec-proposals/pull/3489
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use super::{location::LocationContent, relation::Reference};
+use crate::{MilliSecondsSinceUnixEpoch, OwnedEventId};
+
+/// The content of an `m.beacon` event.
+///
+/// This event contains a location of a live location sharing session. It references the
+/// `m.beacon_info` state event of the session.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.beacon", kind = MessageLike)]
+pub struct BeaconEventContent {
+    /// The `m.beacon_info` event this location belongs to.
+    #[serde(rename = "m.relates_to")]
+    pub relates_to: Reference,
+
+    /// The location of the beacon.
+    #[serde(rename = "m.location")]
+    pub location: LocationContent,
+
+    /// The time when the location was measured.
+    #[serde(rename = "m.ts")]
+    pub ts: MilliSecondsSinceUnixEpoch,
+}
+
+impl BeaconEventContent {
+    /// Creates a new `BeaconEventContent` with the given `m.beacon_info` event ID, geo URI and
+    /// optional timestamp.
+    ///
+    /// If `ts` is `None`, the current time is used.
+    pub fn new(
+        beacon_info_event_id: OwnedEventId,
+        geo_uri: String,
+        ts: Option<MilliSecondsSinceUnixEpoch>,
+    ) -> Self {
+        Self {
+            relates_to: Reference::new(beacon_info_event_id),
+            location: LocationContent::new(geo_uri),
+            ts: ts.unwrap_or_else(MilliSecondsSinceUnixEpoch::now),
+        }
+    }
+}
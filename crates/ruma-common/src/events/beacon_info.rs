@@ -0,0 +1,83 @@
+//! Types for the `m.beacon_info` event ([MSC3489]).
+//!
+//! [MSC3489]: https://github.com/matrix-org/matrix-spec-proposals/pull/3489
+
+use std::{convert::TryFrom, time::Duration};
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use super::location::AssetContent;
+use crate::{MilliSecondsSinceUnixEpoch, OwnedUserId};
+
+/// The content of an `m.beacon_info` event.
+///
+/// This is the state event describing a live location sharing session. Its state key is the ID of
+/// the user sharing their location. The locations are then sent with `m.beacon` events that
+/// reference this event.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.beacon_info", kind = State, state_key_type = OwnedUserId)]
+pub struct BeaconInfoEventContent {
+    /// The description of the location.
+    ///
+    /// It should be used to label the location on a map.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Whether the user is sharing their location live.
+    ///
+    /// This is set to `false` when the user stops sharing their location before the timeout.
+    pub live: bool,
+
+    /// The time when location sharing started.
+    #[serde(rename = "m.ts")]
+    pub ts: MilliSecondsSinceUnixEpoch,
+
+    /// The duration that the location sharing will be live.
+    ///
+    /// Meaning that the location will stop being shared at `ts + timeout`.
+    #[serde(with = "crate::serde::duration::ms")]
+    pub timeout: Duration,
+
+    /// The asset that the location is tracking.
+    #[serde(default, rename = "m.asset")]
+    pub asset: AssetContent,
+}
+
+impl BeaconInfoEventContent {
+    /// Creates a new `BeaconInfoEventContent` with the given description, timeout and live flag.
+    ///
+    /// The start time of the location sharing is set to the current time.
+    pub fn new(description: Option<String>, timeout: Duration, live: bool) -> Self {
+        Self {
+            description,
+            live,
+            ts: MilliSecondsSinceUnixEpoch::now(),
+            timeout,
+            asset: Default::default(),
+        }
+    }
+
+    /// Stops the live location sharing.
+    pub fn stop(&mut self) {
+        self.live = false;
+    }
+
+    /// Whether the location sharing is live at the given time.
+    ///
+    /// It is live if the `live` flag is set, and `time` is between the start of the location
+    /// sharing and its timeout.
+    pub fn is_live_at(&self, time: MilliSecondsSinceUnixEpoch) -> bool {
+        let start = u64::from(self.ts.get());
+        let timeout = u64::try_from(self.timeout.as_millis()).unwrap_or(u64::MAX);
+        let time = u64::from(time.get());
+
+        self.live && start <= time && time < start.saturating_add(timeout)
+    }
+
+    /// Whether the location sharing is live at the current time.
+    pub fn is_live(&self) -> bool {
+        self.is_live_at(MilliSecondsSinceUnixEpoch::now())
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! This module also contains types shared by events in its child namespaces.
 
+#[cfg(feature = "unstable-msc3401")]
+use ruma_macros::EventContent;
 use serde::{Deserialize, Serialize};
 
 use crate::{serde::StringEnum, PrivOwnedStr};
@@ -10,6 +12,106 @@ pub mod answer;
 pub mod candidates;
 pub mod hangup;
 pub mod invite;
+#[cfg(feature = "unstable-msc3401")]
+pub mod member;
+pub mod negotiate;
+pub mod reject;
+pub mod select_answer;
+
+/// The content of an `org.matrix.msc3401.call` event.
+///
+/// This is the state event describing a group call in a room, as defined in [MSC3401]. Its state
+/// key is the ID of the call.
+///
+/// [MSC3401]: https://github.com/matrix-org/matrix-spec-proposals/pull/3401
+#[cfg(feature = "unstable-msc3401")]
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "org.matrix.msc3401.call", kind = State, state_key_type = String)]
+pub struct CallEventContent {
+    /// The intent of the call.
+    #[serde(rename = "m.intent")]
+    pub intent: CallIntent,
+
+    /// The type of the call.
+    #[serde(rename = "m.type")]
+    pub call_type: CallType,
+
+    /// The reason why the call was terminated, if it was.
+    ///
+    /// The call is over if this is set.
+    #[serde(rename = "m.terminated", skip_serializing_if = "Option::is_none")]
+    pub terminated: Option<String>,
+
+    /// The name of the call.
+    #[serde(rename = "m.name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[cfg(feature = "unstable-msc3401")]
+impl CallEventContent {
+    /// Creates a new `CallEventContent` with the given intent and call type.
+    pub fn new(intent: CallIntent, call_type: CallType) -> Self {
+        Self { intent, call_type, terminated: None, name: None }
+    }
+}
+
+/// The intent of a group call.
+#[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
+#[cfg(feature = "unstable-msc3401")]
+#[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
+#[non_exhaustive]
+pub enum CallIntent {
+    /// The call should ring the members of the room.
+    #[ruma_enum(rename = "m.ring")]
+    Ring,
+
+    /// The members of the room should be prompted to join the call.
+    #[ruma_enum(rename = "m.prompt")]
+    Prompt,
+
+    /// The call is a conference that room members can join at any time, without ringing or
+    /// prompting.
+    #[ruma_enum(rename = "m.room")]
+    Room,
+
+    #[doc(hidden)]
+    _Custom(PrivOwnedStr),
+}
+
+#[cfg(feature = "unstable-msc3401")]
+impl CallIntent {
+    /// Creates a string slice from this `CallIntent`.
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+}
+
+/// The type of a group call.
+#[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
+#[cfg(feature = "unstable-msc3401")]
+#[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
+#[non_exhaustive]
+pub enum CallType {
+    /// A voice call.
+    #[ruma_enum(rename = "m.voice")]
+    Voice,
+
+    /// A video call.
+    #[ruma_enum(rename = "m.video")]
+    Video,
+
+    #[doc(hidden)]
+    _Custom(PrivOwnedStr),
+}
+
+#[cfg(feature = "unstable-msc3401")]
+impl CallType {
+    /// Creates a string slice from this `CallType`.
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+}
 
 /// A VoIP session description.
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -52,3 +154,54 @@ impl SessionDescriptionType {
         self.as_ref()
     }
 }
+
+/// Metadata about a VoIP stream.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct StreamMetadata {
+    /// The purpose of the stream.
+    pub purpose: StreamPurpose,
+
+    /// Whether the audio track of the stream is muted.
+    ///
+    /// Defaults to `false`.
+    #[serde(default, skip_serializing_if = "crate::serde::is_default")]
+    pub audio_muted: bool,
+
+    /// Whether the video track of the stream is muted.
+    ///
+    /// Defaults to `false`.
+    #[serde(default, skip_serializing_if = "crate::serde::is_default")]
+    pub video_muted: bool,
+}
+
+impl StreamMetadata {
+    /// Creates a new `StreamMetadata` with the given purpose.
+    pub fn new(purpose: StreamPurpose) -> Self {
+        Self { purpose, audio_muted: false, video_muted: false }
+    }
+}
+
+/// The purpose of a VoIP stream.
+#[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
+#[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
+#[non_exhaustive]
+pub enum StreamPurpose {
+    /// The stream contains the webcam and/or microphone of the user.
+    #[ruma_enum(rename = "m.usermedia")]
+    UserMedia,
+
+    /// The stream contains the screen-sharing media of the user.
+    #[ruma_enum(rename = "m.screenshare")]
+    ScreenShare,
+
+    #[doc(hidden)]
+    _Custom(PrivOwnedStr),
+}
+
+impl StreamPurpose {
+    /// Creates a string slice from this `StreamPurpose`.
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+}
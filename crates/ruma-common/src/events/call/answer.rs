@@ -2,11 +2,13 @@
 //!
 //! [`m.call.answer`]: https://spec.matrix.org/v1.2/client-server-api/#mcallanswer
 
-use js_int::UInt;
+use std::collections::BTreeMap;
+
 use ruma_macros::EventContent;
 use serde::{Deserialize, Serialize};
 
-use super::SessionDescription;
+use super::{SessionDescription, StreamMetadata};
+use crate::VoipVersionId;
 
 /// The content of an `m.call.answer` event.
 ///
@@ -23,13 +25,37 @@ pub struct CallAnswerEventContent {
     /// The ID of the call this event relates to.
     pub call_id: String,
 
+    /// A unique ID for this session for the duration of the call.
+    ///
+    /// Must be the same as the one sent by the previous invite from this session.
+    ///
+    /// Required in VoIP version 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
     /// The version of the VoIP specification this messages adheres to.
-    pub version: UInt,
+    pub version: VoipVersionId,
+
+    /// Metadata describing the streams that will be sent, keyed by their stream ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sdp_stream_metadata: BTreeMap<String, StreamMetadata>,
 }
 
 impl CallAnswerEventContent {
     /// Creates an `AnswerEventContent` with the given answer, call ID and VoIP version.
-    pub fn new(answer: SessionDescription, call_id: String, version: UInt) -> Self {
-        Self { answer, call_id, version }
+    pub fn new(answer: SessionDescription, call_id: String, version: VoipVersionId) -> Self {
+        Self { answer, call_id, party_id: None, version, sdp_stream_metadata: BTreeMap::new() }
+    }
+
+    /// Convenience method to create a VoIP version 0 `CallAnswerEventContent` with all the
+    /// required fields.
+    pub fn version_0(answer: SessionDescription, call_id: String) -> Self {
+        Self::new(answer, call_id, VoipVersionId::V0)
+    }
+
+    /// Convenience method to create a VoIP version 1 `CallAnswerEventContent` with all the
+    /// required fields.
+    pub fn version_1(answer: SessionDescription, call_id: String, party_id: String) -> Self {
+        Self { party_id: Some(party_id), ..Self::new(answer, call_id, VoipVersionId::V1) }
     }
 }
@@ -6,6 +6,8 @@ use js_int::UInt;
 use ruma_macros::EventContent;
 use serde::{Deserialize, Serialize};
 
+use crate::VoipVersionId;
+
 /// The content of an `m.call.candidates` event.
 ///
 /// This event is sent by callers after sending an invite and by the callee after answering. Its
@@ -17,18 +19,38 @@ pub struct CallCandidatesEventContent {
     /// The ID of the call this event relates to.
     pub call_id: String,
 
+    /// The unique ID for this session for the duration of the call.
+    ///
+    /// Must be the same as the one sent by the previous invite or answer from this session.
+    ///
+    /// Required in VoIP version 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
     /// A list of candidates.
     pub candidates: Vec<Candidate>,
 
     /// The version of the VoIP specification this messages adheres to.
-    pub version: UInt,
+    pub version: VoipVersionId,
 }
 
 impl CallCandidatesEventContent {
     /// Creates a new `CandidatesEventContent` with the given call id, candidate list and VoIP
     /// version.
-    pub fn new(call_id: String, candidates: Vec<Candidate>, version: UInt) -> Self {
-        Self { call_id, candidates, version }
+    pub fn new(call_id: String, candidates: Vec<Candidate>, version: VoipVersionId) -> Self {
+        Self { call_id, party_id: None, candidates, version }
+    }
+
+    /// Convenience method to create a VoIP version 0 `CallCandidatesEventContent` with all the
+    /// required fields.
+    pub fn version_0(call_id: String, candidates: Vec<Candidate>) -> Self {
+        Self::new(call_id, candidates, VoipVersionId::V0)
+    }
+
+    /// Convenience method to create a VoIP version 1 `CallCandidatesEventContent` with all the
+    /// required fields.
+    pub fn version_1(call_id: String, party_id: String, candidates: Vec<Candidate>) -> Self {
+        Self { party_id: Some(party_id), ..Self::new(call_id, candidates, VoipVersionId::V1) }
     }
 }
 
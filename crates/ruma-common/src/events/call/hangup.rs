@@ -2,11 +2,10 @@
 //!
 //! [`m.call.hangup`]: https://spec.matrix.org/v1.2/client-server-api/#mcallhangup
 
-use js_int::UInt;
 use ruma_macros::EventContent;
 use serde::{Deserialize, Serialize};
 
-use crate::{serde::StringEnum, PrivOwnedStr};
+use crate::{serde::StringEnum, PrivOwnedStr, VoipVersionId};
 
 /// The content of an `m.call.hangup` event.
 ///
@@ -19,26 +18,52 @@ pub struct CallHangupEventContent {
     /// The ID of the call this event relates to.
     pub call_id: String,
 
+    /// The unique ID for this session for the duration of the call.
+    ///
+    /// Must be the same as the one sent by the previous invite or answer from this session.
+    ///
+    /// Required in VoIP version 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
     /// The version of the VoIP specification this messages adheres to.
-    pub version: UInt,
+    pub version: VoipVersionId,
 
     /// Optional error reason for the hangup.
+    ///
+    /// In VoIP version 1, this should be `user_hangup` when the user naturally ends the call.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<Reason>,
 }
 
 impl CallHangupEventContent {
     /// Creates a new `HangupEventContent` with the given call ID and VoIP version.
-    pub fn new(call_id: String, version: UInt) -> Self {
-        Self { call_id, version, reason: None }
+    pub fn new(call_id: String, version: VoipVersionId) -> Self {
+        Self { call_id, party_id: None, version, reason: None }
+    }
+
+    /// Convenience method to create a VoIP version 0 `CallHangupEventContent` with all the
+    /// required fields.
+    pub fn version_0(call_id: String) -> Self {
+        Self::new(call_id, VoipVersionId::V0)
+    }
+
+    /// Convenience method to create a VoIP version 1 `CallHangupEventContent` with all the
+    /// required fields.
+    pub fn version_1(call_id: String, party_id: String, reason: Reason) -> Self {
+        Self {
+            party_id: Some(party_id),
+            reason: Some(reason),
+            ..Self::new(call_id, VoipVersionId::V1)
+        }
     }
 }
 
 /// A reason for a hangup.
 ///
-/// Should not be provided when the user naturally ends or rejects the call. When there was an error
-/// in the call negotiation, this should be `ice_failed` for when ICE negotiation fails or
-/// `invite_timeout` for when the other party did not answer in time.
+/// In VoIP version 0, should not be provided when the user naturally ends or rejects the call. When
+/// there was an error in the call negotiation, this should be `ice_failed` for when ICE negotiation
+/// fails or `invite_timeout` for when the other party did not answer in time.
 #[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
 #[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
 #[ruma_enum(rename_all = "snake_case")]
@@ -50,6 +75,33 @@ pub enum Reason {
     /// Party did not answer in time.
     InviteTimeout,
 
+    /// The connection timed out after the ICE negotiation succeeded.
+    ///
+    /// Added in VoIP version 1.
+    IceTimeout,
+
+    /// The user chose to end the call.
+    ///
+    /// Added in VoIP version 1.
+    UserHangup,
+
+    /// The client was unable to start capturing media in such a way as it is unable to continue
+    /// the call.
+    ///
+    /// Added in VoIP version 1.
+    UserMediaFailed,
+
+    /// The user is busy.
+    ///
+    /// Added in VoIP version 1.
+    UserBusy,
+
+    /// Some other failure occurred that meant the client was unable to continue the call rather
+    /// than the user choosing to end it.
+    ///
+    /// Added in VoIP version 1.
+    UnknownError,
+
     #[doc(hidden)]
     _Custom(PrivOwnedStr),
 }
@@ -2,11 +2,14 @@
 //!
 //! [`m.call.invite`]: https://spec.matrix.org/v1.2/client-server-api/#mcallinvite
 
+use std::collections::BTreeMap;
+
 use js_int::UInt;
 use ruma_macros::EventContent;
 use serde::{Deserialize, Serialize};
 
-use super::SessionDescription;
+use super::{SessionDescription, StreamMetadata};
+use crate::{OwnedUserId, VoipVersionId};
 
 /// The content of an `m.call.invite` event.
 ///
@@ -18,6 +21,12 @@ pub struct CallInviteEventContent {
     /// A unique identifier for the call.
     pub call_id: String,
 
+    /// A unique ID for this session for the duration of the call.
+    ///
+    /// Required in VoIP version 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+
     /// The time in milliseconds that the invite is valid for.
     ///
     /// Once the invite age exceeds this value, clients should discard it. They should also no
@@ -30,12 +39,55 @@ pub struct CallInviteEventContent {
     pub offer: SessionDescription,
 
     /// The version of the VoIP specification this messages adheres to.
-    pub version: UInt,
+    pub version: VoipVersionId,
+
+    /// The user ID of the person who is being invited.
+    ///
+    /// Invites without an invitee field are defined to be intended for any member of the room
+    /// other than the sender of the event.
+    ///
+    /// Added in VoIP version 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invitee: Option<OwnedUserId>,
+
+    /// Metadata describing the streams that will be sent, keyed by their stream ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sdp_stream_metadata: BTreeMap<String, StreamMetadata>,
 }
 
 impl CallInviteEventContent {
     /// Creates a new `InviteEventContent` with the given call ID, lifetime and VoIP version.
-    pub fn new(call_id: String, lifetime: UInt, offer: SessionDescription, version: UInt) -> Self {
-        Self { call_id, lifetime, offer, version }
+    pub fn new(
+        call_id: String,
+        lifetime: UInt,
+        offer: SessionDescription,
+        version: VoipVersionId,
+    ) -> Self {
+        Self {
+            call_id,
+            party_id: None,
+            lifetime,
+            offer,
+            version,
+            invitee: None,
+            sdp_stream_metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Convenience method to create a VoIP version 0 `CallInviteEventContent` with all the
+    /// required fields.
+    pub fn version_0(call_id: String, lifetime: UInt, offer: SessionDescription) -> Self {
+        Self::new(call_id, lifetime, offer, VoipVersionId::V0)
+    }
+
+    /// Convenience method to create a VoIP version 1 `CallInviteEventContent` with all the
+    /// required fields.
+    pub fn version_1(
+        call_id: String,
+        party_id: String,
+        lifetime: UInt,
+        offer: SessionDescription,
+    ) -> Self {
+        Self { party_id: Some(party_id), ..Self::new(call_id, lifetime, offer, VoipVersionId::V1) }
     }
 }
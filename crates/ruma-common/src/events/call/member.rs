@@ -0,0 +1,90 @@
+//! Types for the `org.matrix.msc3401.call.member` event.
+//!
+//! See [MSC3401].
+//!
+//! [MSC3401]: https://github.com/matrix-org/matrix-spec-proposals/pull/3401
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use super::StreamPurpose;
+use crate::{OwnedDeviceId, OwnedUserId};
+
+/// The content of an `org.matrix.msc3401.call.member` event.
+///
+/// This is the state event describing the group calls a user participates in, with which devices.
+/// Its state key is the ID of the user.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "org.matrix.msc3401.call.member", kind = State, state_key_type = OwnedUserId)]
+pub struct CallMemberEventContent {
+    /// The calls the user participates in.
+    #[serde(rename = "m.calls")]
+    pub calls: Vec<CallMembership>,
+}
+
+impl CallMemberEventContent {
+    /// Creates a new `CallMemberEventContent` with the given calls.
+    pub fn new(calls: Vec<CallMembership>) -> Self {
+        Self { calls }
+    }
+}
+
+/// The participation of a user in a group call.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct CallMembership {
+    /// The ID of the call, i.e. the state key of its `m.call` event.
+    #[serde(rename = "m.call_id")]
+    pub call_id: String,
+
+    /// The devices of the user that participate in the call.
+    #[serde(rename = "m.devices")]
+    pub devices: Vec<CallMemberDevice>,
+}
+
+impl CallMembership {
+    /// Creates a new `CallMembership` with the given call ID and devices.
+    pub fn new(call_id: String, devices: Vec<CallMemberDevice>) -> Self {
+        Self { call_id, devices }
+    }
+}
+
+/// A device participating in a group call.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct CallMemberDevice {
+    /// The ID of the device.
+    pub device_id: OwnedDeviceId,
+
+    /// The ID of the session of the device in the call.
+    ///
+    /// It changes every time the device joins the call again, to detect stale calls.
+    pub session_id: String,
+
+    /// The media feeds that the device sends.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feeds: Vec<CallFeed>,
+}
+
+impl CallMemberDevice {
+    /// Creates a new `CallMemberDevice` with the given device ID, session ID and feeds.
+    pub fn new(device_id: OwnedDeviceId, session_id: String, feeds: Vec<CallFeed>) -> Self {
+        Self { device_id, session_id, feeds }
+    }
+}
+
+/// A media feed sent by a device in a group call.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct CallFeed {
+    /// The purpose of the feed.
+    pub purpose: StreamPurpose,
+}
+
+impl CallFeed {
+    /// Creates a new `CallFeed` with the given purpose.
+    pub fn new(purpose: StreamPurpose) -> Self {
+        Self { purpose }
+    }
+}
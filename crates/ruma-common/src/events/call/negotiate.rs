@@ -0,0 +1,64 @@
+//! Types for the `m.call.negotiate` event.
+//!
+//! Added in VoIP version 1, see [MSC2746].
+//!
+//! [MSC2746]: https://github.com/matrix-org/matrix-spec-proposals/pull/2746
+
+use std::collections::BTreeMap;
+
+use js_int::UInt;
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use super::{SessionDescription, StreamMetadata};
+use crate::VoipVersionId;
+
+/// The content of an `m.call.negotiate` event.
+///
+/// This event is sent by either party after the call is established to renegotiate it. It can be
+/// used for media pause, hold/resume, ICE restarts and voice/video call up/downgrading.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.call.negotiate", kind = MessageLike)]
+pub struct CallNegotiateEventContent {
+    /// The ID of the call this event relates to.
+    pub call_id: String,
+
+    /// The unique ID for this session for the duration of the call.
+    ///
+    /// Must be the same as the one sent by the previous invite or answer from this session.
+    pub party_id: String,
+
+    /// The time in milliseconds that the negotiation is valid for.
+    pub lifetime: UInt,
+
+    /// The session description of the negotiation.
+    pub description: SessionDescription,
+
+    /// The version of the VoIP specification this messages adheres to.
+    pub version: VoipVersionId,
+
+    /// Metadata describing the streams that will be sent, keyed by their stream ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sdp_stream_metadata: BTreeMap<String, StreamMetadata>,
+}
+
+impl CallNegotiateEventContent {
+    /// Creates a `CallNegotiateEventContent` with the given call ID, party ID, lifetime and
+    /// description, for VoIP version 1.
+    pub fn new(
+        call_id: String,
+        party_id: String,
+        lifetime: UInt,
+        description: SessionDescription,
+    ) -> Self {
+        Self {
+            call_id,
+            party_id,
+            lifetime,
+            description,
+            version: VoipVersionId::V1,
+            sdp_stream_metadata: BTreeMap::new(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Types for the `m.call.reject` event.
+//!
+//! Added in VoIP version 1, see [MSC2746].
+//!
+//! [MSC2746]: https://github.com/matrix-org/matrix-spec-proposals/pull/2746
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use crate::VoipVersionId;
+
+/// The content of an `m.call.reject` event.
+///
+/// This event is sent by the callee to reject an invite. It must only be sent in response to an
+/// invite that is intended for the user.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.call.reject", kind = MessageLike)]
+pub struct CallRejectEventContent {
+    /// The ID of the call this event relates to.
+    pub call_id: String,
+
+    /// A unique ID for this session for the duration of the call.
+    pub party_id: String,
+
+    /// The version of the VoIP specification this messages adheres to.
+    pub version: VoipVersionId,
+}
+
+impl CallRejectEventContent {
+    /// Creates a `CallRejectEventContent` with the given call ID and party ID, for VoIP version 1.
+    pub fn new(call_id: String, party_id: String) -> Self {
+        Self { call_id, party_id, version: VoipVersionId::V1 }
+    }
+}
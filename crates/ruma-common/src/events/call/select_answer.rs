@@ -0,0 +1,41 @@
+//! Types for the `m.call.select_answer` event.
+//!
+//! Added in VoIP version 1, see [MSC2746].
+//!
+//! [MSC2746]: https://github.com/matrix-org/matrix-spec-proposals/pull/2746
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use crate::VoipVersionId;
+
+/// The content of an `m.call.select_answer` event.
+///
+/// This event is sent by the caller when it has chosen an answer, to let the other devices of the
+/// callee know that their answer was not chosen.
+#[derive(Clone, Debug, Deserialize, Serialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.call.select_answer", kind = MessageLike)]
+pub struct CallSelectAnswerEventContent {
+    /// The ID of the call this event relates to.
+    pub call_id: String,
+
+    /// The unique ID for this session for the duration of the call.
+    ///
+    /// Must be the same as the one sent by the previous invite from this session.
+    pub party_id: String,
+
+    /// The party ID for the selected answer.
+    pub selected_party_id: String,
+
+    /// The version of the VoIP specification this messages adheres to.
+    pub version: VoipVersionId,
+}
+
+impl CallSelectAnswerEventContent {
+    /// Creates a `CallSelectAnswerEventContent` with the given call ID, party ID and selected
+    /// party ID, for VoIP version 1.
+    pub fn new(call_id: String, party_id: String, selected_party_id: String) -> Self {
+        Self { call_id, party_id, selected_party_id, version: VoipVersionId::V1 }
+    }
+}
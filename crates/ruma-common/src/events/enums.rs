@@ -39,10 +39,15 @@ event_enum! {
     enum MessageLike {
         #[cfg(feature = "unstable-msc3246")]
         "m.audio" => super::audio,
+        #[cfg(feature = "unstable-msc3489")]
+        "m.beacon" => super::beacon,
         "m.call.answer" => super::call::answer,
         "m.call.invite" => super::call::invite,
         "m.call.hangup" => super::call::hangup,
         "m.call.candidates" => super::call::candidates,
+        "m.call.negotiate" => super::call::negotiate,
+        "m.call.reject" => super::call::reject,
+        "m.call.select_answer" => super::call::select_answer,
         #[cfg(feature = "unstable-msc1767")]
         "m.emote" => super::emote,
         #[cfg(feature = "unstable-msc3551")]
@@ -62,6 +67,12 @@ event_enum! {
         "m.message" => super::message,
         #[cfg(feature = "unstable-msc1767")]
         "m.notice" => super::notice,
+        #[cfg(feature = "unstable-msc3381")]
+        "m.poll.start" => super::poll::start,
+        #[cfg(feature = "unstable-msc3381")]
+        "m.poll.response" => super::poll::response,
+        #[cfg(feature = "unstable-msc3381")]
+        "m.poll.end" => super::poll::end,
         #[cfg(feature = "unstable-msc2677")]
         "m.reaction" => super::reaction,
         "m.room.encrypted" => super::room::encrypted,
@@ -77,6 +88,12 @@ event_enum! {
 
     /// Any state event.
     enum State {
+        #[cfg(feature = "unstable-msc3489")]
+        "m.beacon_info" => super::beacon_info,
+        #[cfg(feature = "unstable-msc3401")]
+        "org.matrix.msc3401.call" => super::call,
+        #[cfg(feature = "unstable-msc3401")]
+        "org.matrix.msc3401.call.member" => super::call::member,
         "m.policy.rule.room" => super::policy::rule::room,
         "m.policy.rule.server" => super::policy::rule::server,
         "m.policy.rule.user" => super::policy::rule::user,
@@ -305,6 +322,10 @@ impl AnyMessageLikeEventContent {
                     key: key.clone(),
                 }))
             }
+            #[cfg(feature = "unstable-msc3381")]
+            Self::PollResponse(ev) => Some(ev.relates_to.clone().into()),
+            #[cfg(feature = "unstable-msc3381")]
+            Self::PollEnd(ev) => Some(ev.relates_to.clone().into()),
             Self::RoomEncrypted(ev) => ev.relates_to.clone(),
             Self::RoomMessage(ev) => ev.relates_to.clone().map(Into::into),
             #[cfg(feature = "unstable-msc1767")]
@@ -325,10 +346,17 @@ impl AnyMessageLikeEventContent {
             Self::Image(ev) => ev.relates_to.clone().map(Into::into),
             #[cfg(feature = "unstable-msc3553")]
             Self::Video(ev) => ev.relates_to.clone().map(Into::into),
+            #[cfg(feature = "unstable-msc3489")]
+            Self::Beacon(ev) => Some(ev.relates_to.clone().into()),
+            #[cfg(feature = "unstable-msc3381")]
+            Self::PollStart(_) => None,
             Self::CallAnswer(_)
             | Self::CallInvite(_)
             | Self::CallHangup(_)
             | Self::CallCandidates(_)
+            | Self::CallNegotiate(_)
+            | Self::CallReject(_)
+            | Self::CallSelectAnswer(_)
             | Self::RoomMessageFeedback(_)
             | Self::RoomRedaction(_)
             | Self::Sticker(_)
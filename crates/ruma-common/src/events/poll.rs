@@ -0,0 +1,122 @@
+//! Modules for events in the `m.poll` namespace ([MSC3381]).
+//!
+//! This module also contains types shared by events in its child namespaces, and a helper to
+//! compile the results of a poll.
+//!
+//! [MSC3381]: https://github.com/matrix-org/matrix-spec-proposals/pull/3381
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryInto,
+};
+
+use indexmap::IndexMap;
+
+use self::{
+    response::{OriginalPollResponseEvent, OriginalSyncPollResponseEvent},
+    start::PollStartContent,
+};
+use crate::{MilliSecondsSinceUnixEpoch, UserId};
+
+pub mod end;
+pub mod response;
+pub mod start;
+
+/// The data of a poll response that is needed to compile the results of a poll.
+#[derive(Clone, Copy, Debug)]
+#[allow(clippy::exhaustive_structs)]
+pub struct PollResponseData<'a> {
+    /// The sender of the response.
+    pub sender: &'a UserId,
+
+    /// The time the response was sent.
+    pub origin_server_ts: MilliSecondsSinceUnixEpoch,
+
+    /// The IDs of the answers selected in the response.
+    pub selections: &'a [String],
+}
+
+impl OriginalPollResponseEvent {
+    /// Get the data of this response that is needed to compile the results of a poll.
+    pub fn data(&self) -> PollResponseData<'_> {
+        PollResponseData {
+            sender: &self.sender,
+            origin_server_ts: self.origin_server_ts,
+            selections: &self.content.poll_response.answers,
+        }
+    }
+}
+
+impl OriginalSyncPollResponseEvent {
+    /// Get the data of this response that is needed to compile the results of a poll.
+    pub fn data(&self) -> PollResponseData<'_> {
+        PollResponseData {
+            sender: &self.sender,
+            origin_server_ts: self.origin_server_ts,
+            selections: &self.content.poll_response.answers,
+        }
+    }
+}
+
+/// Compiles the results of a poll with the given responses, according to the rules of [MSC3381].
+///
+/// * Only the latest response of each user is taken into account.
+/// * Selections of answers that are not in the poll, and duplicate selections, are ignored.
+/// * Only the first `max_selections` of a response are taken into account.
+/// * A response without any valid selection is a spoiled vote, it cancels the previous responses of
+///   the user.
+/// * If `end_timestamp` is set, the responses sent after it are ignored. It should be the
+///   `origin_server_ts` of the first valid `m.poll.end` event of the poll.
+///
+/// It is the responsibility of the caller to only provide responses that reference the poll.
+///
+/// Returns a map of answer ID to the set of users that selected it, in the order of the answers
+/// of the poll.
+///
+/// [MSC3381]: https://github.com/matrix-org/matrix-spec-proposals/pull/3381
+pub fn compile_poll_results<'a>(
+    poll: &'a PollStartContent,
+    responses: impl IntoIterator<Item = PollResponseData<'a>>,
+    end_timestamp: Option<MilliSecondsSinceUnixEpoch>,
+) -> IndexMap<&'a str, BTreeSet<&'a UserId>> {
+    // Keep only the latest response of each user, sent before the end of the poll.
+    let mut latest_responses: BTreeMap<&UserId, PollResponseData<'_>> = BTreeMap::new();
+    for response in responses {
+        if end_timestamp.map_or(false, |end_ts| response.origin_server_ts > end_ts) {
+            continue;
+        }
+
+        match latest_responses.get(response.sender) {
+            Some(latest) if latest.origin_server_ts > response.origin_server_ts => {}
+            _ => {
+                latest_responses.insert(response.sender, response);
+            }
+        }
+    }
+
+    let mut results: IndexMap<&str, BTreeSet<&UserId>> =
+        poll.answers.iter().map(|answer| (answer.id.as_str(), BTreeSet::new())).collect();
+    let max_selections: usize = poll.max_selections.try_into().unwrap_or(usize::MAX);
+
+    for (sender, response) in latest_responses {
+        let mut selected = BTreeSet::new();
+
+        for selection in response.selections {
+            if selected.len() == max_selections {
+                break;
+            }
+
+            if results.contains_key(selection.as_str()) {
+                selected.insert(selection.as_str());
+            }
+        }
+
+        for selection in selected {
+            if let Some(voters) = results.get_mut(selection) {
+                voters.insert(sender);
+            }
+        }
+    }
+
+    results
+}
@@ -0,0 +1,50 @@
+//! Types for the `m.poll.end` event.
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{message::MessageContent, relation::Reference};
+
+/// The payload for a poll end event.
+#[derive(Clone, Debug, Serialize, Deserialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.poll.end", kind = MessageLike)]
+pub struct PollEndEventContent {
+    /// The poll end content of the message.
+    #[serde(rename = "m.poll.end")]
+    pub poll_end: PollEndContent,
+
+    /// The text representation of the message, for clients that don't support polls.
+    #[serde(flatten)]
+    pub message: MessageContent,
+
+    /// Information about the poll start event this ends.
+    #[serde(rename = "m.relates_to")]
+    pub relates_to: Reference,
+}
+
+impl PollEndEventContent {
+    /// Creates a new `PollEndEventContent` with the given text representation and relation.
+    pub fn new(message: MessageContent, relates_to: Reference) -> Self {
+        Self { poll_end: PollEndContent::new(), message, relates_to }
+    }
+
+    /// Creates a new `PollEndEventContent` with the given plain text representation and relation.
+    pub fn plain(message: impl Into<String>, relates_to: Reference) -> Self {
+        Self::new(MessageContent::plain(message), relates_to)
+    }
+}
+
+/// Poll end content.
+///
+/// This is currently empty.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct PollEndContent {}
+
+impl PollEndContent {
+    /// Creates a new empty `PollEndContent`.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
@@ -0,0 +1,44 @@
+//! Types for the `m.poll.response` event.
+
+use ruma_macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use crate::events::relation::Reference;
+
+/// The payload for a poll response event.
+#[derive(Clone, Debug, Serialize, Deserialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.poll.response", kind = MessageLike)]
+pub struct PollResponseEventContent {
+    /// The poll response content of the message.
+    #[serde(rename = "m.poll.response")]
+    pub poll_response: SelectionsContent,
+
+    /// Information about the poll start event this responds to.
+    #[serde(rename = "m.relates_to")]
+    pub relates_to: Reference,
+}
+
+impl PollResponseEventContent {
+    /// Creates a new `PollResponseEventContent` with the given poll response content and relation.
+    pub fn new(poll_response: SelectionsContent, relates_to: Reference) -> Self {
+        Self { poll_response, relates_to }
+    }
+}
+
+/// Poll response content.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct SelectionsContent {
+    /// The IDs of the selected answers of the poll.
+    ///
+    /// An empty list is a spoiled vote.
+    pub answers: Vec<String>,
+}
+
+impl SelectionsContent {
+    /// Creates a new `SelectionsContent` with the given answers.
+    pub fn new(answers: Vec<String>) -> Self {
+        Self { answers }
+    }
+}
@@ -0,0 +1,132 @@
+//! Types for the `m.poll.start` event.
+
+use js_int::{uint, UInt};
+use ruma_macros::{EventContent, StringEnum};
+use serde::{Deserialize, Serialize};
+
+use crate::{events::message::MessageContent, PrivOwnedStr};
+
+/// The payload for a poll start event.
+#[derive(Clone, Debug, Serialize, Deserialize, EventContent)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[ruma_event(type = "m.poll.start", kind = MessageLike)]
+pub struct PollStartEventContent {
+    /// The poll start content of the message.
+    #[serde(rename = "m.poll.start")]
+    pub poll_start: PollStartContent,
+
+    /// The text representation of the message, for clients that don't support polls.
+    #[serde(flatten)]
+    pub message: MessageContent,
+}
+
+impl PollStartEventContent {
+    /// Creates a new `PollStartEventContent` with the given poll start content and text
+    /// representation.
+    pub fn new(message: MessageContent, poll_start: PollStartContent) -> Self {
+        Self { poll_start, message }
+    }
+
+    /// Creates a new `PollStartEventContent` with the given poll start content, and a plain text
+    /// representation generated from the question and the answers.
+    pub fn with_plain_text_fallback(poll_start: PollStartContent) -> Self {
+        let message = MessageContent::plain(poll_start.plain_text_fallback());
+        Self { poll_start, message }
+    }
+}
+
+/// Poll start content.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct PollStartContent {
+    /// The question of the poll.
+    pub question: MessageContent,
+
+    /// The kind of the poll.
+    #[serde(default)]
+    pub kind: PollKind,
+
+    /// The maximum number of answers a user can select.
+    ///
+    /// Must be at least 1. Defaults to `1`.
+    #[serde(default = "PollStartContent::default_max_selections")]
+    pub max_selections: UInt,
+
+    /// The possible answers to the poll.
+    pub answers: Vec<PollAnswer>,
+}
+
+impl PollStartContent {
+    /// Creates a new `PollStartContent` with the given question, kind and answers.
+    pub fn new(question: MessageContent, kind: PollKind, answers: Vec<PollAnswer>) -> Self {
+        Self { question, kind, max_selections: Self::default_max_selections(), answers }
+    }
+
+    /// Generates a plain text representation of this poll, with the question followed by the
+    /// numbered list of answers.
+    pub fn plain_text_fallback(&self) -> String {
+        let mut fallback = self.question.find_plain().unwrap_or_default().to_owned();
+
+        for (i, answer) in self.answers.iter().enumerate() {
+            let text = answer.answer.find_plain().unwrap_or(&answer.id);
+            fallback.push_str(&format!("\n{}. {}", i + 1, text));
+        }
+
+        fallback
+    }
+
+    fn default_max_selections() -> UInt {
+        uint!(1)
+    }
+}
+
+/// The kind of poll.
+#[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
+#[derive(Clone, Debug, PartialEq, Eq, StringEnum)]
+#[non_exhaustive]
+pub enum PollKind {
+    /// The results are revealed once the poll is closed.
+    #[ruma_enum(rename = "m.poll.undisclosed")]
+    Undisclosed,
+
+    /// The votes are visible up until and including when the poll is closed.
+    #[ruma_enum(rename = "m.poll.disclosed")]
+    Disclosed,
+
+    #[doc(hidden)]
+    _Custom(PrivOwnedStr),
+}
+
+impl PollKind {
+    /// Creates a string slice from this `PollKind`.
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+}
+
+impl Default for PollKind {
+    fn default() -> Self {
+        Self::Undisclosed
+    }
+}
+
+/// An answer to a poll.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+pub struct PollAnswer {
+    /// The ID of the answer.
+    ///
+    /// This must be unique among the answers of a poll.
+    pub id: String,
+
+    /// The text representation of the answer.
+    #[serde(flatten)]
+    pub answer: MessageContent,
+}
+
+impl PollAnswer {
+    /// Creates a new `PollAnswer` with the given ID and text representation.
+    pub fn new(id: String, answer: MessageContent) -> Self {
+        Self { id, answer }
+    }
+}
@@ -10,6 +10,8 @@ use std::{
 use ruma_macros::EventContent;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "unstable-msc3771")]
+use crate::receipt::ReceiptThread;
 use crate::{
     receipt::ReceiptType, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId, UserId,
 };
@@ -68,6 +70,15 @@ pub struct Receipt {
     /// The time when the receipt was sent.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ts: Option<MilliSecondsSinceUnixEpoch>,
+
+    /// The thread this receipt applies to.
+    ///
+    /// This is defined in [MSC3771].
+    ///
+    /// [MSC3771]: https://github.com/matrix-org/matrix-spec-proposals/pull/3771
+    #[cfg(feature = "unstable-msc3771")]
+    #[serde(rename = "thread_id", default, skip_serializing_if = "crate::serde::is_default")]
+    pub thread: ReceiptThread,
 }
 
 impl Receipt {
@@ -75,6 +86,10 @@ impl Receipt {
     ///
     /// To create an empty receipt instead, use [`Receipt::default`].
     pub fn new(ts: MilliSecondsSinceUnixEpoch) -> Self {
-        Self { ts: Some(ts) }
+        Self {
+            ts: Some(ts),
+            #[cfg(feature = "unstable-msc3771")]
+            thread: ReceiptThread::Unthreaded,
+        }
     }
 }
@@ -1,12 +1,37 @@
-//! Types describing event relations after MSC 2674, 2675, 2676, 2677.
+//! Types describing event relations after MSC 2674, 2675, 2676, 2677, and the `m.reference`
+//! relation used by several other MSCs.
 
 use std::fmt::Debug;
 
+#[cfg(any(feature = "unstable-msc2677", feature = "unstable-msc3440"))]
 use js_int::UInt;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "unstable-msc3440")]
 use super::AnySyncMessageLikeEvent;
-use crate::{serde::Raw, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId};
+#[cfg(feature = "unstable-msc3440")]
+use crate::serde::Raw;
+#[cfg(any(feature = "unstable-msc2676", feature = "unstable-msc2677"))]
+use crate::MilliSecondsSinceUnixEpoch;
+use crate::OwnedEventId;
+#[cfg(feature = "unstable-msc2676")]
+use crate::OwnedUserId;
+
+/// An `m.reference` relation to another event.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
+#[serde(tag = "rel_type", rename = "m.reference")]
+pub struct Reference {
+    /// The ID of the event being referenced.
+    pub event_id: OwnedEventId,
+}
+
+impl Reference {
+    /// Creates a new `Reference` with the given event ID.
+    pub fn new(event_id: OwnedEventId) -> Self {
+        Self { event_id }
+    }
+}
 
 /// Summary of all reactions with the given key to an event.
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
@@ -121,6 +146,7 @@ impl BundledThread {
 
 /// Precompiled list of relations to this event grouped by relation type.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg(feature = "unstable-msc2675")]
 #[cfg_attr(not(feature = "unstable-exhaustive-types"), non_exhaustive)]
 pub struct Relations {
     /// Annotation relations.
@@ -139,6 +165,7 @@ pub struct Relations {
     pub thread: Option<BundledThread>,
 }
 
+#[cfg(feature = "unstable-msc2675")]
 impl Relations {
     /// Creates a new empty `Relations`.
     pub fn new() -> Self {
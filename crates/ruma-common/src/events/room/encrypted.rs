@@ -109,6 +109,13 @@ pub enum Relation {
     _Custom,
 }
 
+#[cfg(any(feature = "unstable-msc3381", feature = "unstable-msc3489"))]
+impl From<crate::events::relation::Reference> for Relation {
+    fn from(reference: crate::events::relation::Reference) -> Self {
+        Self::Reference(Reference { event_id: reference.event_id })
+    }
+}
+
 impl From<message::Relation> for Relation {
     fn from(rel: message::Relation) -> Self {
         match rel {
@@ -3,6 +3,8 @@
 use serde::{de, Deserialize};
 use serde_json::value::RawValue as RawJsonValue;
 
+#[cfg(any(feature = "unstable-msc3488", feature = "unstable-msc3551"))]
+use super::MessageContent;
 #[cfg(feature = "unstable-msc3245")]
 use super::VoiceContent;
 #[cfg(feature = "unstable-msc3488")]
@@ -13,7 +15,7 @@ use super::{
 #[cfg(feature = "unstable-msc3246")]
 use super::{AudioContent, AudioInfo, AudioMessageEventContent};
 #[cfg(feature = "unstable-msc3551")]
-use super::{FileContent, FileInfo, FileMessageEventContent, MediaSource, MessageContent};
+use super::{FileContent, FileInfo, FileMessageEventContent, MediaSource};
 #[cfg(feature = "unstable-msc3552")]
 use super::{ImageContent, ImageInfo, ImageMessageEventContent, ThumbnailContent};
 use super::{MessageType, Relation, RoomMessageEventContent};
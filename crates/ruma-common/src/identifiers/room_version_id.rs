@@ -54,6 +54,9 @@ pub enum RoomVersionId {
     /// A version 9 room.
     V9,
 
+    /// A version 10 room.
+    V10,
+
     #[doc(hidden)]
     _Custom(CustomRoomVersion),
 }
@@ -73,6 +76,7 @@ impl RoomVersionId {
             Self::V7 => "7",
             Self::V8 => "8",
             Self::V9 => "9",
+            Self::V10 => "10",
             Self::_Custom(version) => version.as_str(),
         }
     }
@@ -95,6 +99,7 @@ impl From<RoomVersionId> for String {
             RoomVersionId::V7 => "7".to_owned(),
             RoomVersionId::V8 => "8".to_owned(),
             RoomVersionId::V9 => "9".to_owned(),
+            RoomVersionId::V10 => "10".to_owned(),
             RoomVersionId::_Custom(version) => version.into(),
         }
     }
@@ -161,6 +166,7 @@ where
         "7" => RoomVersionId::V7,
         "8" => RoomVersionId::V8,
         "9" => RoomVersionId::V9,
+        "10" => RoomVersionId::V10,
         custom => {
             ruma_identifiers_validation::room_version_id::validate(custom)?;
             RoomVersionId::_Custom(CustomRoomVersion(room_version_id.into()))
@@ -299,6 +305,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn valid_version_10_room_version_id() {
+        assert_eq!(
+            RoomVersionId::try_from("10").expect("Failed to create RoomVersionId.").as_ref(),
+            "10"
+        );
+        assert_eq!(RoomVersionId::try_from("10").unwrap(), RoomVersionId::V10);
+    }
+
     #[test]
     fn valid_custom_room_version_id() {
         assert_eq!(
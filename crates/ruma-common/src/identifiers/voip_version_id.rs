@@ -0,0 +1,230 @@
+//! Matrix VoIP version identifier.
+
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use js_int::UInt;
+use ruma_macros::DisplayAsRefStr;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{IdParseError, PrivOwnedStr};
+
+/// A Matrix VoIP version ID.
+///
+/// A `VoipVersionId` representing VoIP version 0 is serialized as the integer `0`, the other
+/// versions are serialized as strings. Both integers and strings are accepted when deserializing
+/// any version.
+///
+/// ```
+/// # use std::convert::TryFrom;
+/// # use ruma_common::VoipVersionId;
+/// assert_eq!(VoipVersionId::try_from("1").unwrap(), VoipVersionId::V1);
+/// assert_eq!(VoipVersionId::V0.as_str(), "0");
+/// ```
+///
+/// Custom versions or ones that were introduced into the specification after this code was
+/// written are represented by a hidden enum variant. You can still construct them the same, and
+/// check for them using one of `VoipVersionId`s `PartialEq` implementations or through
+/// `.as_str()`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, DisplayAsRefStr)]
+#[non_exhaustive]
+pub enum VoipVersionId {
+    /// A version 0 VoIP call.
+    V0,
+
+    /// A version 1 VoIP call.
+    V1,
+
+    #[doc(hidden)]
+    _Custom(PrivOwnedStr),
+}
+
+impl VoipVersionId {
+    /// Creates a string slice from this `VoipVersionId`.
+    pub fn as_str(&self) -> &str {
+        match &self {
+            Self::V0 => "0",
+            Self::V1 => "1",
+            Self::_Custom(PrivOwnedStr(version)) => version,
+        }
+    }
+
+    /// Creates a byte slice from this `VoipVersionId`.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+impl From<VoipVersionId> for String {
+    fn from(id: VoipVersionId) -> Self {
+        match id {
+            VoipVersionId::_Custom(PrivOwnedStr(version)) => version.into(),
+            _ => id.as_str().to_owned(),
+        }
+    }
+}
+
+impl AsRef<str> for VoipVersionId {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Serialize for VoipVersionId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::V0 => serializer.serialize_u64(0),
+            _ => serializer.serialize_str(self.as_str()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VoipVersionId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VoipVersionIdVisitor;
+
+        impl<'de> Visitor<'de> for VoipVersionIdVisitor {
+            type Value = VoipVersionId;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an integer or a string")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let uint = UInt::try_from(value).map_err(de::Error::custom)?;
+                Ok(uint.into())
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                let uint = UInt::try_from(value).map_err(de::Error::custom)?;
+                Ok(uint.into())
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                VoipVersionId::try_from(value).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(VoipVersionIdVisitor)
+    }
+}
+
+/// Attempts to create a new Matrix VoIP version ID from a string representation.
+fn try_from<S>(voip_version_id: S) -> Result<VoipVersionId, IdParseError>
+where
+    S: AsRef<str> + Into<Box<str>>,
+{
+    let version = match voip_version_id.as_ref() {
+        "0" => VoipVersionId::V0,
+        "1" => VoipVersionId::V1,
+        "" => return Err(IdParseError::Empty),
+        _ => VoipVersionId::_Custom(PrivOwnedStr(voip_version_id.into())),
+    };
+
+    Ok(version)
+}
+
+impl FromStr for VoipVersionId {
+    type Err = IdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        try_from(s)
+    }
+}
+
+impl TryFrom<&str> for VoipVersionId {
+    type Error = IdParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        try_from(s)
+    }
+}
+
+impl TryFrom<String> for VoipVersionId {
+    type Error = IdParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        try_from(s)
+    }
+}
+
+impl From<UInt> for VoipVersionId {
+    fn from(u: UInt) -> Self {
+        match u64::from(u) {
+            0 => Self::V0,
+            1 => Self::V1,
+            _ => Self::_Custom(PrivOwnedStr(u.to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use js_int::uint;
+    use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+    use super::VoipVersionId;
+
+    #[test]
+    fn valid_version_0() {
+        assert_eq!(VoipVersionId::from(uint!(0)), VoipVersionId::V0);
+        assert_eq!(VoipVersionId::try_from("0"), Ok(VoipVersionId::V0));
+    }
+
+    #[test]
+    fn uint_version() {
+        assert_eq!(VoipVersionId::from(uint!(1)), VoipVersionId::V1);
+        assert_eq!(VoipVersionId::from(uint!(2)).as_str(), "2");
+    }
+
+    #[test]
+    fn valid_string_version() {
+        assert_eq!(VoipVersionId::try_from("1"), Ok(VoipVersionId::V1));
+
+        let custom = VoipVersionId::try_from("io.ruma.2").unwrap();
+        assert_eq!(custom.as_str(), "io.ruma.2");
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(to_json_value(VoipVersionId::V0).unwrap(), json!(0));
+        assert_eq!(to_json_value(VoipVersionId::V1).unwrap(), json!("1"));
+        assert_eq!(
+            to_json_value(VoipVersionId::try_from("io.ruma.2").unwrap()).unwrap(),
+            json!("io.ruma.2")
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(from_json_value::<VoipVersionId>(json!(0)).unwrap(), VoipVersionId::V0);
+        assert_eq!(from_json_value::<VoipVersionId>(json!("1")).unwrap(), VoipVersionId::V1);
+        assert_eq!(
+            from_json_value::<VoipVersionId>(json!("io.ruma.2")).unwrap().as_str(),
+            "io.ruma.2"
+        );
+        assert_eq!(from_json_value::<VoipVersionId>(json!(1)).unwrap(), VoipVersionId::V1);
+        assert_eq!(from_json_value::<VoipVersionId>(json!(2)).unwrap().as_str(), "2");
+        assert!(from_json_value::<VoipVersionId>(json!(-1)).is_err());
+        assert!(from_json_value::<VoipVersionId>(json!("")).is_err());
+    }
+}
@@ -24,11 +24,12 @@ use tracing::instrument;
 
 use crate::{
     serde::{Raw, StringEnum},
-    PrivOwnedStr,
+    PrivOwnedStr, RoomId, UserId,
 };
 
 mod action;
 mod condition;
+mod error;
 mod iter;
 mod predefined;
 
@@ -37,6 +38,7 @@ pub use self::{
     condition::{
         ComparisonOperator, FlattenedJson, PushCondition, PushConditionRoomCtx, RoomMemberCountIs,
     },
+    error::{InsertPushRuleError, RemovePushRuleError, RuleNotFoundError},
     iter::{AnyPushRule, AnyPushRuleRef, RulesetIntoIter, RulesetIter},
 };
 
@@ -96,6 +98,159 @@ impl Ruleset {
         }
     }
 
+    /// Get the rule of the given kind and with the given `rule_id` in this rule set, if any.
+    pub fn get(&self, kind: RuleKind, rule_id: impl AsRef<str>) -> Option<AnyPushRuleRef<'_>> {
+        let rule_id = rule_id.as_ref();
+
+        match kind {
+            RuleKind::Override => self.override_.get(rule_id).map(AnyPushRuleRef::Override),
+            RuleKind::Underride => self.underride.get(rule_id).map(AnyPushRuleRef::Underride),
+            RuleKind::Sender => self.sender.get(rule_id).map(AnyPushRuleRef::Sender),
+            RuleKind::Room => self.room.get(rule_id).map(AnyPushRuleRef::Room),
+            RuleKind::Content => self.content.get(rule_id).map(AnyPushRuleRef::Content),
+            RuleKind::_Custom(_) => None,
+        }
+    }
+
+    /// Inserts a user-defined rule in the rule set, like the [`set_pushrule`] endpoint.
+    ///
+    /// If a rule with the same kind and `rule_id` exists, it is replaced.
+    ///
+    /// If `after` is set, the rule is moved just after the user-defined rule of the same kind with
+    /// that ID. If `before` is set, the rule is moved just before the user-defined rule of the same
+    /// kind with that ID. If neither of them is set, a new rule gets the highest priority of the
+    /// user-defined rules of the same kind, and a replaced rule keeps its position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rule ID is reserved for server-default rules or is invalid for the
+    /// kind of the rule, or if the `after` or `before` rules are invalid.
+    ///
+    /// [`set_pushrule`]: https://spec.matrix.org/v1.2/client-server-api/#put_matrixclientv3pushrulesscopekindruleid
+    pub fn insert(
+        &mut self,
+        rule: AnyPushRule,
+        after: Option<&str>,
+        before: Option<&str>,
+    ) -> Result<(), InsertPushRuleError> {
+        let rule_id = rule.rule_id();
+        if rule_id.starts_with('.') {
+            return Err(InsertPushRuleError::ServerDefaultRuleId);
+        }
+
+        let rule_id_is_valid = match &rule {
+            AnyPushRule::Room(_) => RoomId::parse(rule_id).is_ok(),
+            AnyPushRule::Sender(_) => UserId::parse(rule_id).is_ok(),
+            _ => true,
+        };
+        if !rule_id_is_valid {
+            return Err(InsertPushRuleError::InvalidRuleId);
+        }
+
+        if after.into_iter().chain(before).any(|rule_id| rule_id.starts_with('.')) {
+            return Err(InsertPushRuleError::RelativeToServerDefaultRule);
+        }
+
+        match rule {
+            AnyPushRule::Override(r) => {
+                // `.m.rule.master` always has the highest priority.
+                let default_position = self
+                    .override_
+                    .get_index(0)
+                    .map_or(0, |first| (first.rule_id == ".m.rule.master").into());
+                insert_and_move_rule(&mut self.override_, r, default_position, after, before)
+            }
+            AnyPushRule::Underride(r) => {
+                insert_and_move_rule(&mut self.underride, r, 0, after, before)
+            }
+            AnyPushRule::Content(r) => insert_and_move_rule(&mut self.content, r, 0, after, before),
+            AnyPushRule::Room(r) => insert_and_move_rule(&mut self.room, r, 0, after, before),
+            AnyPushRule::Sender(r) => insert_and_move_rule(&mut self.sender, r, 0, after, before),
+        }
+    }
+
+    /// Removes a user-defined rule from the rule set, like the [`delete_pushrule`] endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rule is a server-default rule or if it was not found.
+    ///
+    /// [`delete_pushrule`]: https://spec.matrix.org/v1.2/client-server-api/#delete_matrixclientv3pushrulesscopekindruleid
+    pub fn remove(
+        &mut self,
+        kind: RuleKind,
+        rule_id: impl AsRef<str>,
+    ) -> Result<(), RemovePushRuleError> {
+        let rule_id = rule_id.as_ref();
+
+        match kind {
+            RuleKind::Override => remove_rule(&mut self.override_, rule_id),
+            RuleKind::Underride => remove_rule(&mut self.underride, rule_id),
+            RuleKind::Sender => remove_rule(&mut self.sender, rule_id),
+            RuleKind::Room => remove_rule(&mut self.room, rule_id),
+            RuleKind::Content => remove_rule(&mut self.content, rule_id),
+            RuleKind::_Custom(_) => Err(RemovePushRuleError::NotFound),
+        }
+    }
+
+    /// Enables or disables a rule in the rule set, like the [`set_pushrule_enabled`] endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rule was not found.
+    ///
+    /// [`set_pushrule_enabled`]: https://spec.matrix.org/v1.2/client-server-api/#put_matrixclientv3pushrulesscopekindruleidenabled
+    pub fn set_enabled(
+        &mut self,
+        kind: RuleKind,
+        rule_id: impl AsRef<str>,
+        enabled: bool,
+    ) -> Result<(), RuleNotFoundError> {
+        let rule_id = rule_id.as_ref();
+
+        match kind {
+            RuleKind::Override => {
+                update_rule(&mut self.override_, rule_id, |r| r.enabled = enabled)
+            }
+            RuleKind::Underride => {
+                update_rule(&mut self.underride, rule_id, |r| r.enabled = enabled)
+            }
+            RuleKind::Sender => update_rule(&mut self.sender, rule_id, |r| r.enabled = enabled),
+            RuleKind::Room => update_rule(&mut self.room, rule_id, |r| r.enabled = enabled),
+            RuleKind::Content => update_rule(&mut self.content, rule_id, |r| r.enabled = enabled),
+            RuleKind::_Custom(_) => Err(RuleNotFoundError),
+        }
+    }
+
+    /// Sets the actions of a rule in the rule set, like the [`set_pushrule_actions`] endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rule was not found.
+    ///
+    /// [`set_pushrule_actions`]: https://spec.matrix.org/v1.2/client-server-api/#put_matrixclientv3pushrulesscopekindruleidactions
+    pub fn set_actions(
+        &mut self,
+        kind: RuleKind,
+        rule_id: impl AsRef<str>,
+        actions: Vec<Action>,
+    ) -> Result<(), RuleNotFoundError> {
+        let rule_id = rule_id.as_ref();
+
+        match kind {
+            RuleKind::Override => {
+                update_rule(&mut self.override_, rule_id, |r| r.actions = actions)
+            }
+            RuleKind::Underride => {
+                update_rule(&mut self.underride, rule_id, |r| r.actions = actions)
+            }
+            RuleKind::Sender => update_rule(&mut self.sender, rule_id, |r| r.actions = actions),
+            RuleKind::Room => update_rule(&mut self.room, rule_id, |r| r.actions = actions),
+            RuleKind::Content => update_rule(&mut self.content, rule_id, |r| r.actions = actions),
+            RuleKind::_Custom(_) => Err(RuleNotFoundError),
+        }
+    }
+
     /// Get the first push rule that applies to this event, if any.
     ///
     /// # Arguments
@@ -132,6 +287,112 @@ impl Ruleset {
     }
 }
 
+/// Access to the fields of a push rule that are needed to edit a `Ruleset`.
+trait RuleFields {
+    fn rule_id(&self) -> &str;
+    fn is_default(&self) -> bool;
+}
+
+macro_rules! impl_rule_fields {
+    ($($ty:ident),*) => {
+        $(
+            impl RuleFields for $ty {
+                fn rule_id(&self) -> &str {
+                    &self.rule_id
+                }
+
+                fn is_default(&self) -> bool {
+                    self.default
+                }
+            }
+        )*
+    };
+}
+
+impl_rule_fields!(SimplePushRule, PatternedPushRule, ConditionalPushRule);
+
+/// Inserts the rule in the set, or replaces the rule with the same ID, and moves it to the
+/// requested position.
+fn insert_and_move_rule<T>(
+    set: &mut IndexSet<T>,
+    rule: T,
+    default_position: usize,
+    after: Option<&str>,
+    before: Option<&str>,
+) -> Result<(), InsertPushRuleError>
+where
+    T: Hash + Eq + RuleFields,
+    str: Equivalent<T>,
+{
+    let old_index = set.get_index_of(rule.rule_id());
+
+    // The index of the given rule, once the rule being inserted is removed from the set.
+    let index_of = |rule_id: &str| {
+        let index = set
+            .get_index_of(rule_id)
+            .filter(|&index| Some(index) != old_index)
+            .ok_or(InsertPushRuleError::UnknownRuleId)?;
+        Ok(match old_index {
+            Some(old_index) if old_index < index => index - 1,
+            _ => index,
+        })
+    };
+
+    let mut new_index = None;
+    if let Some(after) = after {
+        new_index = Some(index_of(after)? + 1);
+    }
+    if let Some(before) = before {
+        let index = index_of(before)?;
+        if new_index.map_or(false, |new_index| index < new_index) {
+            return Err(InsertPushRuleError::BeforeHigherThanAfter);
+        }
+        new_index = Some(index);
+    }
+
+    if let Some(old_index) = old_index {
+        set.shift_remove_index(old_index);
+    }
+
+    let new_index = new_index.or(old_index).unwrap_or(default_position).min(set.len());
+    let (index, _) = set.insert_full(rule);
+    set.move_index(index, new_index);
+
+    Ok(())
+}
+
+/// Removes the user-defined rule with the given ID from the set.
+fn remove_rule<T>(set: &mut IndexSet<T>, rule_id: &str) -> Result<(), RemovePushRuleError>
+where
+    T: Hash + Eq + RuleFields,
+    str: Equivalent<T>,
+{
+    let rule = set.get(rule_id).ok_or(RemovePushRuleError::NotFound)?;
+    if rule.is_default() {
+        return Err(RemovePushRuleError::ServerDefault);
+    }
+
+    set.shift_remove(rule_id);
+    Ok(())
+}
+
+/// Updates the rule with the given ID in the set.
+fn update_rule<T>(
+    set: &mut IndexSet<T>,
+    rule_id: &str,
+    f: impl FnOnce(&mut T),
+) -> Result<(), RuleNotFoundError>
+where
+    T: Clone + Hash + Eq,
+    str: Equivalent<T>,
+{
+    let mut rule = set.get(rule_id).ok_or(RuleNotFoundError)?.clone();
+    f(&mut rule);
+    // The rule ID didn't change so the rule keeps its position.
+    set.replace(rule);
+    Ok(())
+}
+
 /// A push rule is a single rule that states under what conditions an event should be passed onto a
 /// push gateway and how the notification should be presented.
 ///
@@ -447,6 +708,38 @@ impl PusherData {
     }
 }
 
+/// The kinds of push rules that are available.
+#[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, StringEnum)]
+#[ruma_enum(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum RuleKind {
+    /// User-configured rules that override all other kinds.
+    Override,
+
+    /// Lowest priority user-defined rules.
+    Underride,
+
+    /// Sender-specific rules.
+    Sender,
+
+    /// Room-specific rules.
+    Room,
+
+    /// Content-specific rules.
+    Content,
+
+    #[doc(hidden)]
+    _Custom(PrivOwnedStr),
+}
+
+impl RuleKind {
+    /// Creates a string slice from this `RuleKind`.
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+}
+
 /// A special format that the homeserver should use when sending notifications to a Push Gateway.
 /// Currently, only "event_id_only" is supported as of [Push Gateway API r0.1.1][spec].
 ///
@@ -484,7 +777,8 @@ mod tests {
     use super::{
         action::{Action, Tweak},
         condition::{PushCondition, PushConditionRoomCtx, RoomMemberCountIs},
-        AnyPushRule, ConditionalPushRule, PatternedPushRule, Ruleset, SimplePushRule,
+        AnyPushRule, ConditionalPushRule, InsertPushRuleError, PatternedPushRule,
+        RemovePushRuleError, RuleKind, RuleNotFoundError, Ruleset, SimplePushRule,
     };
     use crate::{power_levels::NotificationPowerLevels, room_id, serde::Raw, user_id};
 
@@ -1203,4 +1497,170 @@ mod tests {
             if sound == "three"
         );
     }
+
+    fn user_rule(kind: RuleKind, rule_id: &str) -> AnyPushRule {
+        let actions = vec![Action::Notify];
+        let rule =
+            SimplePushRule { actions, default: false, enabled: true, rule_id: rule_id.into() };
+
+        match kind {
+            RuleKind::Override => AnyPushRule::Override(ConditionalPushRule {
+                actions: rule.actions,
+                default: false,
+                enabled: true,
+                rule_id: rule.rule_id,
+                conditions: vec![],
+            }),
+            RuleKind::Content => AnyPushRule::Content(PatternedPushRule {
+                actions: rule.actions,
+                default: false,
+                enabled: true,
+                rule_id: rule.rule_id,
+                pattern: "ruma".into(),
+            }),
+            RuleKind::Room => AnyPushRule::Room(rule),
+            _ => unreachable!(),
+        }
+    }
+
+    fn override_rule_ids(set: &Ruleset) -> Vec<&str> {
+        set.override_.iter().map(|rule| rule.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn insert_push_rule() {
+        let mut set = Ruleset::server_default(user_id!("@jolly_jumper:server.name"));
+        let override_len = set.override_.len();
+
+        // New rules come after `.m.rule.master` and before the other server-default rules.
+        set.insert(user_rule(RuleKind::Override, "first"), None, None).unwrap();
+        set.insert(user_rule(RuleKind::Override, "second"), None, None).unwrap();
+        assert_eq!(override_rule_ids(&set)[..3], [".m.rule.master", "second", "first"]);
+        assert_eq!(set.override_.len(), override_len + 2);
+
+        set.insert(user_rule(RuleKind::Override, "third"), Some("first"), None).unwrap();
+        assert_eq!(override_rule_ids(&set)[..4], [".m.rule.master", "second", "first", "third"]);
+
+        set.insert(user_rule(RuleKind::Override, "fourth"), None, Some("first")).unwrap();
+        assert_eq!(
+            override_rule_ids(&set)[..5],
+            [".m.rule.master", "second", "fourth", "first", "third"]
+        );
+
+        // Moving an existing rule.
+        set.insert(user_rule(RuleKind::Override, "second"), Some("third"), None).unwrap();
+        assert_eq!(
+            override_rule_ids(&set)[..5],
+            [".m.rule.master", "fourth", "first", "third", "second"]
+        );
+
+        set.insert(user_rule(RuleKind::Override, "second"), Some("fourth"), Some("first")).unwrap();
+        assert_eq!(
+            override_rule_ids(&set)[..5],
+            [".m.rule.master", "fourth", "second", "first", "third"]
+        );
+
+        // Replacing an existing rule keeps its position.
+        let mut rule = user_rule(RuleKind::Override, "first");
+        if let AnyPushRule::Override(rule) = &mut rule {
+            rule.enabled = false;
+        }
+        set.insert(rule, None, None).unwrap();
+        assert_eq!(
+            override_rule_ids(&set)[..5],
+            [".m.rule.master", "fourth", "second", "first", "third"]
+        );
+        assert!(!set.get(RuleKind::Override, "first").unwrap().enabled());
+        assert_eq!(set.override_.len(), override_len + 4);
+
+        set.insert(user_rule(RuleKind::Content, "ruma"), None, None).unwrap();
+        assert_eq!(set.content.get_index(0).unwrap().rule_id, "ruma");
+    }
+
+    #[test]
+    fn insert_invalid_push_rule() {
+        let mut set = Ruleset::server_default(user_id!("@jolly_jumper:server.name"));
+        set.insert(user_rule(RuleKind::Override, "first"), None, None).unwrap();
+        set.insert(user_rule(RuleKind::Override, "second"), None, None).unwrap();
+
+        assert_matches!(
+            set.insert(user_rule(RuleKind::Override, ".m.rule.mine"), None, None),
+            Err(InsertPushRuleError::ServerDefaultRuleId)
+        );
+        assert_matches!(
+            set.insert(user_rule(RuleKind::Room, "not_a_room_id"), None, None),
+            Err(InsertPushRuleError::InvalidRuleId)
+        );
+        assert_matches!(
+            set.insert(user_rule(RuleKind::Override, "third"), Some(".m.rule.master"), None),
+            Err(InsertPushRuleError::RelativeToServerDefaultRule)
+        );
+        assert_matches!(
+            set.insert(user_rule(RuleKind::Override, "third"), None, Some("unknown")),
+            Err(InsertPushRuleError::UnknownRuleId)
+        );
+        assert_matches!(
+            set.insert(user_rule(RuleKind::Override, "first"), Some("first"), None),
+            Err(InsertPushRuleError::UnknownRuleId)
+        );
+        assert_matches!(
+            set.insert(user_rule(RuleKind::Override, "third"), Some("first"), Some("second")),
+            Err(InsertPushRuleError::BeforeHigherThanAfter)
+        );
+
+        // The rule set is unchanged after an error.
+        assert_eq!(override_rule_ids(&set)[..3], [".m.rule.master", "second", "first"]);
+        assert!(set.get(RuleKind::Override, "third").is_none());
+
+        set.insert(user_rule(RuleKind::Room, "!dummy:server.name"), None, None).unwrap();
+        assert!(set.get(RuleKind::Room, "!dummy:server.name").is_some());
+    }
+
+    #[test]
+    fn remove_push_rule() {
+        let mut set = Ruleset::server_default(user_id!("@jolly_jumper:server.name"));
+        set.insert(user_rule(RuleKind::Override, "first"), None, None).unwrap();
+        set.insert(user_rule(RuleKind::Override, "second"), None, None).unwrap();
+
+        set.remove(RuleKind::Override, "second").unwrap();
+        assert_eq!(
+            override_rule_ids(&set)[..3],
+            [".m.rule.master", "first", ".m.rule.suppress_notices"]
+        );
+
+        assert_matches!(
+            set.remove(RuleKind::Override, "second"),
+            Err(RemovePushRuleError::NotFound)
+        );
+        assert_matches!(set.remove(RuleKind::Room, "first"), Err(RemovePushRuleError::NotFound));
+        assert_matches!(
+            set.remove(RuleKind::Override, ".m.rule.master"),
+            Err(RemovePushRuleError::ServerDefault)
+        );
+    }
+
+    #[test]
+    fn update_push_rule() {
+        let mut set = Ruleset::server_default(user_id!("@jolly_jumper:server.name"));
+
+        set.set_enabled(RuleKind::Override, ".m.rule.master", true).unwrap();
+        let master = set.get(RuleKind::Override, ".m.rule.master").unwrap();
+        assert!(master.enabled());
+        assert_eq!(override_rule_ids(&set)[0], ".m.rule.master");
+
+        set.set_actions(RuleKind::Underride, ".m.rule.message", vec![Action::DontNotify]).unwrap();
+        assert_matches!(
+            set.get(RuleKind::Underride, ".m.rule.message").unwrap().actions(),
+            [Action::DontNotify]
+        );
+
+        assert_matches!(
+            set.set_enabled(RuleKind::Override, "unknown", true),
+            Err(RuleNotFoundError)
+        );
+        assert_matches!(
+            set.set_actions(RuleKind::Sender, ".m.rule.message", vec![]),
+            Err(RuleNotFoundError)
+        );
+    }
 }
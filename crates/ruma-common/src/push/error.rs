@@ -0,0 +1,77 @@
+use std::{error::Error, fmt};
+
+/// The error type returned when trying to insert a user-defined push rule into a `Ruleset`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InsertPushRuleError {
+    /// The rule ID starts with a dot (`.`), which is reserved for server-default rules.
+    ServerDefaultRuleId,
+
+    /// The rule ID is not valid for the kind of the rule.
+    ///
+    /// The rule ID of a room rule must be a room ID and the rule ID of a sender rule must be a
+    /// user ID.
+    InvalidRuleId,
+
+    /// The rule is being placed relative to a server-default rule, which is forbidden.
+    RelativeToServerDefaultRule,
+
+    /// The `before` or `after` rule could not be found.
+    UnknownRuleId,
+
+    /// `before` has a higher priority than `after`.
+    BeforeHigherThanAfter,
+}
+
+impl fmt::Display for InsertPushRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ServerDefaultRuleId => {
+                write!(f, "rule IDs starting with a dot are reserved for server-default rules")
+            }
+            Self::InvalidRuleId => write!(f, "invalid rule ID for this kind of push rule"),
+            Self::RelativeToServerDefaultRule => {
+                write!(f, "can't make a user-defined rule relative to a server-default rule")
+            }
+            Self::UnknownRuleId => write!(f, "the before or after rule could not be found"),
+            Self::BeforeHigherThanAfter => write!(f, "before has a higher priority than after"),
+        }
+    }
+}
+
+impl Error for InsertPushRuleError {}
+
+/// The error type returned when trying to remove a push rule from a `Ruleset`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RemovePushRuleError {
+    /// The rule is a server-default rule, which can't be removed.
+    ServerDefault,
+
+    /// The rule could not be found.
+    NotFound,
+}
+
+impl fmt::Display for RemovePushRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ServerDefault => write!(f, "server-default push rules can't be removed"),
+            Self::NotFound => write!(f, "push rule not found"),
+        }
+    }
+}
+
+impl Error for RemovePushRuleError {}
+
+/// The error type returned when a push rule could not be found in a `Ruleset`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RuleNotFoundError;
+
+impl fmt::Display for RuleNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "push rule not found")
+    }
+}
+
+impl Error for RuleNotFoundError {}
@@ -1,9 +1,17 @@
 //! Common types for receipts.
 
+#[cfg(feature = "unstable-msc3771")]
+use std::convert::TryFrom;
+
+#[cfg(feature = "unstable-msc3771")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::{
     serde::{OrdAsRefStr, PartialEqAsRefStr, PartialOrdAsRefStr, StringEnum},
     PrivOwnedStr,
 };
+#[cfg(feature = "unstable-msc3771")]
+use crate::{IdParseError, OwnedEventId};
 
 /// The type of receipt.
 #[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/doc/string_enum.md"))]
@@ -14,6 +22,17 @@ pub enum ReceiptType {
     #[ruma_enum(rename = "m.read")]
     Read,
 
+    /// m.read.private
+    ///
+    /// A read receipt that is only visible to the user that sent it.
+    ///
+    /// This uses the unstable prefix in [MSC2285].
+    ///
+    /// [MSC2285]: https://github.com/matrix-org/matrix-spec-proposals/pull/2285
+    #[cfg(feature = "unstable-msc2285")]
+    #[ruma_enum(rename = "org.matrix.msc2285.read.private", alias = "m.read.private")]
+    ReadPrivate,
+
     #[doc(hidden)]
     _Custom(PrivOwnedStr),
 }
@@ -24,3 +43,120 @@ impl ReceiptType {
         self.as_ref()
     }
 }
+
+/// The thread a receipt applies to.
+///
+/// This is defined in [MSC3771].
+///
+/// [MSC3771]: https://github.com/matrix-org/matrix-spec-proposals/pull/3771
+#[cfg(feature = "unstable-msc3771")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReceiptThread {
+    /// The receipt applies to the timeline, regardless of threads.
+    ///
+    /// This is the default, and is not serialized.
+    Unthreaded,
+
+    /// The receipt applies to the main timeline.
+    ///
+    /// This is used for events that are not part of a thread.
+    Main,
+
+    /// The receipt applies to the thread with the given root event ID.
+    Thread(OwnedEventId),
+}
+
+#[cfg(feature = "unstable-msc3771")]
+impl ReceiptThread {
+    /// Get the string representation of this `ReceiptThread`.
+    ///
+    /// [`ReceiptThread::Unthreaded`] returns `None`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Unthreaded => None,
+            Self::Main => Some("main"),
+            Self::Thread(event_id) => Some(event_id.as_str()),
+        }
+    }
+
+    /// Whether this is [`ReceiptThread::Unthreaded`].
+    pub fn is_unthreaded(&self) -> bool {
+        matches!(self, Self::Unthreaded)
+    }
+}
+
+#[cfg(feature = "unstable-msc3771")]
+impl Default for ReceiptThread {
+    fn default() -> Self {
+        Self::Unthreaded
+    }
+}
+
+#[cfg(feature = "unstable-msc3771")]
+impl<T> TryFrom<Option<T>> for ReceiptThread
+where
+    T: AsRef<str>,
+{
+    type Error = IdParseError;
+
+    fn try_from(s: Option<T>) -> Result<Self, Self::Error> {
+        let res = match s {
+            None => Self::Unthreaded,
+            Some(s) => match s.as_ref() {
+                "main" => Self::Main,
+                s => Self::Thread(OwnedEventId::try_from(s)?),
+            },
+        };
+
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "unstable-msc3771")]
+impl Serialize for ReceiptThread {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.as_str().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "unstable-msc3771")]
+impl<'de> Deserialize<'de> for ReceiptThread {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = crate::serde::deserialize_cow_str(deserializer)?;
+        Self::try_from(Some(s)).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(all(test, feature = "unstable-msc3771"))]
+mod tests {
+    use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+    use super::ReceiptThread;
+    use crate::event_id;
+
+    #[test]
+    fn serialize_receipt_thread() {
+        assert_eq!(to_json_value(ReceiptThread::Main).unwrap(), json!("main"));
+        assert_eq!(
+            to_json_value(ReceiptThread::Thread(event_id!("$thread_root").to_owned())).unwrap(),
+            json!("$thread_root")
+        );
+    }
+
+    #[test]
+    fn deserialize_receipt_thread() {
+        assert_eq!(from_json_value::<ReceiptThread>(json!("main")).unwrap(), ReceiptThread::Main);
+        assert_eq!(
+            from_json_value::<ReceiptThread>(json!("$thread_root")).unwrap(),
+            ReceiptThread::Thread(event_id!("$thread_root").to_owned())
+        );
+        assert!(from_json_value::<ReceiptThread>(json!("not_an_event_id")).is_err());
+    }
+}
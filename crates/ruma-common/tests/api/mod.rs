@@ -6,6 +6,7 @@ mod manual_endpoint_impl;
 mod no_fields;
 mod optional_headers;
 mod path_arg_ordering;
+mod router;
 mod ruma_api;
 mod ruma_api_lifetime;
 mod ruma_api_macros;
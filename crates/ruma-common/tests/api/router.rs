@@ -0,0 +1,207 @@
+use http::{header, Method, StatusCode};
+use matches::assert_matches;
+use ruma_common::api::{OutgoingResponse as _, RouteError, Router};
+use serde_json::{from_slice as from_json_slice, json, Value as JsonValue};
+
+mod get_state {
+    use ruma_common::api::ruma_api;
+
+    ruma_api! {
+        metadata: {
+            description: "Gets a state event.",
+            method: GET,
+            name: "get_state",
+            unstable_path: "/_matrix/unstable/rooms/:room_id/state/:event_type/:state_key",
+            r0_path: "/_matrix/r0/rooms/:room_id/state/:event_type/:state_key",
+            stable_path: "/_matrix/v3/rooms/:room_id/state/:event_type/:state_key",
+            rate_limited: false,
+            authentication: None,
+            added: 1.0,
+        }
+
+        request: {
+            #[ruma_api(path)]
+            pub room_id: String,
+
+            #[ruma_api(path)]
+            pub event_type: String,
+
+            #[ruma_api(path)]
+            pub state_key: String,
+        }
+
+        response: {}
+    }
+}
+
+mod get_members {
+    use ruma_common::api::ruma_api;
+
+    ruma_api! {
+        metadata: {
+            description: "Gets the members of a room.",
+            method: GET,
+            name: "get_members",
+            stable_path: "/_matrix/v3/rooms/:room_id/state/members/list",
+            rate_limited: false,
+            authentication: None,
+            added: 1.1,
+        }
+
+        request: {
+            #[ruma_api(path)]
+            pub room_id: String,
+        }
+
+        response: {}
+    }
+}
+
+mod send_state {
+    use ruma_common::api::ruma_api;
+
+    ruma_api! {
+        metadata: {
+            description: "Sends a state event.",
+            method: PUT,
+            name: "send_state",
+            stable_path: "/_matrix/v3/rooms/:room_id/state/:event_type/:state_key",
+            rate_limited: false,
+            authentication: None,
+            added: 1.1,
+        }
+
+        request: {
+            #[ruma_api(path)]
+            pub room_id: String,
+
+            #[ruma_api(path)]
+            pub event_type: String,
+
+            #[ruma_api(path)]
+            pub state_key: String,
+
+            pub body: String,
+        }
+
+        response: {}
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Handled {
+    GetState(String, String, String),
+    GetMembers(String),
+    SendState(String),
+}
+
+fn router() -> Router<Vec<u8>, Handled> {
+    Router::new()
+        .route(|req: get_state::Request| {
+            Handled::GetState(req.room_id, req.event_type, req.state_key)
+        })
+        .route(|req: get_members::Request| Handled::GetMembers(req.room_id))
+        .route(|req: send_state::Request| Handled::SendState(req.body))
+}
+
+fn request(method: Method, uri: &str, body: &[u8]) -> http::Request<Vec<u8>> {
+    http::Request::builder().method(method).uri(uri).body(body.to_vec()).unwrap()
+}
+
+#[test]
+fn route_all_path_versions() {
+    let router = router();
+
+    for uri in [
+        "/_matrix/unstable/rooms/!room:localhost/state/m.room.name/",
+        "/_matrix/r0/rooms/!room:localhost/state/m.room.name/",
+        "/_matrix/v3/rooms/!room:localhost/state/m.room.name/",
+    ] {
+        // The empty state key is not a valid path argument.
+        assert_matches!(router.handle(request(Method::GET, uri, b"")), Err(RouteError::NotFound));
+    }
+
+    for uri in [
+        "/_matrix/unstable/rooms/%21room%3Alocalhost/state/m.room.member/%40alice%3Alocalhost",
+        "/_matrix/r0/rooms/%21room%3Alocalhost/state/m.room.member/%40alice%3Alocalhost",
+        "/_matrix/v3/rooms/%21room%3Alocalhost/state/m.room.member/%40alice%3Alocalhost",
+    ] {
+        assert_eq!(
+            router.handle(request(Method::GET, uri, b"")).unwrap(),
+            Handled::GetState(
+                "!room:localhost".to_owned(),
+                "m.room.member".to_owned(),
+                "@alice:localhost".to_owned()
+            )
+        );
+    }
+}
+
+#[test]
+fn route_prefers_literal_segments() {
+    let res = router()
+        .handle(request(Method::GET, "/_matrix/v3/rooms/!room:localhost/state/members/list", b""))
+        .unwrap();
+    assert_eq!(res, Handled::GetMembers("!room:localhost".to_owned()));
+}
+
+#[test]
+fn route_by_method() {
+    let res = router()
+        .handle(request(
+            Method::PUT,
+            "/_matrix/v3/rooms/!room:localhost/state/m.room.topic/key",
+            br#"{ "body": "hello" }"#,
+        ))
+        .unwrap();
+    assert_eq!(res, Handled::SendState("hello".to_owned()));
+}
+
+#[test]
+fn unrecognized_path() {
+    let err = router().handle(request(Method::GET, "/_matrix/v3/unknown", b"")).unwrap_err();
+    assert_matches!(err, RouteError::NotFound);
+
+    let response = err.try_into_http_response::<Vec<u8>>().unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        from_json_slice::<JsonValue>(response.body()).unwrap()["errcode"],
+        json!("M_UNRECOGNIZED")
+    );
+}
+
+#[test]
+fn method_not_allowed() {
+    let err = router()
+        .handle(request(
+            Method::DELETE,
+            "/_matrix/v3/rooms/!room:localhost/state/m.room.name/key",
+            b"",
+        ))
+        .unwrap_err();
+    assert_matches!(
+        &err,
+        RouteError::MethodNotAllowed { allowed } if *allowed == [Method::GET, Method::PUT]
+    );
+
+    let response = err.try_into_http_response::<Vec<u8>>().unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[header::ALLOW], "GET, PUT");
+    assert_eq!(
+        from_json_slice::<JsonValue>(response.body()).unwrap()["errcode"],
+        json!("M_UNRECOGNIZED")
+    );
+}
+
+#[test]
+fn invalid_request_body() {
+    let err = router()
+        .handle(request(
+            Method::PUT,
+            "/_matrix/v3/rooms/!room:localhost/state/m.room.name/key",
+            b"{",
+        ))
+        .unwrap_err();
+    assert_matches!(err, RouteError::Request { endpoint: "send_state", .. });
+    assert_eq!(err.try_into_http_response::<Vec<u8>>().unwrap().status(), StatusCode::BAD_REQUEST);
+}
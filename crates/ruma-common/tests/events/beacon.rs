@@ -0,0 +1,167 @@
+#![cfg(feature = "unstable-msc3489")]
+
+use std::time::Duration;
+
+use js_int::uint;
+use ruma_common::{
+    event_id,
+    events::{
+        beacon::BeaconEventContent, beacon_info::BeaconInfoEventContent, location::AssetType,
+        room::encrypted::Relation, AnyMessageLikeEvent, AnyMessageLikeEventContent, AnyStateEvent,
+        MessageLikeEvent, OriginalMessageLikeEvent, OriginalStateEvent, StateEvent,
+    },
+    MilliSecondsSinceUnixEpoch,
+};
+use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+fn beacon_info_content() -> BeaconInfoEventContent {
+    let mut content = BeaconInfoEventContent::new(
+        Some("Alice's live location".to_owned()),
+        Duration::from_millis(60_000),
+        true,
+    );
+    content.ts = MilliSecondsSinceUnixEpoch(uint!(1_636_829_458));
+    content
+}
+
+#[test]
+fn beacon_info_content_serialization() {
+    assert_eq!(
+        to_json_value(beacon_info_content()).unwrap(),
+        json!({
+            "description": "Alice's live location",
+            "live": true,
+            "m.ts": 1_636_829_458,
+            "timeout": 60_000,
+            "m.asset": {
+                "type": "m.self",
+            },
+        })
+    );
+}
+
+#[test]
+fn beacon_info_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "live": true,
+            "m.ts": 1_636_829_458,
+            "timeout": 60_000,
+        },
+        "event_id": "$beacon_info:example.com",
+        "origin_server_ts": 1_636_829_458,
+        "room_id": "!roomid:example.com",
+        "sender": "@alice:example.com",
+        "state_key": "@alice:example.com",
+        "type": "m.beacon_info",
+    });
+
+    let (content, state_key) = match from_json_value::<AnyStateEvent>(json_data).unwrap() {
+        AnyStateEvent::BeaconInfo(StateEvent::Original(OriginalStateEvent {
+            content,
+            state_key,
+            ..
+        })) => (content, state_key),
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(state_key, "@alice:example.com");
+    assert_eq!(content.description, None);
+    assert!(content.live);
+    assert_eq!(content.ts, MilliSecondsSinceUnixEpoch(uint!(1_636_829_458)));
+    assert_eq!(content.timeout, Duration::from_millis(60_000));
+    assert_eq!(content.asset.type_, AssetType::Self_);
+}
+
+#[test]
+fn beacon_info_is_live_at() {
+    let mut content = beacon_info_content();
+
+    assert!(!content.is_live_at(MilliSecondsSinceUnixEpoch(uint!(1_636_829_457))));
+    assert!(content.is_live_at(MilliSecondsSinceUnixEpoch(uint!(1_636_829_458))));
+    assert!(content.is_live_at(MilliSecondsSinceUnixEpoch(uint!(1_636_889_457))));
+    assert!(!content.is_live_at(MilliSecondsSinceUnixEpoch(uint!(1_636_889_458))));
+
+    content.stop();
+    assert!(!content.is_live_at(MilliSecondsSinceUnixEpoch(uint!(1_636_829_458))));
+}
+
+#[test]
+fn beacon_info_is_live() {
+    let content = BeaconInfoEventContent::new(None, Duration::from_secs(60), true);
+    assert!(content.is_live());
+
+    let content = BeaconInfoEventContent::new(None, Duration::from_secs(60), false);
+    assert!(!content.is_live());
+}
+
+#[test]
+fn beacon_content_serialization() {
+    let content = BeaconEventContent::new(
+        event_id!("$beacon_info:example.com").to_owned(),
+        "geo:51.5008,0.1247;u=35".to_owned(),
+        Some(MilliSecondsSinceUnixEpoch(uint!(1_636_829_460))),
+    );
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": "$beacon_info:example.com",
+            },
+            "m.location": {
+                "uri": "geo:51.5008,0.1247;u=35",
+            },
+            "m.ts": 1_636_829_460,
+        })
+    );
+}
+
+#[test]
+fn beacon_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": "$beacon_info:example.com",
+            },
+            "m.location": {
+                "uri": "geo:51.5008,0.1247;u=35",
+                "description": "Alice's location",
+            },
+            "m.ts": 1_636_829_460,
+        },
+        "event_id": "$beacon:example.com",
+        "origin_server_ts": 1_636_829_460,
+        "room_id": "!roomid:example.com",
+        "sender": "@alice:example.com",
+        "type": "m.beacon",
+    });
+
+    let content = match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+        AnyMessageLikeEvent::Beacon(MessageLikeEvent::Original(OriginalMessageLikeEvent {
+            content,
+            ..
+        })) => content,
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(content.relates_to.event_id, "$beacon_info:example.com");
+    assert_eq!(content.location.uri, "geo:51.5008,0.1247;u=35");
+    assert_eq!(content.location.description.as_deref(), Some("Alice's location"));
+    assert_eq!(content.ts, MilliSecondsSinceUnixEpoch(uint!(1_636_829_460)));
+}
+
+#[test]
+fn beacon_relation() {
+    let content = BeaconEventContent::new(
+        event_id!("$beacon_info:example.com").to_owned(),
+        "geo:51.5008,0.1247;u=35".to_owned(),
+        None,
+    );
+
+    let event_id = match AnyMessageLikeEventContent::Beacon(content).relation() {
+        Some(Relation::Reference(reference)) => reference.event_id,
+        _ => panic!("unexpected relation"),
+    };
+    assert_eq!(event_id, "$beacon_info:example.com");
+}
@@ -0,0 +1,412 @@
+use std::collections::BTreeMap;
+
+use js_int::uint;
+use ruma_common::{
+    events::{
+        call::{
+            answer::CallAnswerEventContent,
+            hangup::{CallHangupEventContent, Reason},
+            invite::CallInviteEventContent,
+            negotiate::CallNegotiateEventContent,
+            reject::CallRejectEventContent,
+            select_answer::CallSelectAnswerEventContent,
+            SessionDescription, SessionDescriptionType, StreamMetadata, StreamPurpose,
+        },
+        AnyMessageLikeEvent, MessageLikeEvent, OriginalMessageLikeEvent,
+    },
+    user_id, VoipVersionId,
+};
+use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+#[test]
+fn invite_content_version_0_serialization() {
+    let content = CallInviteEventContent::version_0(
+        "abcdef".into(),
+        uint!(30000),
+        SessionDescription::new(SessionDescriptionType::Offer, "not a real sdp".to_owned()),
+    );
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "call_id": "abcdef",
+            "lifetime": 30000,
+            "version": 0,
+            "offer": {
+                "type": "offer",
+                "sdp": "not a real sdp",
+            },
+        })
+    );
+}
+
+#[test]
+fn invite_content_version_1_serialization() {
+    let mut content = CallInviteEventContent::version_1(
+        "abcdef".into(),
+        "9876".into(),
+        uint!(60000),
+        SessionDescription::new(SessionDescriptionType::Offer, "not a real sdp".to_owned()),
+    );
+    content.invitee = Some(user_id!("@bob:example.org").to_owned());
+    content.sdp_stream_metadata =
+        BTreeMap::from([("abc".to_owned(), StreamMetadata::new(StreamPurpose::ScreenShare))]);
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "lifetime": 60000,
+            "version": "1",
+            "invitee": "@bob:example.org",
+            "offer": {
+                "type": "offer",
+                "sdp": "not a real sdp",
+            },
+            "sdp_stream_metadata": {
+                "abc": {
+                    "purpose": "m.screenshare",
+                },
+            },
+        })
+    );
+}
+
+#[test]
+fn invite_event_version_1_deserialization() {
+    let json_data = json!({
+        "content": {
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "lifetime": 60000,
+            "version": "1",
+            "offer": {
+                "type": "offer",
+                "sdp": "not a real sdp",
+            },
+            "sdp_stream_metadata": {
+                "abc": {
+                    "purpose": "m.usermedia",
+                    "audio_muted": true,
+                },
+            },
+        },
+        "event_id": "$h29iv0s8:example.com",
+        "origin_server_ts": 1,
+        "room_id": "!roomid:room.com",
+        "sender": "@carl:example.com",
+        "type": "m.call.invite"
+    });
+
+    let content = match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+        AnyMessageLikeEvent::CallInvite(MessageLikeEvent::Original(OriginalMessageLikeEvent {
+            content,
+            ..
+        })) => content,
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(content.call_id, "abcdef");
+    assert_eq!(content.party_id.as_deref(), Some("9876"));
+    assert_eq!(content.version, VoipVersionId::V1);
+    assert_eq!(content.invitee, None);
+
+    let metadata = content.sdp_stream_metadata.get("abc").unwrap();
+    assert_eq!(metadata.purpose, StreamPurpose::UserMedia);
+    assert!(metadata.audio_muted);
+    assert!(!metadata.video_muted);
+}
+
+#[test]
+fn answer_event_version_0_deserialization() {
+    let json_data = json!({
+        "content": {
+            "answer": {
+                "type": "answer",
+                "sdp": "Hello"
+            },
+            "call_id": "foofoo",
+            "version": 0
+        },
+        "event_id": "$h29iv0s8:example.com",
+        "origin_server_ts": 1,
+        "room_id": "!roomid:room.com",
+        "sender": "@carl:example.com",
+        "type": "m.call.answer"
+    });
+
+    let content = match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+        AnyMessageLikeEvent::CallAnswer(MessageLikeEvent::Original(OriginalMessageLikeEvent {
+            content,
+            ..
+        })) => content,
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(content.version, VoipVersionId::V0);
+    assert_eq!(content.party_id, None);
+    assert!(content.sdp_stream_metadata.is_empty());
+}
+
+#[test]
+fn answer_content_version_1_serialization() {
+    let content = CallAnswerEventContent::version_1(
+        SessionDescription::new(SessionDescriptionType::Answer, "not a real sdp".to_owned()),
+        "abcdef".into(),
+        "9876".into(),
+    );
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "version": "1",
+            "answer": {
+                "type": "answer",
+                "sdp": "not a real sdp",
+            },
+        })
+    );
+}
+
+#[test]
+fn hangup_content_version_1_serialization() {
+    let content =
+        CallHangupEventContent::version_1("abcdef".into(), "9876".into(), Reason::UserBusy);
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "version": "1",
+            "reason": "user_busy",
+        })
+    );
+}
+
+#[test]
+fn negotiate_content_serialization() {
+    let content = CallNegotiateEventContent::new(
+        "abcdef".into(),
+        "9876".into(),
+        uint!(30000),
+        SessionDescription::new(SessionDescriptionType::Offer, "not a real sdp".to_owned()),
+    );
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "lifetime": 30000,
+            "version": "1",
+            "description": {
+                "type": "offer",
+                "sdp": "not a real sdp",
+            },
+        })
+    );
+}
+
+#[test]
+fn select_answer_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "selected_party_id": "6336",
+            "version": "1",
+        },
+        "event_id": "$h29iv0s8:example.com",
+        "origin_server_ts": 1,
+        "room_id": "!roomid:room.com",
+        "sender": "@carl:example.com",
+        "type": "m.call.select_answer"
+    });
+
+    let content: CallSelectAnswerEventContent =
+        match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+            AnyMessageLikeEvent::CallSelectAnswer(MessageLikeEvent::Original(
+                OriginalMessageLikeEvent { content, .. },
+            )) => content,
+            _ => panic!("unexpected event"),
+        };
+    assert_eq!(content.call_id, "abcdef");
+    assert_eq!(content.party_id, "9876");
+    assert_eq!(content.selected_party_id, "6336");
+    assert_eq!(content.version, VoipVersionId::V1);
+}
+
+#[test]
+fn reject_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "call_id": "abcdef",
+            "party_id": "9876",
+            "version": "1",
+        },
+        "event_id": "$h29iv0s8:example.com",
+        "origin_server_ts": 1,
+        "room_id": "!roomid:room.com",
+        "sender": "@carl:example.com",
+        "type": "m.call.reject"
+    });
+
+    let content: CallRejectEventContent =
+        match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+            AnyMessageLikeEvent::CallReject(MessageLikeEvent::Original(
+                OriginalMessageLikeEvent { content, .. },
+            )) => content,
+            _ => panic!("unexpected event"),
+        };
+    assert_eq!(content.call_id, "abcdef");
+    assert_eq!(content.party_id, "9876");
+    assert_eq!(content.version, VoipVersionId::V1);
+}
+
+#[cfg(feature = "unstable-msc3401")]
+#[test]
+fn call_event_serialization() {
+    use ruma_common::events::call::{CallEventContent, CallIntent, CallType};
+
+    let mut content = CallEventContent::new(CallIntent::Room, CallType::Video);
+    content.name = Some("Weekly sync".to_owned());
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "m.intent": "m.room",
+            "m.type": "m.video",
+            "m.name": "Weekly sync",
+        })
+    );
+}
+
+#[cfg(feature = "unstable-msc3401")]
+#[test]
+fn call_event_deserialization() {
+    use ruma_common::events::{
+        call::{CallIntent, CallType},
+        AnyStateEvent, OriginalStateEvent, StateEvent,
+    };
+
+    let json_data = json!({
+        "content": {
+            "m.intent": "m.ring",
+            "m.type": "m.voice",
+            "m.terminated": "call_ended",
+        },
+        "event_id": "$h29iv0s8:example.com",
+        "origin_server_ts": 1,
+        "room_id": "!roomid:room.com",
+        "sender": "@carl:example.com",
+        "state_key": "cvsiu2893",
+        "type": "org.matrix.msc3401.call"
+    });
+
+    let (content, state_key) = match from_json_value::<AnyStateEvent>(json_data).unwrap() {
+        AnyStateEvent::Call(StateEvent::Original(OriginalStateEvent {
+            content,
+            state_key,
+            ..
+        })) => (content, state_key),
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(state_key, "cvsiu2893");
+    assert_eq!(content.intent, CallIntent::Ring);
+    assert_eq!(content.call_type, CallType::Voice);
+    assert_eq!(content.terminated.as_deref(), Some("call_ended"));
+    assert_eq!(content.name, None);
+}
+
+#[cfg(feature = "unstable-msc3401")]
+#[test]
+fn call_member_event_serialization() {
+    use ruma_common::{
+        device_id,
+        events::call::member::{
+            CallFeed, CallMemberDevice, CallMemberEventContent, CallMembership,
+        },
+    };
+
+    let content = CallMemberEventContent::new(vec![CallMembership::new(
+        "cvsiu2893".to_owned(),
+        vec![CallMemberDevice::new(
+            device_id!("ASDUHDGFYUW").to_owned(),
+            "GHKJFKLJLJ".to_owned(),
+            vec![
+                CallFeed::new(StreamPurpose::UserMedia),
+                CallFeed::new(StreamPurpose::ScreenShare),
+            ],
+        )],
+    )]);
+
+    assert_eq!(
+        to_json_value(&content).unwrap(),
+        json!({
+            "m.calls": [
+                {
+                    "m.call_id": "cvsiu2893",
+                    "m.devices": [
+                        {
+                            "device_id": "ASDUHDGFYUW",
+                            "session_id": "GHKJFKLJLJ",
+                            "feeds": [
+                                { "purpose": "m.usermedia" },
+                                { "purpose": "m.screenshare" },
+                            ],
+                        },
+                    ],
+                },
+            ],
+        })
+    );
+}
+
+#[cfg(feature = "unstable-msc3401")]
+#[test]
+fn call_member_event_deserialization() {
+    use ruma_common::events::{AnyStateEvent, OriginalStateEvent, StateEvent};
+
+    let json_data = json!({
+        "content": {
+            "m.calls": [
+                {
+                    "m.call_id": "cvsiu2893",
+                    "m.devices": [
+                        {
+                            "device_id": "ASDUHDGFYUW",
+                            "session_id": "GHKJFKLJLJ",
+                            "feeds": [{ "purpose": "m.usermedia" }],
+                        },
+                    ],
+                },
+            ],
+        },
+        "event_id": "$h29iv0s8:example.com",
+        "origin_server_ts": 1,
+        "room_id": "!roomid:room.com",
+        "sender": "@carl:example.com",
+        "state_key": "@carl:example.com",
+        "type": "org.matrix.msc3401.call.member"
+    });
+
+    let (content, state_key) = match from_json_value::<AnyStateEvent>(json_data).unwrap() {
+        AnyStateEvent::CallMember(StateEvent::Original(OriginalStateEvent {
+            content,
+            state_key,
+            ..
+        })) => (content, state_key),
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(state_key, "@carl:example.com");
+    assert_eq!(content.calls.len(), 1);
+
+    let call = &content.calls[0];
+    assert_eq!(call.call_id, "cvsiu2893");
+    assert_eq!(call.devices.len(), 1);
+    assert_eq!(call.devices[0].device_id, "ASDUHDGFYUW");
+    assert_eq!(call.devices[0].session_id, "GHKJFKLJLJ");
+    assert_eq!(call.devices[0].feeds[0].purpose, StreamPurpose::UserMedia);
+}
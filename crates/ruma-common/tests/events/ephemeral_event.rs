@@ -113,3 +113,94 @@ fn deserialize_ephemeral_receipt() {
                 == Some(MilliSecondsSinceUnixEpoch(uint!(1)))
     );
 }
+
+#[cfg(feature = "unstable-msc2285")]
+#[test]
+fn ephemeral_private_receipt_roundtrip() {
+    let event_id = event_id!("$h29iv0s8:example.com");
+    let user_id = user_id!("@carl:example.com");
+
+    let content = ReceiptEventContent(btreemap! {
+        event_id.to_owned() => btreemap! {
+            ReceiptType::ReadPrivate => btreemap! {
+                user_id.to_owned() => Receipt::new(MilliSecondsSinceUnixEpoch(uint!(1))),
+            },
+        },
+    });
+
+    let json = json!({
+        "$h29iv0s8:example.com": {
+            "org.matrix.msc2285.read.private": {
+                "@carl:example.com": { "ts": 1 }
+            }
+        }
+    });
+    assert_eq!(to_json_value(&content).unwrap(), json);
+
+    let content = from_json_value::<ReceiptEventContent>(json).unwrap();
+    let (receipt_event_id, receipt) =
+        content.user_receipt(user_id, ReceiptType::ReadPrivate).unwrap();
+    assert_eq!(receipt_event_id, event_id);
+    assert_eq!(receipt.ts, Some(MilliSecondsSinceUnixEpoch(uint!(1))));
+
+    // The stable name is accepted too.
+    let content = from_json_value::<ReceiptEventContent>(json!({
+        "$h29iv0s8:example.com": {
+            "m.read.private": {
+                "@carl:example.com": { "ts": 1 }
+            }
+        }
+    }))
+    .unwrap();
+    assert!(content.user_receipt(user_id, ReceiptType::ReadPrivate).is_some());
+}
+
+#[cfg(feature = "unstable-msc3771")]
+#[test]
+fn ephemeral_threaded_receipt_roundtrip() {
+    use ruma_common::receipt::ReceiptThread;
+
+    let event_id = event_id!("$h29iv0s8:example.com");
+    let user_id = user_id!("@carl:example.com");
+
+    let mut receipt = Receipt::new(MilliSecondsSinceUnixEpoch(uint!(1)));
+    receipt.thread = ReceiptThread::Thread(event_id!("$thread_root:example.com").to_owned());
+    let content = ReceiptEventContent(btreemap! {
+        event_id.to_owned() => btreemap! {
+            ReceiptType::Read => btreemap! {
+                user_id.to_owned() => receipt,
+            },
+        },
+    });
+
+    let json = json!({
+        "$h29iv0s8:example.com": {
+            "m.read": {
+                "@carl:example.com": {
+                    "ts": 1,
+                    "thread_id": "$thread_root:example.com"
+                }
+            }
+        }
+    });
+    assert_eq!(to_json_value(&content).unwrap(), json);
+
+    let content = from_json_value::<ReceiptEventContent>(json).unwrap();
+    let (_, receipt) = content.user_receipt(user_id, ReceiptType::Read).unwrap();
+    assert_eq!(
+        receipt.thread,
+        ReceiptThread::Thread(event_id!("$thread_root:example.com").to_owned())
+    );
+
+    // A receipt without a thread ID is unthreaded.
+    let content = from_json_value::<ReceiptEventContent>(json!({
+        "$h29iv0s8:example.com": {
+            "m.read": {
+                "@carl:example.com": { "ts": 1 }
+            }
+        }
+    }))
+    .unwrap();
+    let (_, receipt) = content.user_receipt(user_id, ReceiptType::Read).unwrap();
+    assert_eq!(receipt.thread, ReceiptThread::Unthreaded);
+}
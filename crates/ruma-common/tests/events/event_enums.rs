@@ -1,7 +1,7 @@
-use js_int::{uint, UInt};
+use js_int::uint;
 use matches::assert_matches;
 use ruma_common::{
-    event_id, events::MessageLikeEvent, room_id, user_id, MilliSecondsSinceUnixEpoch,
+    event_id, events::MessageLikeEvent, room_id, user_id, MilliSecondsSinceUnixEpoch, VoipVersionId,
 };
 use serde_json::{from_value as from_json_value, json};
 
@@ -55,7 +55,7 @@ fn deserialize_message_event() {
             room_id,
             sender,
             unsigned,
-        })) if sdp == "Hello" && call_id == "foofoo" && version == UInt::new(1).unwrap()
+        })) if sdp == "Hello" && call_id == "foofoo" && version == VoipVersionId::V1
             && event_id == event_id!("$h29iv0s8:example.com")
             && origin_server_ts == MilliSecondsSinceUnixEpoch(uint!(1))
             && room_id == room_id!("!roomid:room.com")
@@ -12,7 +12,7 @@ use ruma_common::{
     },
     mxc_uri, room_id,
     serde::Raw,
-    user_id, MilliSecondsSinceUnixEpoch,
+    user_id, MilliSecondsSinceUnixEpoch, VoipVersionId,
 };
 use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
 
@@ -144,7 +144,7 @@ fn deserialize_message_call_answer_content() {
             call_id,
             version,
             ..
-        }) if sdp == "Hello" && call_id == "foofoo" && version == UInt::new(1).unwrap()
+        }) if sdp == "Hello" && call_id == "foofoo" && version == VoipVersionId::V1
     );
 }
 
@@ -184,7 +184,7 @@ fn deserialize_message_call_answer() {
             room_id,
             sender,
             unsigned,
-        })) if sdp == "Hello" && call_id == "foofoo" && version == UInt::new(1).unwrap()
+        })) if sdp == "Hello" && call_id == "foofoo" && version == VoipVersionId::V1
             && event_id == event_id!("$h29iv0s8:example.com")
             && origin_server_ts == MilliSecondsSinceUnixEpoch(uint!(1))
             && room_id == room_id!("!roomid:room.com")
@@ -312,7 +312,7 @@ fn deserialize_message_then_convert_to_full() {
             unsigned,
         })) if sdp == "Hello"
             && call_id == "foofoo"
-            && version == uint!(1)
+            && version == VoipVersionId::V1
             && event_id == "$h29iv0s8:example.com"
             && origin_server_ts == MilliSecondsSinceUnixEpoch(uint!(1))
             && room_id == "!roomid:room.com"
@@ -1,6 +1,8 @@
 #![cfg(feature = "events")]
 
 mod audio;
+mod beacon;
+mod call;
 mod enums;
 mod ephemeral_event;
 mod event;
@@ -12,6 +14,7 @@ mod initial_state;
 mod location;
 mod message;
 mod message_event;
+mod poll;
 mod pdu;
 mod redacted;
 mod redaction;
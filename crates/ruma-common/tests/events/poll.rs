@@ -0,0 +1,273 @@
+#![cfg(feature = "unstable-msc3381")]
+
+use std::convert::TryInto;
+
+use js_int::uint;
+use matches::assert_matches;
+use ruma_common::{
+    event_id,
+    events::{
+        message::MessageContent,
+        poll::{
+            compile_poll_results,
+            end::PollEndEventContent,
+            response::{PollResponseEventContent, SelectionsContent},
+            start::{PollAnswer, PollKind, PollStartContent, PollStartEventContent},
+            PollResponseData,
+        },
+        relation::Reference,
+        AnyMessageLikeEvent, MessageLikeEvent, OriginalMessageLikeEvent,
+    },
+    user_id, MilliSecondsSinceUnixEpoch,
+};
+use serde_json::{from_value as from_json_value, json, to_value as to_json_value};
+
+fn poll_start_content() -> PollStartContent {
+    PollStartContent::new(
+        MessageContent::plain("How's the weather?"),
+        PollKind::Undisclosed,
+        vec![
+            PollAnswer::new("not-bad".to_owned(), MessageContent::plain("Not bad…")),
+            PollAnswer::new("fine".to_owned(), MessageContent::plain("Fine.")),
+            PollAnswer::new("amazing".to_owned(), MessageContent::plain("Amazing!")),
+        ],
+    )
+}
+
+#[test]
+fn poll_start_content_serialization() {
+    let event_content = PollStartEventContent::with_plain_text_fallback(poll_start_content());
+
+    assert_eq!(
+        to_json_value(&event_content).unwrap(),
+        json!({
+            "org.matrix.msc1767.text": "How's the weather?\n1. Not bad…\n2. Fine.\n3. Amazing!",
+            "m.poll.start": {
+                "question": { "org.matrix.msc1767.text": "How's the weather?" },
+                "kind": "m.poll.undisclosed",
+                "max_selections": 1,
+                "answers": [
+                    { "id": "not-bad", "org.matrix.msc1767.text": "Not bad…" },
+                    { "id": "fine", "org.matrix.msc1767.text": "Fine." },
+                    { "id": "amazing", "org.matrix.msc1767.text": "Amazing!" },
+                ],
+            },
+        })
+    );
+}
+
+#[test]
+fn poll_start_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "m.text": "How's the weather?\n1. Not bad…\n2. Fine.",
+            "m.poll.start": {
+                "question": { "m.text": "How's the weather?" },
+                "kind": "m.poll.disclosed",
+                "max_selections": 2,
+                "answers": [
+                    { "id": "not-bad", "m.text": "Not bad…" },
+                    { "id": "fine", "m.text": "Fine." },
+                ],
+            },
+        },
+        "event_id": "$event:notareal.hs",
+        "origin_server_ts": 134_829_848,
+        "room_id": "!roomid:notareal.hs",
+        "sender": "@user:notareal.hs",
+        "type": "m.poll.start",
+    });
+
+    let content = match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+        AnyMessageLikeEvent::PollStart(MessageLikeEvent::Original(OriginalMessageLikeEvent {
+            content,
+            ..
+        })) => content,
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(content.message.find_plain(), Some("How's the weather?\n1. Not bad…\n2. Fine."));
+
+    let poll = content.poll_start;
+    assert_eq!(poll.question.find_plain(), Some("How's the weather?"));
+    assert_eq!(poll.kind, PollKind::Disclosed);
+    assert_eq!(poll.max_selections, uint!(2));
+    assert_eq!(poll.answers.len(), 2);
+    assert_eq!(poll.answers[0].id, "not-bad");
+    assert_eq!(poll.answers[1].answer.find_plain(), Some("Fine."));
+}
+
+#[test]
+fn poll_start_defaults() {
+    let json_data = json!({
+        "question": { "m.text": "How's the weather?" },
+        "answers": [{ "id": "fine", "m.text": "Fine." }],
+    });
+
+    let poll = from_json_value::<PollStartContent>(json_data).unwrap();
+    assert_eq!(poll.kind, PollKind::Undisclosed);
+    assert_eq!(poll.max_selections, uint!(1));
+}
+
+#[test]
+fn poll_response_content_serialization() {
+    let event_content = PollResponseEventContent::new(
+        SelectionsContent::new(vec!["fine".to_owned()]),
+        Reference::new(event_id!("$poll_start").to_owned()),
+    );
+
+    assert_eq!(
+        to_json_value(&event_content).unwrap(),
+        json!({
+            "m.poll.response": {
+                "answers": ["fine"],
+            },
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": "$poll_start",
+            },
+        })
+    );
+}
+
+#[test]
+fn poll_response_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "m.poll.response": {
+                "answers": ["my-answer"],
+            },
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": "$related_event:notareal.hs",
+            },
+        },
+        "event_id": "$event:notareal.hs",
+        "origin_server_ts": 134_829_848,
+        "room_id": "!roomid:notareal.hs",
+        "sender": "@user:notareal.hs",
+        "type": "m.poll.response",
+    });
+
+    let event = match from_json_value::<AnyMessageLikeEvent>(json_data).unwrap() {
+        AnyMessageLikeEvent::PollResponse(MessageLikeEvent::Original(event)) => event,
+        _ => panic!("unexpected event"),
+    };
+    assert_eq!(event.content.relates_to.event_id, "$related_event:notareal.hs");
+
+    let data = event.data();
+    assert_eq!(data.sender, "@user:notareal.hs");
+    assert_eq!(data.origin_server_ts, MilliSecondsSinceUnixEpoch(uint!(134_829_848)));
+    assert_eq!(data.selections, ["my-answer".to_owned()]);
+}
+
+#[test]
+fn poll_end_content_serialization() {
+    let event_content = PollEndEventContent::plain(
+        "The poll has closed. Top answer: Amazing!",
+        Reference::new(event_id!("$poll_start").to_owned()),
+    );
+
+    assert_eq!(
+        to_json_value(&event_content).unwrap(),
+        json!({
+            "org.matrix.msc1767.text": "The poll has closed. Top answer: Amazing!",
+            "m.poll.end": {},
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": "$poll_start",
+            },
+        })
+    );
+}
+
+#[test]
+fn poll_end_event_deserialization() {
+    let json_data = json!({
+        "content": {
+            "m.text": "The poll has closed. Top answer: Amazing!",
+            "m.poll.end": {},
+            "m.relates_to": {
+                "rel_type": "m.reference",
+                "event_id": "$related_event:notareal.hs",
+            },
+        },
+        "event_id": "$event:notareal.hs",
+        "origin_server_ts": 134_829_848,
+        "room_id": "!roomid:notareal.hs",
+        "sender": "@user:notareal.hs",
+        "type": "m.poll.end",
+    });
+
+    assert_matches!(
+        from_json_value::<AnyMessageLikeEvent>(json_data).unwrap(),
+        AnyMessageLikeEvent::PollEnd(MessageLikeEvent::Original(OriginalMessageLikeEvent {
+            content: PollEndEventContent { message, relates_to, .. },
+            ..
+        })) if message.find_plain() == Some("The poll has closed. Top answer: Amazing!")
+            && relates_to.event_id == "$related_event:notareal.hs"
+    );
+}
+
+fn response<'a>(sender: &'a str, ts: u32, selections: &'a [String]) -> PollResponseData<'a> {
+    PollResponseData {
+        sender: sender.try_into().unwrap(),
+        origin_server_ts: MilliSecondsSinceUnixEpoch(ts.into()),
+        selections,
+    }
+}
+
+#[test]
+fn compile_results() {
+    let poll = poll_start_content();
+
+    let fine = vec!["fine".to_owned()];
+    let amazing = vec!["amazing".to_owned()];
+    let two_answers = vec!["amazing".to_owned(), "not-bad".to_owned()];
+    let unknown = vec!["terrible".to_owned()];
+
+    let results = compile_poll_results(
+        &poll,
+        vec![
+            // Latest response of alice is `amazing`.
+            response("@alice:localhost", 2, &amazing),
+            response("@alice:localhost", 1, &fine),
+            // Only the first selection of bob is taken into account.
+            response("@bob:localhost", 1, &two_answers),
+            // The vote of carl is spoiled by an unknown answer.
+            response("@carl:localhost", 1, &fine),
+            response("@carl:localhost", 2, &unknown),
+            // The response of dan is sent after the end.
+            response("@dan:localhost", 4, &fine),
+        ],
+        Some(MilliSecondsSinceUnixEpoch(uint!(3))),
+    );
+
+    assert_eq!(results.keys().copied().collect::<Vec<_>>(), ["not-bad", "fine", "amazing"]);
+    assert!(results["not-bad"].is_empty());
+    assert!(results["fine"].is_empty());
+    assert_eq!(
+        results["amazing"].iter().copied().collect::<Vec<_>>(),
+        [user_id!("@alice:localhost"), user_id!("@bob:localhost")]
+    );
+}
+
+#[test]
+fn compile_results_max_selections() {
+    let mut poll = poll_start_content();
+    poll.max_selections = uint!(2);
+
+    let selections = vec![
+        "fine".to_owned(),
+        "fine".to_owned(),
+        "terrible".to_owned(),
+        "amazing".to_owned(),
+        "not-bad".to_owned(),
+    ];
+
+    let results =
+        compile_poll_results(&poll, vec![response("@alice:localhost", 1, &selections)], None);
+
+    assert!(results["not-bad"].is_empty());
+    assert!(results["fine"].contains(user_id!("@alice:localhost")));
+    assert!(results["amazing"].contains(user_id!("@alice:localhost")));
+}
@@ -158,12 +158,33 @@ pub struct ReceiptMap {
     /// Read receipts for users in the room.
     #[serde(rename = "m.read")]
     pub read: BTreeMap<OwnedUserId, ReceiptData>,
+
+    /// Private read receipts for users in the room.
+    ///
+    /// [MSC2285] specifies that servers must not send these receipts to other servers, this is
+    /// only provided to be able to handle them if they are received.
+    ///
+    /// This uses the unstable prefix in [MSC2285].
+    ///
+    /// [MSC2285]: https://github.com/matrix-org/matrix-spec-proposals/pull/2285
+    #[cfg(feature = "unstable-msc2285")]
+    #[serde(
+        rename = "org.matrix.msc2285.read.private",
+        alias = "m.read.private",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub read_private: BTreeMap<OwnedUserId, ReceiptData>,
 }
 
 impl ReceiptMap {
     /// Creates a new `ReceiptMap`.
     pub fn new(read: BTreeMap<OwnedUserId, ReceiptData>) -> Self {
-        Self { read }
+        Self {
+            read,
+            #[cfg(feature = "unstable-msc2285")]
+            read_private: BTreeMap::new(),
+        }
     }
 }
 
@@ -446,6 +467,79 @@ mod test {
         assert_eq!(serde_json::to_value(&edu).unwrap(), json);
     }
 
+    #[cfg(feature = "unstable-msc2285")]
+    #[test]
+    fn private_receipt_edu() {
+        let json = json!({
+            "content": {
+                "!some_room:example.org": {
+                    "m.read": {},
+                    "org.matrix.msc2285.read.private": {
+                        "@john:matrix.org": {
+                            "data": {
+                                "ts": 1_533_358
+                            },
+                            "event_ids": [
+                                "$read_this_event:matrix.org"
+                            ]
+                        }
+                    }
+                }
+            },
+            "edu_type": "m.receipt"
+        });
+
+        let edu = serde_json::from_value::<Edu>(json.clone()).unwrap();
+        let receipts = match &edu {
+            Edu::Receipt(ReceiptContent { receipts }) => receipts,
+            _ => panic!("unexpected EDU"),
+        };
+        let receipt_map = receipts.get(room_id!("!some_room:example.org")).unwrap();
+        assert!(receipt_map.read.is_empty());
+        assert!(receipt_map.read_private.contains_key(user_id!("@john:matrix.org")));
+
+        assert_eq!(serde_json::to_value(&edu).unwrap(), json);
+    }
+
+    #[cfg(feature = "unstable-msc3771")]
+    #[test]
+    fn threaded_receipt_edu() {
+        use ruma_common::{event_id, receipt::ReceiptThread};
+
+        let json = json!({
+            "content": {
+                "!some_room:example.org": {
+                    "m.read": {
+                        "@john:matrix.org": {
+                            "data": {
+                                "ts": 1_533_358,
+                                "thread_id": "$thread_root:matrix.org"
+                            },
+                            "event_ids": [
+                                "$read_this_event:matrix.org"
+                            ]
+                        }
+                    }
+                }
+            },
+            "edu_type": "m.receipt"
+        });
+
+        let edu = serde_json::from_value::<Edu>(json.clone()).unwrap();
+        let receipts = match &edu {
+            Edu::Receipt(ReceiptContent { receipts }) => receipts,
+            _ => panic!("unexpected EDU"),
+        };
+        let receipt_data = &receipts.get(room_id!("!some_room:example.org")).unwrap().read
+            [user_id!("@john:matrix.org")];
+        assert_eq!(
+            receipt_data.data.thread,
+            ReceiptThread::Thread(event_id!("$thread_root:matrix.org").to_owned())
+        );
+
+        assert_eq!(serde_json::to_value(&edu).unwrap(), json);
+    }
+
     #[test]
     fn typing_edu() {
         let json = json!({
@@ -1,6 +1,13 @@
 //! Endpoints to look up Matrix IDs bound to 3PIDs.
 
-use ruma_common::serde::StringEnum;
+use std::collections::BTreeMap;
+
+use ruma_common::{
+    serde::{base64::UrlSafe, Base64, StringEnum},
+    thirdparty::Medium,
+    OwnedUserId, UserId,
+};
+use sha2::{Digest, Sha256};
 
 use crate::PrivOwnedStr;
 
@@ -27,8 +34,60 @@ pub enum IdentifierHashingAlgorithm {
     _Custom(PrivOwnedStr),
 }
 
+impl IdentifierHashingAlgorithm {
+    /// Hashes a 3PID with this algorithm, to look it up with [`lookup_3pid`].
+    ///
+    /// With `sha256`, the SHA-256 hash of `"{address} {medium} {pepper}"` is encoded with unpadded
+    /// URL-safe base64. With `none`, the `pepper` is ignored and `"{address} {medium}"` is used
+    /// as is.
+    ///
+    /// The address should be normalized beforehand, e.g. email addresses should be lowercase.
+    ///
+    /// Returns `None` if the algorithm is not supported.
+    ///
+    /// [`lookup_3pid`]: lookup_3pid::v2
+    pub fn hash_3pid(&self, medium: &Medium, address: &str, pepper: &str) -> Option<String> {
+        match self {
+            Self::Sha256 => {
+                let hash = Sha256::digest(format!("{} {} {}", address, medium, pepper).as_bytes());
+                Some(Base64::<UrlSafe, _>::new(hash).encode())
+            }
+            Self::None => Some(format!("{} {}", address, medium)),
+            Self::_Custom(_) => None,
+        }
+    }
+
+    /// Builds the table used by an identity server to answer [`lookup_3pid`] requests made with
+    /// this algorithm and `pepper`.
+    ///
+    /// `associations` are the 3PIDs known by the identity server with the user they are bound
+    /// to. The keys of the returned map are the hashed 3PIDs, as computed by
+    /// [`hash_3pid`](Self::hash_3pid).
+    ///
+    /// Returns `None` if the algorithm is not supported.
+    ///
+    /// [`lookup_3pid`]: lookup_3pid::v2
+    pub fn lookup_table<'a, I>(
+        &self,
+        pepper: &str,
+        associations: I,
+    ) -> Option<BTreeMap<String, OwnedUserId>>
+    where
+        I: IntoIterator<Item = (&'a Medium, &'a str, &'a UserId)>,
+    {
+        associations
+            .into_iter()
+            .map(|(medium, address, user_id)| {
+                Some((self.hash_3pid(medium, address, pepper)?, user_id.to_owned()))
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use ruma_common::{thirdparty::Medium, user_id};
+
     use super::IdentifierHashingAlgorithm;
 
     #[test]
@@ -36,4 +95,50 @@ mod test {
         assert_eq!(IdentifierHashingAlgorithm::from("sha256"), IdentifierHashingAlgorithm::Sha256);
         assert_eq!(IdentifierHashingAlgorithm::from("none"), IdentifierHashingAlgorithm::None);
     }
+
+    #[test]
+    fn hash_3pid() {
+        // Example from the spec.
+        assert_eq!(
+            IdentifierHashingAlgorithm::Sha256
+                .hash_3pid(&Medium::Email, "alice@example.com", "matrixrocks")
+                .unwrap(),
+            "4kenr7N9drpCJ4AfalmlGQVsOn3o2RHjkADUpXJWZUc"
+        );
+        assert_eq!(
+            IdentifierHashingAlgorithm::None
+                .hash_3pid(&Medium::Msisdn, "12345678910", "matrixrocks")
+                .unwrap(),
+            "12345678910 msisdn"
+        );
+        assert_eq!(
+            IdentifierHashingAlgorithm::from("md5").hash_3pid(
+                &Medium::Email,
+                "alice@example.com",
+                "matrixrocks"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn lookup_table() {
+        let alice = user_id!("@alice:example.com");
+        let bob = user_id!("@bob:example.com");
+        let associations = [
+            (&Medium::Email, "alice@example.com", alice),
+            (&Medium::Email, "bob@example.com", bob),
+        ];
+
+        let table = IdentifierHashingAlgorithm::Sha256
+            .lookup_table("matrixrocks", associations.iter().copied())
+            .unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table["4kenr7N9drpCJ4AfalmlGQVsOn3o2RHjkADUpXJWZUc"], alice);
+        assert_eq!(table["LJwSazmv46n0hlMlsb_iYxI0_HXEqy_yj6Jm636cdT8"], bob);
+
+        assert!(IdentifierHashingAlgorithm::from("md5")
+            .lookup_table("matrixrocks", associations.iter().copied())
+            .is_none());
+    }
 }
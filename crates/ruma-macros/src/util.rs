@@ -35,14 +35,21 @@ pub(crate) fn to_camel_case(name: &Ident) -> Ident {
 
 /// Splits the given string on `.` and `_` removing the `m.` then camel casing to give a Rust type
 /// name.
+///
+/// Unstable names of the form `org.matrix.mscXXXX.name` give the same Rust type name as
+/// `m.name`.
 pub(crate) fn m_prefix_name_to_type_name(name: &LitStr) -> syn::Result<Ident> {
     let span = name.span();
     let name = name.value();
 
-    let name = name.strip_prefix("m.").ok_or_else(|| {
+    let name = name.strip_prefix("m.").or_else(|| strip_msc_prefix(&name)).ok_or_else(|| {
         syn::Error::new(
             span,
-            format!("well-known matrix events have to start with `m.` found `{}`", name),
+            format!(
+                "well-known matrix events have to start with `m.` or `org.matrix.mscXXXX.` \
+                 found `{}`",
+                name
+            ),
         )
     })?;
 
@@ -55,3 +62,9 @@ pub(crate) fn m_prefix_name_to_type_name(name: &LitStr) -> syn::Result<Ident> {
 
     Ok(Ident::new(&s, span))
 }
+
+/// Strips the `org.matrix.mscXXXX.` prefix of an unstable name.
+fn strip_msc_prefix(name: &str) -> Option<&str> {
+    let (msc, rest) = name.strip_prefix("org.matrix.msc")?.split_once('.')?;
+    (!msc.is_empty() && msc.bytes().all(|b| b.is_ascii_digit())).then(|| rest)
+}
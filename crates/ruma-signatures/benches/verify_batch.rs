@@ -0,0 +1,77 @@
+// Because of criterion `cargo bench` works,
+// but if you use `cargo bench -- --save-baseline <name>`
+// or pass any other args to it, it fails with the error
+// `cargo bench unknown option --save-baseline`.
+// To pass args to criterion, use this form
+// `cargo bench --bench <name of the bench> -- --save-baseline <name>`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruma_common::{serde::CanonicalJsonObject, RoomVersionId};
+use ruma_signatures::{
+    hash_and_sign_event, verify_event, verify_events_batch, Ed25519KeyPair, PublicKeyMap,
+};
+use serde_json::json;
+
+/// The maximum number of PDUs in a federation transaction.
+const PDU_COUNT: usize = 50;
+
+fn signed_events() -> (PublicKeyMap, Vec<CanonicalJsonObject>) {
+    let key_pair = Ed25519KeyPair::random("1".to_owned());
+
+    let mut public_key_map = PublicKeyMap::new();
+    public_key_map.insert(
+        "domain".to_owned(),
+        vec![(key_pair.key_id().to_string(), key_pair.public_key_base64())].into_iter().collect(),
+    );
+
+    let events = (0..PDU_COUNT)
+        .map(|depth| {
+            let mut object = serde_json::from_value(json!({
+                "auth_events": [],
+                "content": { "body": "Hello world!", "msgtype": "m.text" },
+                "depth": depth,
+                "origin": "domain",
+                "origin_server_ts": 1_000_000,
+                "prev_events": [],
+                "room_id": "!x:domain",
+                "sender": "@name:domain",
+                "type": "m.room.message",
+            }))
+            .unwrap();
+            hash_and_sign_event("domain", &key_pair, &mut object, &RoomVersionId::V9).unwrap();
+            object
+        })
+        .collect();
+
+    (public_key_map, events)
+}
+
+fn verify_events_one_by_one(c: &mut Criterion) {
+    let (public_key_map, events) = signed_events();
+
+    c.bench_function("verify 50 events one by one", |b| {
+        b.iter(|| {
+            for event in &events {
+                verify_event(&public_key_map, event, &RoomVersionId::V9).unwrap();
+            }
+        })
+    });
+}
+
+fn verify_events_in_batch(c: &mut Criterion) {
+    let (public_key_map, events) = signed_events();
+
+    c.bench_function("verify 50 events in batch", |b| {
+        b.iter(|| {
+            let results = verify_events_batch(
+                &public_key_map,
+                events.iter().map(|event| (event, &RoomVersionId::V9)),
+            );
+            assert!(results.iter().all(Result::is_ok));
+        })
+    });
+}
+
+criterion_group!(benches, verify_events_one_by_one, verify_events_in_batch);
+
+criterion_main!(benches);
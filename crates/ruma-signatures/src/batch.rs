@@ -0,0 +1,223 @@
+//! Verification of the signatures of several JSON objects or events at once.
+
+use std::convert::TryFrom;
+
+use ed25519_dalek::{PublicKey, Signature};
+use ruma_common::{serde::CanonicalJsonObject, RoomVersionId};
+
+use crate::{
+    functions::{event_signatures, json_signatures, SignedJson},
+    keys::PublicKeyMap,
+    verification::Verified,
+    Error, ParseError,
+};
+
+/// Uses a set of public keys to verify the signatures of several signed JSON objects at once.
+///
+/// This is equivalent to calling [`verify_json`](crate::verify_json) on every object, but all the
+/// signatures are checked together with ed25519 batch verification, which is faster when there
+/// are many of them.
+///
+/// Returns the result of the verification of each object, in the same order as `objects`. An
+/// invalid object doesn't prevent the others from being verified.
+pub fn verify_json_batch<'a, I>(public_key_map: &PublicKeyMap, objects: I) -> Vec<Result<(), Error>>
+where
+    I: IntoIterator<Item = &'a CanonicalJsonObject>,
+{
+    let items = objects
+        .into_iter()
+        .map(|object| json_signatures(public_key_map, object).map(|signed| (signed, ())))
+        .collect();
+
+    verify_prepared(items)
+}
+
+/// Verifies that several signed events contain all the required valid signatures.
+///
+/// This is equivalent to calling [`verify_event`](crate::verify_event) on every event, for
+/// example on the PDUs of a federation transaction, but all the signatures are checked together
+/// with ed25519 batch verification, which is faster when there are many of them.
+///
+/// Each event comes with the version of its room, since the events can come from different rooms.
+///
+/// Returns the result of the verification of each event, in the same order as `events`. An
+/// invalid event doesn't prevent the others from being verified.
+pub fn verify_events_batch<'a, I>(
+    public_key_map: &PublicKeyMap,
+    events: I,
+) -> Vec<Result<Verified, Error>>
+where
+    I: IntoIterator<Item = (&'a CanonicalJsonObject, &'a RoomVersionId)>,
+{
+    let items = events
+        .into_iter()
+        .map(|(object, version)| event_signatures(public_key_map, object, version))
+        .collect();
+
+    verify_prepared(items)
+}
+
+/// Verifies the signatures of the items of a batch, and returns their values if they are valid.
+fn verify_prepared<T>(items: Vec<Result<(SignedJson<'_>, T), Error>>) -> Vec<Result<T, Error>> {
+    // Parse all the keys and signatures, the items with invalid ones are not part of the batch.
+    let items: Vec<Result<_, Error>> = items
+        .into_iter()
+        .map(|item| {
+            let (signed, value) = item?;
+            let parsed = parse_signatures(&signed)?;
+            Ok((signed, parsed, value))
+        })
+        .collect();
+
+    let mut messages = Vec::new();
+    let mut signatures = Vec::new();
+    let mut public_keys = Vec::new();
+
+    for (signed, parsed, _) in items.iter().flatten() {
+        for (public_key, signature) in parsed {
+            messages.push(signed.message.as_bytes());
+            public_keys.push(*public_key);
+            signatures.push(*signature);
+        }
+    }
+
+    let batch_is_valid = messages.is_empty()
+        || ed25519_dalek::verify_batch(&messages, &signatures, &public_keys).is_ok();
+
+    items
+        .into_iter()
+        .map(|item| {
+            let (signed, _, value) = item?;
+
+            // If the batch is invalid, check the items one by one to find the invalid ones.
+            if !batch_is_valid {
+                signed.verify()?;
+            }
+
+            Ok(value)
+        })
+        .collect()
+}
+
+fn parse_signatures(signed: &SignedJson<'_>) -> Result<Vec<(PublicKey, Signature)>, Error> {
+    signed
+        .signatures
+        .iter()
+        .map(|(public_key, signature)| {
+            let public_key =
+                PublicKey::from_bytes(public_key.as_bytes()).map_err(ParseError::PublicKey)?;
+            let signature =
+                Signature::try_from(signature.as_bytes()).map_err(ParseError::Signature)?;
+            Ok((public_key, signature))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma_common::{
+        serde::{CanonicalJsonObject, CanonicalJsonValue},
+        RoomVersionId,
+    };
+    use serde_json::json;
+
+    use super::{verify_events_batch, verify_json_batch};
+    use crate::{sign_json, Ed25519KeyPair, Error, PublicKeyMap, VerificationError, Verified};
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::random("1".to_owned())
+    }
+
+    fn public_key_map(key_pair: &Ed25519KeyPair) -> PublicKeyMap {
+        let mut public_key_map = PublicKeyMap::new();
+        public_key_map.insert(
+            "domain".to_owned(),
+            vec![(key_pair.key_id().to_string(), key_pair.public_key_base64())]
+                .into_iter()
+                .collect(),
+        );
+        public_key_map
+    }
+
+    fn signed_event(key_pair: &Ed25519KeyPair, depth: u32) -> CanonicalJsonObject {
+        let mut object: CanonicalJsonObject = serde_json::from_value(json!({
+            "auth_events": [],
+            "content": {},
+            "depth": depth,
+            "hashes": {
+                "sha256": "5jM4wQpv6lnBo7CLIghJuHdW+s2CMBJPUOGOC89ncos"
+            },
+            "origin": "domain",
+            "origin_server_ts": 1000,
+            "prev_events": [],
+            "room_id": "!x:domain",
+            "sender": "@name:domain",
+            "type": "X",
+        }))
+        .unwrap();
+        sign_json("domain", key_pair, &mut object).unwrap();
+        object
+    }
+
+    #[test]
+    fn verify_events_batch_all_valid() {
+        let key_pair = key_pair();
+        let events: Vec<_> = (0..10).map(|depth| signed_event(&key_pair, depth)).collect();
+
+        let results = verify_events_batch(
+            &public_key_map(&key_pair),
+            events.iter().map(|event| (event, &RoomVersionId::V6)),
+        );
+
+        assert_eq!(results.len(), 10);
+        assert!(results.iter().all(|result| matches!(result, Ok(Verified::Signatures))));
+    }
+
+    #[test]
+    fn verify_events_batch_reports_invalid_events() {
+        let key_pair = key_pair();
+        let mut events: Vec<_> = (0..5).map(|depth| signed_event(&key_pair, depth)).collect();
+
+        // Tamper with the second event after signing it.
+        events[1].insert("depth".to_owned(), CanonicalJsonValue::Integer(42_u32.into()));
+        // Remove the signatures of the fourth event.
+        events[3].remove("signatures");
+
+        let results = verify_events_batch(
+            &public_key_map(&key_pair),
+            events.iter().map(|event| (event, &RoomVersionId::V6)),
+        );
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::Verification(VerificationError::Signature(_)))));
+        assert!(results[2].is_ok());
+        assert!(results[3].is_err());
+        assert!(results[4].is_ok());
+    }
+
+    #[test]
+    fn verify_json_batch_reports_invalid_objects() {
+        let key_pair = key_pair();
+        let mut objects: Vec<CanonicalJsonObject> = (0..3)
+            .map(|i| {
+                let mut object = serde_json::from_value(json!({ "i": i })).unwrap();
+                sign_json("domain", &key_pair, &mut object).unwrap();
+                object
+            })
+            .collect();
+        objects[2].insert("i".to_owned(), CanonicalJsonValue::Integer(5_u32.into()));
+
+        let results = verify_json_batch(&public_key_map(&key_pair), &objects);
+
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(Error::Verification(VerificationError::Signature(_)))));
+    }
+
+    #[test]
+    fn verify_batch_empty() {
+        let public_key_map = PublicKeyMap::new();
+        assert!(verify_json_batch(&public_key_map, &[]).is_empty());
+        assert!(verify_events_batch(&public_key_map, Vec::new()).is_empty());
+    }
+}
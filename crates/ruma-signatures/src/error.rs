@@ -1,5 +1,6 @@
 use ruma_common::{
-    serde::Base64DecodeError, EventId, OwnedEventId, OwnedServerName, RoomVersionId,
+    api::error::IntoHttpError, serde::Base64DecodeError, EventId, OwnedEventId, OwnedServerName,
+    RoomVersionId,
 };
 use thiserror::Error;
 
@@ -39,6 +40,19 @@ pub enum Error {
     /// PDU was too large
     #[error("PDU is larger than maximum of 65535 bytes")]
     PduSize,
+
+    /// Wrapper for [`IntoHttpError`], when converting an outgoing request failed.
+    #[error("HTTP conversion error: {0}")]
+    IntoHttp(#[from] IntoHttpError),
+
+    /// Wrapper for [`InvalidHeaderValue`](http::header::InvalidHeaderValue), when an
+    /// [`XMatrix`](crate::XMatrix) can't be converted to a header value.
+    #[error("invalid header value: {0}")]
+    HeaderValue(#[from] http::header::InvalidHeaderValue),
+
+    /// An error returned by a [`Signer`](crate::Signer).
+    #[error("Signer error: {0}")]
+    Signer(Box<dyn std::error::Error + Send + Sync>),
 }
 
 /// All errors related to JSON validation/parsing.
@@ -159,6 +173,18 @@ pub enum VerificationError {
     /// For when [`ed25519_dalek`] cannot verify a signature.
     #[error("Could not verify signature: {0}")]
     Signature(#[source] ed25519_dalek::SignatureError),
+
+    /// For when a request has no `Authorization` header using the `X-Matrix` scheme.
+    #[error("Could not find an X-Matrix Authorization header")]
+    AuthorizationNotFound,
+
+    /// For when the destination in an `X-Matrix` header is not the receiving server.
+    #[error("X-Matrix Authorization header is meant for another destination: {0:?}")]
+    DestinationMismatch(OwnedServerName),
+
+    /// For when the `X-Matrix` headers of a request have different origins.
+    #[error("X-Matrix Authorization headers have different origins, found {0:?}")]
+    OriginMismatch(OwnedServerName),
 }
 
 impl VerificationError {
@@ -221,6 +247,18 @@ pub enum ParseError {
     #[error("Could not parse signature: {0}")]
     Signature(#[source] ed25519_dalek::SignatureError),
 
+    /// For when an `X-Matrix` `Authorization` header is malformed.
+    #[error("Could not parse X-Matrix Authorization header: {0}")]
+    XMatrix(String),
+
+    /// For when a line of a signing key file is malformed.
+    #[error("Could not parse signing key: {0}")]
+    SigningKey(String),
+
+    /// For when a PEM document is malformed.
+    #[error("Could not parse PEM document: {0}")]
+    Pem(String),
+
     /// For when parsing base64 gives an error.
     #[error("Could not parse {of_type} base64 string {string:?}: {source}")]
     Base64 {
@@ -253,6 +291,10 @@ impl ParseError {
         .into()
     }
 
+    pub(crate) fn x_matrix<T: Into<String>>(message: T) -> Error {
+        Self::XMatrix(message.into()).into()
+    }
+
     pub(crate) fn base64<T1: Into<String>, T2: Into<String>>(
         of_type: T1,
         string: T2,
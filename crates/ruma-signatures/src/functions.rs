@@ -1,7 +1,6 @@
 //! Functions for signing and verifying JSON and events.
 
 use std::{
-    borrow::Cow,
     collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     mem,
@@ -12,11 +11,13 @@ use ruma_common::{
     serde::{base64::Standard, Base64, CanonicalJsonObject, CanonicalJsonValue},
     OwnedEventId, OwnedServerName, RoomVersionId, UserId,
 };
-use serde_json::{from_str as from_json_str, to_string as to_json_string};
+use serde_json::to_string as to_json_string;
 use sha2::{digest::Digest, Sha256};
 
 use crate::{
     keys::{KeyPair, PublicKeyMap},
+    signatures::Signature,
+    signer::Signer,
     split_id,
     verification::{Ed25519Verifier, Verified, Verifier},
     Error, JsonError, JsonType, ParseError, VerificationError,
@@ -46,12 +47,14 @@ static ALLOWED_KEYS: &[&str] = &[
 fn allowed_content_keys_for(event_type: &str, version: &RoomVersionId) -> &'static [&'static str] {
     match event_type {
         "m.room.member" => match version {
-            RoomVersionId::V9 => &["membership", "join_authorised_via_users_server"],
+            RoomVersionId::V9 | RoomVersionId::V10 => {
+                &["membership", "join_authorised_via_users_server"]
+            }
             _ => &["membership"],
         },
         "m.room.create" => &["creator"],
         "m.room.join_rules" => match version {
-            RoomVersionId::V8 | RoomVersionId::V9 => &["join_rule", "allow"],
+            RoomVersionId::V8 | RoomVersionId::V9 | RoomVersionId::V10 => &["join_rule", "allow"],
             _ => &["join_rule"],
         },
         "m.room.power_levels" => &[
@@ -154,21 +157,62 @@ pub fn sign_json<K>(
 where
     K: KeyPair,
 {
-    let (signatures_key, mut signature_map) = match object.remove_entry("signatures") {
-        Some((key, CanonicalJsonValue::Object(signatures))) => (Cow::Owned(key), signatures),
-        Some(_) => return Err(JsonError::not_of_type("signatures", JsonType::Object)),
-        None => (Cow::Borrowed("signatures"), BTreeMap::new()),
-    };
+    let json = json_to_sign(object)?;
+    let signature = key_pair.sign(json.as_bytes());
+
+    insert_signature(entity_id, signature, object)
+}
+
+/// Signs an arbitrary JSON object with a [`Signer`] and adds the signature to an object under the
+/// key `signatures`.
+///
+/// This is the same as [`sign_json`], except that the JSON is signed asynchronously, for example
+/// by a signing service running in another process.
+///
+/// # Errors
+///
+/// Returns an error if:
+///
+/// * `object` contains a field called `signatures` that is not a JSON object.
+/// * The signer fails to sign the JSON.
+pub async fn sign_json_async<S>(
+    entity_id: &str,
+    signer: &S,
+    object: &mut CanonicalJsonObject,
+) -> Result<(), Error>
+where
+    S: Signer + ?Sized,
+{
+    let json = json_to_sign(object)?;
+    let signature = signer.sign(json.as_bytes()).await?;
+
+    insert_signature(entity_id, signature, object)
+}
 
-    let maybe_unsigned_entry = object.remove_entry("unsigned");
+/// Get the canonical JSON string of an object to sign, without its `signatures` and `unsigned`.
+fn json_to_sign(object: &CanonicalJsonObject) -> Result<String, Error> {
+    match object.get("signatures") {
+        Some(CanonicalJsonValue::Object(_)) | None => {}
+        Some(_) => return Err(JsonError::not_of_type("signatures", JsonType::Object)),
+    }
 
-    // Get the canonical JSON string.
-    let json = to_json_string(object).map_err(JsonError::Serde)?;
+    canonical_json(object)
+}
 
-    // Sign the canonical JSON string.
-    let signature = key_pair.sign(json.as_bytes());
+/// Insert a new signature in the `signatures` of an object, creating them if necessary.
+fn insert_signature(
+    entity_id: &str,
+    signature: Signature,
+    object: &mut CanonicalJsonObject,
+) -> Result<(), Error> {
+    let signature_map = match object
+        .entry("signatures".to_owned())
+        .or_insert_with(|| CanonicalJsonValue::Object(BTreeMap::new()))
+    {
+        CanonicalJsonValue::Object(signatures) => signatures,
+        _ => return Err(JsonError::not_of_type("signatures", JsonType::Object)),
+    };
 
-    // Insert the new signature in the map we pulled out (or created) previously.
     let signature_set = signature_map
         .entry(entity_id.to_owned())
         .or_insert_with(|| CanonicalJsonValue::Object(BTreeMap::new()));
@@ -180,13 +224,6 @@ where
 
     signature_set.insert(signature.id(), CanonicalJsonValue::String(signature.base64()));
 
-    // Put `signatures` and `unsigned` back in.
-    object.insert(signatures_key.into(), CanonicalJsonValue::Object(signature_map));
-
-    if let Some((k, v)) = maybe_unsigned_entry {
-        object.insert(k, v);
-    }
-
     Ok(())
 }
 
@@ -266,52 +303,75 @@ pub fn verify_json(
     public_key_map: &PublicKeyMap,
     object: &CanonicalJsonObject,
 ) -> Result<(), Error> {
+    json_signatures(public_key_map, object)?.verify()
+}
+
+/// The signatures of a JSON object to verify, with the public keys to use and the signed message.
+pub(crate) struct SignedJson<'a> {
+    /// The canonical JSON that was signed.
+    pub(crate) message: String,
+
+    /// The public keys and the signatures to verify with them.
+    pub(crate) signatures: Vec<(&'a Base64, Base64<Standard>)>,
+}
+
+impl SignedJson<'_> {
+    /// Verifies all the signatures one by one.
+    pub(crate) fn verify(&self) -> Result<(), Error> {
+        for (public_key, signature) in &self.signatures {
+            Ed25519Verifier.verify_json(
+                public_key.as_bytes(),
+                signature.as_bytes(),
+                self.message.as_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Collects the signatures to verify for [`verify_json`].
+pub(crate) fn json_signatures<'a>(
+    public_key_map: &'a PublicKeyMap,
+    object: &CanonicalJsonObject,
+) -> Result<SignedJson<'a>, Error> {
     let signature_map = match object.get("signatures") {
-        Some(CanonicalJsonValue::Object(signatures)) => signatures.clone(),
+        Some(CanonicalJsonValue::Object(signatures)) => signatures,
         Some(_) => return Err(JsonError::not_of_type("signatures", JsonType::Object)),
         None => return Err(JsonError::field_missing_from_object("signatures")),
     };
 
+    let mut signatures = Vec::new();
+
     for (entity_id, signature_set) in signature_map {
         let signature_set = match signature_set {
             CanonicalJsonValue::Object(set) => set,
             _ => return Err(JsonError::not_multiples_of_type("signature sets", JsonType::Object)),
         };
 
-        let public_keys = match public_key_map.get(&entity_id) {
+        let public_keys = match public_key_map.get(entity_id) {
             Some(keys) => keys,
-            None => {
-                return Err(JsonError::key_missing("public_key_map", "public_keys", &entity_id))
-            }
+            None => return Err(JsonError::key_missing("public_key_map", "public_keys", entity_id)),
         };
 
-        for (key_id, signature) in &signature_set {
+        for (key_id, signature) in signature_set {
             let signature = match signature {
                 CanonicalJsonValue::String(s) => s,
                 _ => return Err(JsonError::not_of_type("signature", JsonType::String)),
             };
 
             let public_key = public_keys.get(key_id).ok_or_else(|| {
-                JsonError::key_missing(
-                    format!("public_keys of {}", &entity_id),
-                    "signature",
-                    key_id,
-                )
+                JsonError::key_missing(format!("public_keys of {}", entity_id), "signature", key_id)
             })?;
 
             let signature = Base64::<Standard>::parse(signature)
                 .map_err(|e| ParseError::base64("signature", signature, e))?;
 
-            verify_json_with(
-                &Ed25519Verifier,
-                public_key.as_bytes(),
-                signature.as_bytes(),
-                object,
-            )?;
+            signatures.push((public_key, signature));
         }
     }
 
-    Ok(())
+    Ok(SignedJson { message: canonical_json(object)?, signatures })
 }
 
 /// Uses a public key to verify a signed JSON object.
@@ -326,7 +386,7 @@ pub fn verify_json(
 /// # Errors
 ///
 /// Returns an error if verification fails.
-fn verify_json_with<V>(
+pub(crate) fn verify_json_with<V>(
     verifier: &V,
     public_key: &[u8],
     signature: &[u8],
@@ -504,6 +564,48 @@ pub fn hash_and_sign_event<K>(
 where
     K: KeyPair,
 {
+    let mut redacted = insert_content_hash(object, version)?;
+
+    sign_json(entity_id, key_pair, &mut redacted)?;
+
+    object.insert("signatures".into(), mem::take(redacted.get_mut("signatures").unwrap()));
+
+    Ok(())
+}
+
+/// Hashes and signs an event with a [`Signer`] and adds the hash and signature to objects under
+/// the keys `hashes` and `signatures`, respectively.
+///
+/// This is the same as [`hash_and_sign_event`], except that the event is signed asynchronously,
+/// for example by a signing service running in another process.
+///
+/// # Errors
+///
+/// Returns an error if the event is too large, if redaction fails or if the signer fails to sign
+/// the event.
+pub async fn hash_and_sign_event_async<S>(
+    entity_id: &str,
+    signer: &S,
+    object: &mut CanonicalJsonObject,
+    version: &RoomVersionId,
+) -> Result<(), Error>
+where
+    S: Signer + ?Sized,
+{
+    let mut redacted = insert_content_hash(object, version)?;
+
+    sign_json_async(entity_id, signer, &mut redacted).await?;
+
+    object.insert("signatures".into(), mem::take(redacted.get_mut("signatures").unwrap()));
+
+    Ok(())
+}
+
+/// Inserts the content hash of an event in its `hashes`, and returns the redacted event to sign.
+fn insert_content_hash(
+    object: &mut CanonicalJsonObject,
+    version: &RoomVersionId,
+) -> Result<CanonicalJsonObject, Error> {
     let hash = content_hash(object)?;
 
     let hashes_value = object
@@ -517,13 +619,7 @@ where
         _ => return Err(JsonError::not_of_type("hashes", JsonType::Object)),
     };
 
-    let mut redacted = redact(object, version)?;
-
-    sign_json(entity_id, key_pair, &mut redacted)?;
-
-    object.insert("signatures".into(), mem::take(redacted.get_mut("signatures").unwrap()));
-
-    Ok(())
+    redact(object, version)
 }
 
 /// Verifies that the signed event contains all the required valid signatures.
@@ -598,6 +694,18 @@ pub fn verify_event(
     object: &CanonicalJsonObject,
     version: &RoomVersionId,
 ) -> Result<Verified, Error> {
+    let (signed, verified) = event_signatures(public_key_map, object, version)?;
+    signed.verify()?;
+
+    Ok(verified)
+}
+
+/// Collects the signatures to verify for [`verify_event`], and checks the content hash.
+pub(crate) fn event_signatures<'a>(
+    public_key_map: &'a PublicKeyMap,
+    object: &CanonicalJsonObject,
+    version: &RoomVersionId,
+) -> Result<(SignedJson<'a>, Verified), Error> {
     let redacted = redact(object, version)?;
 
     let hash = match object.get("hashes") {
@@ -621,7 +729,7 @@ pub fn verify_event(
     };
 
     let servers_to_check = servers_to_check_signatures(object, version)?;
-    let canonical_json = from_json_str(&canonical_json(&redacted)?).map_err(JsonError::from)?;
+    let mut signatures = Vec::new();
 
     for entity_id in servers_to_check {
         let signature_set = match signature_map.get(entity_id.as_str()) {
@@ -646,49 +754,38 @@ pub fn verify_event(
             }
 
             if let Some(signature) = signature_set.get(key_id) {
-                maybe_signature_and_public_key = Some(SignatureAndPubkey { signature, public_key });
+                maybe_signature_and_public_key = Some((signature, public_key));
 
                 break;
             }
         }
 
-        let signature_and_pubkey = match maybe_signature_and_public_key {
+        let (signature, public_key) = match maybe_signature_and_public_key {
             Some(value) => value,
             None => return Err(VerificationError::UnknownPublicKeysForSignature.into()),
         };
 
-        let signature = match signature_and_pubkey.signature {
+        let signature = match signature {
             CanonicalJsonValue::String(signature) => signature,
             _ => return Err(JsonError::not_of_type("signature", JsonType::String)),
         };
 
-        let public_key = signature_and_pubkey.public_key;
-
         let signature = Base64::<Standard>::parse(signature)
             .map_err(|e| ParseError::base64("signature", signature, e))?;
 
-        verify_json_with(
-            &Ed25519Verifier,
-            public_key.as_bytes(),
-            signature.as_bytes(),
-            &canonical_json,
-        )?;
+        signatures.push((public_key, signature));
     }
 
+    let signed = SignedJson { message: canonical_json(&redacted)?, signatures };
     let calculated_hash = content_hash(object)?;
 
     if let Ok(hash) = Base64::<Standard>::parse(hash) {
         if hash.as_bytes() == calculated_hash.as_bytes() {
-            return Ok(Verified::All);
+            return Ok((signed, Verified::All));
         }
     }
 
-    Ok(Verified::Signatures)
-}
-
-struct SignatureAndPubkey<'a> {
-    signature: &'a CanonicalJsonValue,
-    public_key: &'a Base64,
+    Ok((signed, Verified::Signatures))
 }
 
 /// Internal implementation detail of the canonical JSON algorithm.
@@ -804,7 +901,7 @@ fn object_retain_keys(object: &mut CanonicalJsonObject, keys: &[&str]) {
 ///
 /// Starting with room version 8, if join_authorised_via_users_server is present, a signature from
 /// that user is required.
-fn servers_to_check_signatures(
+pub(crate) fn servers_to_check_signatures(
     object: &CanonicalJsonObject,
     version: &RoomVersionId,
 ) -> Result<BTreeSet<OwnedServerName>, Error> {
@@ -845,7 +942,7 @@ fn servers_to_check_signatures(
         | RoomVersionId::V6
         | RoomVersionId::V7 => {}
         // TODO: And for all future versions that have join_authorised_via_users_server
-        RoomVersionId::V8 | RoomVersionId::V9 => {
+        RoomVersionId::V8 | RoomVersionId::V9 | RoomVersionId::V10 => {
             if let Some(authorized_user) = object
                 .get("content")
                 .and_then(|c| c.as_object())
@@ -889,7 +986,7 @@ mod tests {
 
     use super::canonical_json;
     use crate::{
-        sign_json, verify_event, Ed25519KeyPair, Error, PublicKeyMap, PublicKeySet,
+        redact, sign_json, verify_event, Ed25519KeyPair, Error, PublicKeyMap, PublicKeySet,
         VerificationError, Verified,
     };
 
@@ -1031,11 +1128,41 @@ mod tests {
         add_key_to_map(&mut public_key_map, "domain-sender", &key_pair_sender);
         add_key_to_map(&mut public_key_map, "domain-authorized", &key_pair_authorized);
 
-        let verification_result = verify_event(&public_key_map, &signed_event, &RoomVersionId::V9);
+        for version in [RoomVersionId::V9, RoomVersionId::V10] {
+            let verification_result = verify_event(&public_key_map, &signed_event, &version);
 
-        assert!(verification_result.is_ok());
-        let verification = verification_result.unwrap();
-        assert!(matches!(verification, Verified::Signatures));
+            assert!(verification_result.is_ok());
+            let verification = verification_result.unwrap();
+            assert!(matches!(verification, Verified::Signatures));
+        }
+    }
+
+    #[test]
+    fn redact_join_rules_v10() {
+        let event = serde_json::from_str(
+            r#"{
+                "content": {
+                    "join_rule": "knock_restricted",
+                    "allow": [{ "type": "m.room_membership", "room_id": "!y:domain" }],
+                    "extra": "removed"
+                },
+                "room_id": "!x:domain",
+                "sender": "@name:domain",
+                "state_key": "",
+                "type": "m.room.join_rules"
+            }"#,
+        )
+        .unwrap();
+
+        let redacted = redact(&event, &RoomVersionId::V10).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&redacted["content"]).unwrap(),
+            json!({
+                "join_rule": "knock_restricted",
+                "allow": [{ "type": "m.room_membership", "room_id": "!y:domain" }],
+            })
+        );
     }
 
     #[test]
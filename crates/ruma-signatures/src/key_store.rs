@@ -0,0 +1,445 @@
+//! Storage of the signing keys of homeservers.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use js_int::UInt;
+
+use ruma_common::{
+    serde::{base64::Standard, Base64, CanonicalJsonObject, CanonicalJsonValue, Raw},
+    MilliSecondsSinceUnixEpoch, OwnedServerName, RoomVersionId, ServerName,
+};
+use serde::Deserialize;
+
+use crate::{
+    functions::{servers_to_check_signatures, verify_json_with},
+    keys::{PublicKeyMap, PublicKeySet},
+    split_id,
+    verification::{Ed25519Verifier, Verified},
+    verify_event, Error, JsonError, JsonType, ParseError, VerificationError,
+};
+
+/// A store for the public signing keys of homeservers.
+pub trait KeyStore {
+    /// Get the public keys of the given server.
+    ///
+    /// If `valid_at` is set, only the keys that were valid at that time should be returned.
+    /// Otherwise, all the known keys of the server should be returned.
+    ///
+    /// Returns `None` if no keys are known for the server.
+    fn public_keys(
+        &self,
+        server_name: &ServerName,
+        valid_at: Option<MilliSecondsSinceUnixEpoch>,
+    ) -> Option<PublicKeySet>;
+}
+
+/// All the keys of a [`PublicKeyMap`] are considered valid at any time.
+impl KeyStore for PublicKeyMap {
+    fn public_keys(
+        &self,
+        server_name: &ServerName,
+        _valid_at: Option<MilliSecondsSinceUnixEpoch>,
+    ) -> Option<PublicKeySet> {
+        self.get(server_name.as_str()).cloned()
+    }
+}
+
+/// An in-memory [`KeyStore`] that keeps track of the validity of the keys.
+///
+/// Keys are added with the responses of the federation `get_server_keys` and
+/// `get_remote_server_keys_batch` endpoints, whose signatures by the homeserver itself are checked.
+#[derive(Clone, Debug, Default)]
+pub struct ServerKeyStore {
+    servers: BTreeMap<OwnedServerName, BTreeMap<String, StoredKey>>,
+}
+
+#[derive(Clone, Debug)]
+struct StoredKey {
+    key: Base64,
+    valid_until_ts: MilliSecondsSinceUnixEpoch,
+}
+
+impl ServerKeyStore {
+    /// Creates an empty `ServerKeyStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the keys of a homeserver, as returned by the federation `get_server_keys` or
+    /// `get_remote_server_keys_batch` endpoints.
+    ///
+    /// The keys must be signed by the homeserver with all of its current `verify_keys`. The
+    /// current keys are valid until `valid_until_ts`, and the old keys until their `expired_ts`.
+    /// When a key is already known, the longest validity is kept.
+    ///
+    /// Returns the name of the homeserver.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keys can't be parsed or if their signatures are invalid.
+    pub fn add_server_keys<T>(&mut self, server_keys: &Raw<T>) -> Result<OwnedServerName, Error> {
+        let object: CanonicalJsonObject =
+            serde_json::from_str(server_keys.json().get()).map_err(JsonError::from)?;
+        let fields: ServerKeysFields =
+            serde_json::from_str(server_keys.json().get()).map_err(JsonError::from)?;
+
+        verify_self_signatures(&object, &fields)?;
+
+        let ServerKeysFields { server_name, verify_keys, old_verify_keys, valid_until_ts } = fields;
+
+        let keys = verify_keys
+            .into_iter()
+            .map(|(key_id, VerifyKey { key })| (key_id, StoredKey { key, valid_until_ts }))
+            .chain(old_verify_keys.into_iter().map(
+                |(key_id, OldVerifyKey { key, expired_ts })| {
+                    (key_id, StoredKey { key, valid_until_ts: expired_ts })
+                },
+            ));
+
+        for (key_id, key) in keys {
+            self.insert(server_name.clone(), key_id, key);
+        }
+
+        Ok(server_name)
+    }
+
+    /// Adds a trusted public key of a homeserver, valid until the given time.
+    ///
+    /// This can be used to add the keys of the local homeserver, or keys obtained by other
+    /// means. When the key is already known, the longest validity is kept.
+    pub fn add_key(
+        &mut self,
+        server_name: OwnedServerName,
+        key_id: String,
+        key: Base64,
+        valid_until_ts: MilliSecondsSinceUnixEpoch,
+    ) {
+        self.insert(server_name, key_id, StoredKey { key, valid_until_ts });
+    }
+
+    /// Get the public keys of the given server that were valid at the given time.
+    ///
+    /// A key that expired at exactly `ts` is still considered valid.
+    pub fn valid_keys(
+        &self,
+        server_name: &ServerName,
+        ts: MilliSecondsSinceUnixEpoch,
+    ) -> PublicKeySet {
+        self.servers
+            .get(server_name)
+            .into_iter()
+            .flatten()
+            .filter(|(_, stored)| stored.valid_until_ts >= ts)
+            .map(|(key_id, stored)| (key_id.clone(), stored.key.clone()))
+            .collect()
+    }
+
+    /// Get the latest time at which the given key is valid, if it is known.
+    pub fn valid_until_ts(
+        &self,
+        server_name: &ServerName,
+        key_id: &str,
+    ) -> Option<MilliSecondsSinceUnixEpoch> {
+        Some(self.servers.get(server_name)?.get(key_id)?.valid_until_ts)
+    }
+
+    fn insert(&mut self, server_name: OwnedServerName, key_id: String, key: StoredKey) {
+        let keys = self.servers.entry(server_name).or_default();
+        match keys.get(&key_id) {
+            Some(existing) if existing.valid_until_ts >= key.valid_until_ts => {}
+            _ => {
+                keys.insert(key_id, key);
+            }
+        }
+    }
+}
+
+impl KeyStore for ServerKeyStore {
+    fn public_keys(
+        &self,
+        server_name: &ServerName,
+        valid_at: Option<MilliSecondsSinceUnixEpoch>,
+    ) -> Option<PublicKeySet> {
+        let keys = self.servers.get(server_name)?;
+        Some(match valid_at {
+            Some(ts) => self.valid_keys(server_name, ts),
+            None => {
+                keys.iter().map(|(key_id, stored)| (key_id.clone(), stored.key.clone())).collect()
+            }
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ServerKeysFields {
+    server_name: OwnedServerName,
+    #[serde(default)]
+    verify_keys: BTreeMap<String, VerifyKey>,
+    #[serde(default)]
+    old_verify_keys: BTreeMap<String, OldVerifyKey>,
+    valid_until_ts: MilliSecondsSinceUnixEpoch,
+}
+
+#[derive(Deserialize)]
+struct VerifyKey {
+    key: Base64,
+}
+
+#[derive(Deserialize)]
+struct OldVerifyKey {
+    key: Base64,
+    expired_ts: MilliSecondsSinceUnixEpoch,
+}
+
+/// Checks that the keys of a homeserver are signed with all of its current keys.
+fn verify_self_signatures(
+    object: &CanonicalJsonObject,
+    fields: &ServerKeysFields,
+) -> Result<(), Error> {
+    if fields.verify_keys.is_empty() {
+        return Err(JsonError::field_missing_from_object("verify_keys"));
+    }
+
+    let signature_set = match object.get("signatures") {
+        Some(CanonicalJsonValue::Object(signatures)) => match signatures
+            .get(fields.server_name.as_str())
+        {
+            Some(CanonicalJsonValue::Object(set)) => set,
+            Some(_) => {
+                return Err(JsonError::not_multiples_of_type("signature sets", JsonType::Object))
+            }
+            None => return Err(VerificationError::signature_not_found(fields.server_name.clone())),
+        },
+        Some(_) => return Err(JsonError::not_of_type("signatures", JsonType::Object)),
+        None => return Err(JsonError::field_missing_from_object("signatures")),
+    };
+
+    for (key_id, VerifyKey { key }) in &fields.verify_keys {
+        split_id(key_id)?;
+
+        let signature = match signature_set.get(key_id) {
+            Some(CanonicalJsonValue::String(signature)) => signature,
+            Some(_) => return Err(JsonError::not_of_type("signature", JsonType::String)),
+            None => {
+                return Err(JsonError::key_missing(
+                    format!("signatures of {}", fields.server_name),
+                    "signature",
+                    key_id,
+                ))
+            }
+        };
+
+        let signature = Base64::<Standard>::parse(signature)
+            .map_err(|e| ParseError::base64("signature", signature, e))?;
+
+        verify_json_with(&Ed25519Verifier, key.as_bytes(), signature.as_bytes(), object)?;
+    }
+
+    Ok(())
+}
+
+/// Whether the room version requires the signing keys to be valid when the event was sent.
+fn enforces_key_validity(version: &RoomVersionId) -> bool {
+    !matches!(
+        version,
+        RoomVersionId::V1 | RoomVersionId::V2 | RoomVersionId::V3 | RoomVersionId::V4
+    )
+}
+
+/// Uses the keys of a [`KeyStore`] to check that an event is signed by the relevant homeservers.
+///
+/// This is the same as [`verify_event`], except that the public keys are fetched from the
+/// `key_store`. Starting with room version 5, only the keys that were valid at the
+/// `origin_server_ts` of the event are used.
+///
+/// # Errors
+///
+/// Returns an error if the event doesn't have a valid `origin_server_ts`, or if [`verify_event`]
+/// fails.
+pub fn verify_event_with_key_store<S: KeyStore + ?Sized>(
+    key_store: &S,
+    object: &CanonicalJsonObject,
+    version: &RoomVersionId,
+) -> Result<Verified, Error> {
+    let valid_at = if enforces_key_validity(version) {
+        match object.get("origin_server_ts") {
+            Some(CanonicalJsonValue::Integer(ts)) => {
+                let ts = UInt::try_from(i64::from(*ts))
+                    .map_err(|_| JsonError::not_of_type("origin_server_ts", JsonType::Integer))?;
+                Some(MilliSecondsSinceUnixEpoch(ts))
+            }
+            Some(_) => return Err(JsonError::not_of_type("origin_server_ts", JsonType::Integer)),
+            None => return Err(JsonError::field_missing_from_object("origin_server_ts")),
+        }
+    } else {
+        None
+    };
+
+    let public_key_map: PublicKeyMap = servers_to_check_signatures(object, version)?
+        .into_iter()
+        .filter_map(|server_name| {
+            let keys = key_store.public_keys(&server_name, valid_at)?;
+            Some((server_name.as_str().to_owned(), keys))
+        })
+        .collect();
+
+    verify_event(&public_key_map, object, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma_common::{
+        serde::{CanonicalJsonObject, Raw},
+        server_name, MilliSecondsSinceUnixEpoch, RoomVersionId,
+    };
+    use serde_json::{json, value::to_raw_value as to_raw_json_value};
+
+    use super::{verify_event_with_key_store, KeyStore, ServerKeyStore};
+    use crate::{sign_json, Ed25519KeyPair, Error, VerificationError};
+
+    fn ts(ms: u32) -> MilliSecondsSinceUnixEpoch {
+        MilliSecondsSinceUnixEpoch(ms.into())
+    }
+
+    fn key_id(key_pair: &Ed25519KeyPair) -> String {
+        key_pair.key_id().to_string()
+    }
+
+    /// Server keys of `domain`, signed with `current`.
+    fn server_keys(current: &Ed25519KeyPair, old: &Ed25519KeyPair) -> Raw<CanonicalJsonObject> {
+        let mut object: CanonicalJsonObject = serde_json::from_value(json!({
+            "server_name": "domain",
+            "verify_keys": {
+                key_id(current): { "key": current.public_key_base64() },
+            },
+            "old_verify_keys": {
+                key_id(old): { "key": old.public_key_base64(), "expired_ts": 1000 },
+            },
+            "valid_until_ts": 5000,
+        }))
+        .unwrap();
+        sign_json("domain", current, &mut object).unwrap();
+
+        Raw::from_json(to_raw_json_value(&object).unwrap())
+    }
+
+    fn signed_event(key_pair: &Ed25519KeyPair, origin_server_ts: u32) -> CanonicalJsonObject {
+        let mut object: CanonicalJsonObject = serde_json::from_value(json!({
+            "auth_events": [],
+            "content": {},
+            "depth": 3,
+            "hashes": {
+                "sha256": "5jM4wQpv6lnBo7CLIghJuHdW+s2CMBJPUOGOC89ncos"
+            },
+            "origin": "domain",
+            "origin_server_ts": origin_server_ts,
+            "prev_events": [],
+            "room_id": "!x:domain",
+            "sender": "@name:domain",
+            "type": "X",
+        }))
+        .unwrap();
+        sign_json("domain", key_pair, &mut object).unwrap();
+        object
+    }
+
+    #[test]
+    fn add_server_keys_with_validity() {
+        let current = Ed25519KeyPair::random("2".to_owned());
+        let old = Ed25519KeyPair::random("1".to_owned());
+
+        let mut key_store = ServerKeyStore::new();
+        let server = key_store.add_server_keys(&server_keys(&current, &old)).unwrap();
+        assert_eq!(server, "domain");
+
+        assert_eq!(key_store.valid_until_ts(&server, &key_id(&current)), Some(ts(5000)));
+        assert_eq!(key_store.valid_until_ts(&server, &key_id(&old)), Some(ts(1000)));
+
+        let keys = key_store.valid_keys(&server, ts(1000));
+        assert_eq!(keys.len(), 2);
+        let keys = key_store.valid_keys(&server, ts(2000));
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys.get(&key_id(&current)), Some(&current.public_key_base64()));
+        assert!(key_store.valid_keys(&server, ts(6000)).is_empty());
+
+        assert_eq!(key_store.public_keys(&server, None).unwrap().len(), 2);
+        assert!(key_store.public_keys(server_name!("other"), None).is_none());
+    }
+
+    #[test]
+    fn add_key_keeps_longest_validity() {
+        let key_pair = Ed25519KeyPair::random("1".to_owned());
+        let server = server_name!("domain");
+
+        let mut key_store = ServerKeyStore::new();
+        key_store.add_key(
+            server.to_owned(),
+            key_id(&key_pair),
+            key_pair.public_key_base64(),
+            ts(5000),
+        );
+        key_store.add_key(
+            server.to_owned(),
+            key_id(&key_pair),
+            key_pair.public_key_base64(),
+            ts(1000),
+        );
+
+        assert_eq!(key_store.valid_until_ts(server, &key_id(&key_pair)), Some(ts(5000)));
+    }
+
+    #[test]
+    fn add_server_keys_rejects_invalid_signature() {
+        let current = Ed25519KeyPair::random("2".to_owned());
+        let other = Ed25519KeyPair::random("2".to_owned());
+        let old = Ed25519KeyPair::random("1".to_owned());
+
+        let mut object: CanonicalJsonObject =
+            serde_json::from_str(server_keys(&current, &old).json().get()).unwrap();
+        object.remove("signatures");
+        sign_json("domain", &other, &mut object).unwrap();
+        let raw: Raw<CanonicalJsonObject> = Raw::from_json(to_raw_json_value(&object).unwrap());
+
+        let mut key_store = ServerKeyStore::new();
+        assert!(matches!(
+            key_store.add_server_keys(&raw),
+            Err(Error::Verification(VerificationError::Signature(_)))
+        ));
+        assert!(key_store.public_keys(server_name!("domain"), None).is_none());
+    }
+
+    #[test]
+    fn verify_event_enforces_key_validity() {
+        let current = Ed25519KeyPair::random("2".to_owned());
+        let old = Ed25519KeyPair::random("1".to_owned());
+
+        let mut key_store = ServerKeyStore::new();
+        key_store.add_server_keys(&server_keys(&current, &old)).unwrap();
+
+        let event = signed_event(&old, 500);
+        verify_event_with_key_store(&key_store, &event, &RoomVersionId::V6).unwrap();
+
+        let event = signed_event(&old, 2000);
+        assert!(matches!(
+            verify_event_with_key_store(&key_store, &event, &RoomVersionId::V6),
+            Err(Error::Verification(VerificationError::UnknownPublicKeysForSignature))
+        ));
+        verify_event_with_key_store(&key_store, &event, &RoomVersionId::V4).unwrap();
+
+        let event = signed_event(&current, 2000);
+        verify_event_with_key_store(&key_store, &event, &RoomVersionId::V6).unwrap();
+    }
+
+    #[test]
+    fn public_key_map_as_key_store() {
+        let key_pair = Ed25519KeyPair::random("1".to_owned());
+        let mut public_key_map = crate::PublicKeyMap::new();
+        public_key_map.insert(
+            "domain".to_owned(),
+            vec![(key_id(&key_pair), key_pair.public_key_base64())].into_iter().collect(),
+        );
+
+        let event = signed_event(&key_pair, 2000);
+        verify_event_with_key_store(&public_key_map, &event, &RoomVersionId::V6).unwrap();
+    }
+}
@@ -41,25 +41,47 @@
 //! To verify a signature on arbitrary JSON, use the `verify_json` function. To verify the
 //! signatures and hashes on an event, use the `verify_event` function. See the documentation for
 //! these respective functions for more details and full examples of use.
+//!
+//! # Federation requests
+//!
+//! Requests between homeservers are authenticated with signatures in an `Authorization` header
+//! using the `X-Matrix` scheme. To sign an outgoing request, use the `sign_request` function or
+//! the `OutgoingRequestFederationExt` trait. To verify the signature of an incoming request, use
+//! the `verify_request` function.
 
 #![warn(missing_docs)]
 
 use ruma_common::serde::{AsRefStr, DisplayAsRefStr};
 
+pub use batch::{verify_events_batch, verify_json_batch};
 pub use error::{Error, JsonError, JsonType, ParseError, VerificationError};
 pub use functions::{
-    canonical_json, content_hash, hash_and_sign_event, redact, redact_content_in_place,
-    redact_in_place, reference_hash, sign_json, verify_event, verify_json,
+    canonical_json, content_hash, hash_and_sign_event, hash_and_sign_event_async, redact,
+    redact_content_in_place, redact_in_place, reference_hash, sign_json, sign_json_async,
+    verify_event, verify_json,
 };
+pub use key_store::{verify_event_with_key_store, KeyStore, ServerKeyStore};
 pub use keys::{Ed25519KeyPair, KeyPair, PublicKeyMap, PublicKeySet};
+pub use request::{
+    sign_request, sign_request_async, verify_request, OutgoingRequestFederationExt, XMatrix,
+};
 pub use ruma_common::serde::{CanonicalJsonError, CanonicalJsonObject, CanonicalJsonValue};
 pub use signatures::Signature;
+pub use signer::{RecordingSigner, Signer};
+pub use third_party_invite::{
+    generate_third_party_invite_key_pair, sign_third_party_invite, verify_third_party_invite,
+};
 pub use verification::Verified;
 
+mod batch;
 mod error;
 mod functions;
+mod key_store;
 mod keys;
+mod request;
 mod signatures;
+mod signer;
+mod third_party_invite;
 mod verification;
 
 /// The algorithm used for signing data.
@@ -0,0 +1,547 @@
+//! Signing and verification of federation requests with the `X-Matrix` authorization scheme.
+
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use bytes::BufMut;
+use http::header::{HeaderValue, AUTHORIZATION};
+use ruma_common::{
+    api::{MatrixVersion, OutgoingRequest, SendAccessToken},
+    serde::{Base64, CanonicalJsonObject, CanonicalJsonValue},
+    OwnedServerName, ServerName,
+};
+use serde_json::from_slice as from_json_slice;
+
+use crate::{
+    keys::{KeyPair, PublicKeyMap},
+    sign_json, sign_json_async,
+    signer::Signer,
+    verify_json, Error, JsonError, ParseError, VerificationError,
+};
+
+/// The content of an `Authorization` header using the `X-Matrix` scheme, as defined in the
+/// [server-server specification][spec].
+///
+/// Its `Display` and `FromStr` implementations produce and parse the full header value, including
+/// the leading `X-Matrix` scheme.
+///
+/// [spec]: https://spec.matrix.org/v1.2/server-server-api/#request-authentication
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::exhaustive_structs)]
+pub struct XMatrix {
+    /// The server name of the sending server.
+    pub origin: OwnedServerName,
+
+    /// The server name of the receiving server.
+    ///
+    /// Older servers don't send this parameter.
+    pub destination: Option<OwnedServerName>,
+
+    /// The ID of the key used for the signature, e.g. `ed25519:1`.
+    pub key: String,
+
+    /// The signature of the request.
+    pub sig: Base64,
+}
+
+impl XMatrix {
+    /// Parses the value of an `Authorization` header.
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        value.parse()
+    }
+
+    /// Converts `self` to an `Authorization` header value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key ID contains characters that are not allowed in a header value.
+    pub fn to_header_value(&self) -> Result<HeaderValue, Error> {
+        Ok(HeaderValue::from_str(&self.to_string())?)
+    }
+}
+
+impl Display for XMatrix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("X-Matrix ")?;
+        write_param(f, "origin", self.origin.as_str())?;
+        if let Some(destination) = &self.destination {
+            f.write_str(",")?;
+            write_param(f, "destination", destination.as_str())?;
+        }
+        f.write_str(",")?;
+        write_param(f, "key", &self.key)?;
+        f.write_str(",")?;
+        write_param(f, "sig", &self.sig.encode())
+    }
+}
+
+/// Writes a `name="value"` parameter of an `X-Matrix` header, escaping `"` and `\` in the value.
+fn write_param(f: &mut Formatter<'_>, name: &str, value: &str) -> fmt::Result {
+    write!(f, "{}=\"", name)?;
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
+}
+
+impl FromStr for XMatrix {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let params = s
+            .strip_prefix("X-Matrix ")
+            .ok_or_else(|| ParseError::x_matrix("missing `X-Matrix` scheme"))?;
+
+        let mut origin = None;
+        let mut destination = None;
+        let mut key = None;
+        let mut sig = None;
+
+        for (name, value) in parse_params(params)? {
+            match name {
+                "origin" => {
+                    origin = Some(
+                        OwnedServerName::try_from(value)
+                            .map_err(|_| ParseError::x_matrix("invalid `origin`"))?,
+                    );
+                }
+                "destination" => {
+                    destination = Some(
+                        OwnedServerName::try_from(value)
+                            .map_err(|_| ParseError::x_matrix("invalid `destination`"))?,
+                    );
+                }
+                "key" => key = Some(value),
+                "sig" => {
+                    sig = Some(
+                        Base64::parse(&value)
+                            .map_err(|e| ParseError::base64("signature", value, e))?,
+                    );
+                }
+                // Unknown parameters are ignored.
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            origin: origin.ok_or_else(|| ParseError::x_matrix("missing `origin`"))?,
+            destination,
+            key: key.ok_or_else(|| ParseError::x_matrix("missing `key`"))?,
+            sig: sig.ok_or_else(|| ParseError::x_matrix("missing `sig`"))?,
+        })
+    }
+}
+
+/// Splits the comma-separated `name=value` parameters of an `X-Matrix` header.
+///
+/// Values may be quoted, in which case backslash escapes are resolved.
+fn parse_params(params: &str) -> Result<Vec<(&str, String)>, Error> {
+    let mut result = Vec::new();
+    let mut rest = params.trim_start();
+
+    while !rest.is_empty() {
+        let (name, after_name) =
+            rest.split_once('=').ok_or_else(|| ParseError::x_matrix("parameter without value"))?;
+        let name = name.trim();
+
+        let (value, after_value) = if let Some(quoted) = after_name.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ParseError::x_matrix("unterminated escape")),
+                    },
+                    Some((i, '"')) => break i,
+                    Some((_, c)) => value.push(c),
+                    None => return Err(ParseError::x_matrix("unterminated quoted value")),
+                }
+            };
+            (value, &quoted[end + 1..])
+        } else {
+            let end = after_name.find(',').unwrap_or(after_name.len());
+            (after_name[..end].trim_end().to_owned(), &after_name[end..])
+        };
+
+        result.push((name, value));
+
+        let after_value = after_value.trim_start();
+        rest = match after_value.strip_prefix(',') {
+            Some(next) => next.trim_start(),
+            None if after_value.is_empty() => after_value,
+            None => return Err(ParseError::x_matrix("expected `,` between parameters")),
+        };
+    }
+
+    Ok(result)
+}
+
+/// Builds the JSON object that is signed for a federation request.
+fn request_json<T: AsRef<[u8]>>(
+    request: &http::Request<T>,
+    origin: &ServerName,
+    destination: &ServerName,
+) -> Result<CanonicalJsonObject, Error> {
+    let uri = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path(), |path_and_query| path_and_query.as_str());
+
+    let mut object = BTreeMap::new();
+    object.insert("method".to_owned(), CanonicalJsonValue::String(request.method().to_string()));
+    object.insert("uri".to_owned(), CanonicalJsonValue::String(uri.to_owned()));
+    object.insert("origin".to_owned(), CanonicalJsonValue::String(origin.to_string()));
+    object.insert("destination".to_owned(), CanonicalJsonValue::String(destination.to_string()));
+
+    let body = request.body().as_ref();
+    if !body.is_empty() {
+        let content: CanonicalJsonValue = from_json_slice(body).map_err(JsonError::Serde)?;
+        object.insert("content".to_owned(), content);
+    }
+
+    Ok(object)
+}
+
+/// Signs a federation request and adds the corresponding `X-Matrix` `Authorization` header to it.
+///
+/// The request body, if any, must be JSON.
+///
+/// # Parameters
+///
+/// * origin: The server name of the sending server.
+/// * destination: The server name of the receiving server.
+/// * key_pair: The key pair of the sending server, used to sign the request.
+/// * request: The request to sign.
+///
+/// # Errors
+///
+/// Returns an error if the request body is not valid canonical JSON.
+pub fn sign_request<K, T>(
+    origin: &ServerName,
+    destination: &ServerName,
+    key_pair: &K,
+    request: &mut http::Request<T>,
+) -> Result<(), Error>
+where
+    K: KeyPair,
+    T: AsRef<[u8]>,
+{
+    let mut object = request_json(request, origin, destination)?;
+    sign_json(origin.as_str(), key_pair, &mut object)?;
+
+    add_x_matrix_header(&object, origin, destination, request)
+}
+
+/// Signs a federation request with a [`Signer`] and adds the corresponding `X-Matrix`
+/// `Authorization` header to it.
+///
+/// This is the same as [`sign_request`], except that the request is signed asynchronously, for
+/// example by a signing service running in another process.
+///
+/// # Errors
+///
+/// Returns an error if the request body is not valid canonical JSON, or if the signer fails to
+/// sign the request.
+pub async fn sign_request_async<S, T>(
+    origin: &ServerName,
+    destination: &ServerName,
+    signer: &S,
+    request: &mut http::Request<T>,
+) -> Result<(), Error>
+where
+    S: Signer + ?Sized,
+    T: AsRef<[u8]>,
+{
+    let mut object = request_json(request, origin, destination)?;
+    sign_json_async(origin.as_str(), signer, &mut object).await?;
+
+    add_x_matrix_header(&object, origin, destination, request)
+}
+
+/// Adds the `X-Matrix` header with the signature of `origin` in the signed JSON of a request.
+fn add_x_matrix_header<T>(
+    object: &CanonicalJsonObject,
+    origin: &ServerName,
+    destination: &ServerName,
+    request: &mut http::Request<T>,
+) -> Result<(), Error> {
+    // `sign_json` inserted exactly one signature for `origin`.
+    let (key, sig) = match &object["signatures"] {
+        CanonicalJsonValue::Object(signatures) => match &signatures[origin.as_str()] {
+            CanonicalJsonValue::Object(set) => {
+                set.iter().next().expect("signature set of origin must not be empty")
+            }
+            _ => unreachable!("signature set was created by sign_json"),
+        },
+        _ => unreachable!("signatures were created by sign_json"),
+    };
+    let sig = match sig {
+        CanonicalJsonValue::String(sig) => {
+            Base64::parse(sig).map_err(|e| ParseError::base64("signature", sig, e))?
+        }
+        _ => unreachable!("signature was created by sign_json"),
+    };
+
+    let x_matrix = XMatrix {
+        origin: origin.to_owned(),
+        destination: Some(destination.to_owned()),
+        key: key.clone(),
+        sig,
+    };
+    request.headers_mut().append(AUTHORIZATION, x_matrix.to_header_value()?);
+
+    Ok(())
+}
+
+/// Verifies the `X-Matrix` `Authorization` headers of an incoming federation request.
+///
+/// Every `X-Matrix` header of the request must contain a valid signature from a key in
+/// `public_key_map`, and all of them must have the same origin. Headers using other
+/// authorization schemes are ignored.
+///
+/// # Parameters
+///
+/// * public_key_map: The public keys of the origin server, keyed by server name and key ID.
+/// * destination: The server name of the receiving server, i.e. the server calling this function.
+/// * request: The request to verify.
+///
+/// # Errors
+///
+/// Returns an error if the request has no `X-Matrix` header, if a header can't be parsed, is
+/// meant for another destination or has an invalid signature.
+///
+/// On success, returns the server name of the origin of the request.
+pub fn verify_request<T: AsRef<[u8]>>(
+    public_key_map: &PublicKeyMap,
+    destination: &ServerName,
+    request: &http::Request<T>,
+) -> Result<OwnedServerName, Error> {
+    let mut origin: Option<OwnedServerName> = None;
+
+    for value in request.headers().get_all(AUTHORIZATION) {
+        let value = match value.to_str() {
+            Ok(value) if value.starts_with("X-Matrix ") => value,
+            _ => continue,
+        };
+        let x_matrix = XMatrix::parse(value)?;
+
+        if let Some(header_destination) = &x_matrix.destination {
+            if header_destination != destination {
+                return Err(
+                    VerificationError::DestinationMismatch(header_destination.clone()).into()
+                );
+            }
+        }
+
+        match &origin {
+            Some(origin) if *origin != x_matrix.origin => {
+                return Err(VerificationError::OriginMismatch(x_matrix.origin).into());
+            }
+            _ => {}
+        }
+
+        let mut object = request_json(request, &x_matrix.origin, destination)?;
+
+        let mut signature_set = BTreeMap::new();
+        signature_set.insert(x_matrix.key, CanonicalJsonValue::String(x_matrix.sig.encode()));
+        let mut signatures = BTreeMap::new();
+        signatures.insert(x_matrix.origin.to_string(), CanonicalJsonValue::Object(signature_set));
+        object.insert("signatures".to_owned(), CanonicalJsonValue::Object(signatures));
+
+        verify_json(public_key_map, &object)?;
+
+        origin = Some(x_matrix.origin);
+    }
+
+    origin.ok_or_else(|| VerificationError::AuthorizationNotFound.into())
+}
+
+/// An extension to [`OutgoingRequest`] which provides methods for federation requests.
+pub trait OutgoingRequestFederationExt: OutgoingRequest {
+    /// Tries to convert this request into an `http::Request` and signs it with the `X-Matrix`
+    /// authorization scheme.
+    ///
+    /// See [`OutgoingRequest::try_into_http_request`] and [`sign_request`] for details.
+    fn try_into_signed_http_request<K, T>(
+        self,
+        base_url: &str,
+        origin: &ServerName,
+        destination: &ServerName,
+        key_pair: &K,
+        considering_versions: &'_ [MatrixVersion],
+    ) -> Result<http::Request<T>, Error>
+    where
+        K: KeyPair,
+        T: Default + BufMut + AsRef<[u8]>,
+    {
+        let mut request =
+            self.try_into_http_request(base_url, SendAccessToken::None, considering_versions)?;
+        sign_request(origin, destination, key_pair, &mut request)?;
+
+        Ok(request)
+    }
+}
+
+impl<T: OutgoingRequest> OutgoingRequestFederationExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use http::header::AUTHORIZATION;
+    use ruma_common::{serde::Base64, server_name};
+
+    use super::{sign_request, verify_request, XMatrix};
+    use crate::{Ed25519KeyPair, Error, VerificationError};
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::from_der(&Ed25519KeyPair::generate().unwrap(), "1".to_owned()).unwrap()
+    }
+
+    fn public_key_map(key_pair: &Ed25519KeyPair) -> crate::PublicKeyMap {
+        let mut public_key_set = BTreeMap::new();
+        public_key_set
+            .insert("ed25519:1".to_owned(), Base64::new(key_pair.public_key().to_owned()));
+        let mut public_key_map = BTreeMap::new();
+        public_key_map.insert("origin.hs.example.com".to_owned(), public_key_set);
+        public_key_map
+    }
+
+    #[test]
+    fn parse_x_matrix() {
+        let header = "X-Matrix origin=\"origin.hs.example.com\",\
+                      destination=\"destination.hs.example.com\",\
+                      key=\"ed25519:key1\",sig=\"ABCDEFGH\"";
+        let x_matrix = XMatrix::parse(header).unwrap();
+
+        assert_eq!(x_matrix.origin, "origin.hs.example.com");
+        assert_eq!(x_matrix.destination.as_deref().unwrap(), "destination.hs.example.com");
+        assert_eq!(x_matrix.key, "ed25519:key1");
+        assert_eq!(x_matrix.sig.encode(), "ABCDEFGH");
+        assert_eq!(x_matrix.to_string(), header);
+    }
+
+    #[test]
+    fn parse_x_matrix_unquoted() {
+        let x_matrix =
+            XMatrix::parse("X-Matrix origin=origin.hs.example.com,key=\"ed25519:key1\",sig=ABCDEF")
+                .unwrap();
+
+        assert_eq!(x_matrix.origin, "origin.hs.example.com");
+        assert_eq!(x_matrix.destination, None);
+        assert_eq!(x_matrix.key, "ed25519:key1");
+    }
+
+    #[test]
+    fn x_matrix_escaped_round_trip() {
+        let header =
+            r#"X-Matrix origin="origin.hs.example.com",key="ed25519:\"key\\1",sig="ABCDEFGH""#;
+        let x_matrix = XMatrix::parse(header).unwrap();
+
+        assert_eq!(x_matrix.key, r#"ed25519:"key\1"#);
+        assert_eq!(x_matrix.to_string(), header);
+        assert_eq!(XMatrix::parse(&x_matrix.to_string()).unwrap(), x_matrix);
+    }
+
+    #[test]
+    fn x_matrix_invalid_header_value() {
+        let x_matrix = XMatrix {
+            origin: server_name!("origin.hs.example.com").to_owned(),
+            destination: None,
+            key: "ed25519:key\n1".to_owned(),
+            sig: Base64::parse("ABCDEFGH").unwrap(),
+        };
+
+        assert!(matches!(x_matrix.to_header_value(), Err(Error::HeaderValue(_))));
+    }
+
+    #[test]
+    fn parse_x_matrix_invalid() {
+        XMatrix::parse("Bearer abc").unwrap_err();
+        XMatrix::parse("X-Matrix origin=\"origin.hs.example.com\",key=\"ed25519:key1\"")
+            .unwrap_err();
+        XMatrix::parse("X-Matrix origin=\"origin.hs.example.com").unwrap_err();
+    }
+
+    #[test]
+    fn sign_and_verify_request() {
+        let key_pair = key_pair();
+        let origin = server_name!("origin.hs.example.com");
+        let destination = server_name!("destination.hs.example.com");
+
+        let mut request = http::Request::put("/_matrix/federation/v1/send/1234?foo=bar")
+            .body(br#"{"pdus":[],"edus":[]}"#.to_vec())
+            .unwrap();
+        sign_request(origin, destination, &key_pair, &mut request).unwrap();
+
+        let x_matrix = XMatrix::parse(request.headers()[AUTHORIZATION].to_str().unwrap()).unwrap();
+        assert_eq!(x_matrix.origin, origin);
+        assert_eq!(x_matrix.destination.as_deref(), Some(destination));
+        assert_eq!(x_matrix.key, "ed25519:1");
+
+        let verified = verify_request(&public_key_map(&key_pair), destination, &request).unwrap();
+        assert_eq!(verified, origin);
+    }
+
+    #[test]
+    fn verify_request_without_body() {
+        let key_pair = key_pair();
+        let origin = server_name!("origin.hs.example.com");
+        let destination = server_name!("destination.hs.example.com");
+
+        let mut request = http::Request::get("/_matrix/federation/v1/version").body(b"").unwrap();
+        sign_request(origin, destination, &key_pair, &mut request).unwrap();
+
+        verify_request(&public_key_map(&key_pair), destination, &request).unwrap();
+    }
+
+    #[test]
+    fn verify_tampered_request() {
+        let key_pair = key_pair();
+        let origin = server_name!("origin.hs.example.com");
+        let destination = server_name!("destination.hs.example.com");
+
+        let mut request =
+            http::Request::put("/_matrix/federation/v1/send/1234").body(b"{}".to_vec()).unwrap();
+        sign_request(origin, destination, &key_pair, &mut request).unwrap();
+        *request.body_mut() = br#"{"pdus":[]}"#.to_vec();
+
+        verify_request(&public_key_map(&key_pair), destination, &request).unwrap_err();
+    }
+
+    #[test]
+    fn verify_request_wrong_destination() {
+        let key_pair = key_pair();
+        let origin = server_name!("origin.hs.example.com");
+
+        let mut request = http::Request::get("/_matrix/federation/v1/version").body(b"").unwrap();
+        sign_request(origin, server_name!("destination.hs.example.com"), &key_pair, &mut request)
+            .unwrap();
+
+        let err =
+            verify_request(&public_key_map(&key_pair), server_name!("other.example.com"), &request)
+                .unwrap_err();
+        assert!(matches!(err, Error::Verification(VerificationError::DestinationMismatch(_))));
+    }
+
+    #[test]
+    fn verify_request_without_authorization() {
+        let request = http::Request::get("/_matrix/federation/v1/version").body(b"").unwrap();
+
+        let err = verify_request(
+            &public_key_map(&key_pair()),
+            server_name!("destination.hs.example.com"),
+            &request,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Verification(VerificationError::AuthorizationNotFound)));
+    }
+}
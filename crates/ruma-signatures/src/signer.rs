@@ -0,0 +1,194 @@
+//! Asynchronous signing of JSON objects.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::{keys::KeyPair, signatures::Signature, Ed25519KeyPair, Error};
+
+/// An asynchronous signer of messages.
+///
+/// Unlike [`KeyPair`], the private key doesn't need to be in the memory of the process: the
+/// signer can send the messages to another process or to a hardware security module, for example.
+///
+/// It can be used with [`sign_json_async`](crate::sign_json_async),
+/// [`hash_and_sign_event_async`](crate::hash_and_sign_event_async) and
+/// [`sign_request_async`](crate::sign_request_async).
+#[async_trait]
+pub trait Signer: Sync {
+    /// Signs a message.
+    ///
+    /// # Parameters
+    ///
+    /// * message: An arbitrary series of bytes to sign.
+    ///
+    /// # Errors
+    ///
+    /// Implementations should return [`Error::Signer`] if the message can't be signed.
+    async fn sign(&self, message: &[u8]) -> Result<Signature, Error>;
+}
+
+/// Signs the messages in memory.
+#[async_trait]
+impl Signer for Ed25519KeyPair {
+    async fn sign(&self, message: &[u8]) -> Result<Signature, Error> {
+        Ok(KeyPair::sign(self, message))
+    }
+}
+
+/// A [`Signer`] that records all the messages it signs, for tests.
+///
+/// The messages are signed by the wrapped signer.
+#[derive(Debug, Default)]
+pub struct RecordingSigner<S> {
+    inner: S,
+    messages: Mutex<Vec<Vec<u8>>>,
+}
+
+impl<S> RecordingSigner<S> {
+    /// Creates a new `RecordingSigner` wrapping the given signer.
+    pub fn new(inner: S) -> Self {
+        Self { inner, messages: Mutex::new(Vec::new()) }
+    }
+
+    /// Get a reference to the wrapped signer.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the messages that were sent to this signer, in order.
+    pub fn messages(&self) -> Vec<Vec<u8>> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl<S: Signer + Send> Signer for RecordingSigner<S> {
+    async fn sign(&self, message: &[u8]) -> Result<Signature, Error> {
+        self.messages.lock().unwrap().push(message.to_owned());
+        self.inner.sign(message).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error as StdError, fmt};
+
+    use async_trait::async_trait;
+    use futures_executor::block_on;
+    use ruma_common::{serde::CanonicalJsonObject, server_name, RoomVersionId};
+    use serde_json::json;
+
+    use super::{RecordingSigner, Signer};
+    use crate::{
+        canonical_json, hash_and_sign_event, hash_and_sign_event_async, sign_json, sign_json_async,
+        sign_request_async, verify_request, Ed25519KeyPair, Error, PublicKeyMap, Signature,
+    };
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::random("1".to_owned())
+    }
+
+    fn object() -> CanonicalJsonObject {
+        serde_json::from_value(json!({
+            "content": { "body": "Hello" },
+            "room_id": "!x:domain",
+            "sender": "@name:domain",
+            "type": "m.room.message",
+            "unsigned": { "age": 1 },
+        }))
+        .unwrap()
+    }
+
+    #[derive(Debug)]
+    struct Unreachable;
+
+    impl fmt::Display for Unreachable {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("signing process is unreachable")
+        }
+    }
+
+    impl StdError for Unreachable {}
+
+    struct FailingSigner;
+
+    #[async_trait]
+    impl Signer for FailingSigner {
+        async fn sign(&self, _message: &[u8]) -> Result<Signature, Error> {
+            Err(Error::Signer(Box::new(Unreachable)))
+        }
+    }
+
+    #[test]
+    fn sign_json_async_matches_sign_json() {
+        let signer = RecordingSigner::new(key_pair());
+
+        let mut sync_object = object();
+        sign_json("domain", signer.inner(), &mut sync_object).unwrap();
+
+        let mut async_object = object();
+        block_on(sign_json_async("domain", &signer, &mut async_object)).unwrap();
+
+        assert_eq!(async_object, sync_object);
+        assert_eq!(signer.messages(), [canonical_json(&object()).unwrap().into_bytes()]);
+    }
+
+    #[test]
+    fn hash_and_sign_event_async_matches_hash_and_sign_event() {
+        let signer = RecordingSigner::new(key_pair());
+
+        let mut sync_object = object();
+        hash_and_sign_event("domain", signer.inner(), &mut sync_object, &RoomVersionId::V9)
+            .unwrap();
+
+        let mut async_object = object();
+        block_on(hash_and_sign_event_async(
+            "domain",
+            &signer,
+            &mut async_object,
+            &RoomVersionId::V9,
+        ))
+        .unwrap();
+
+        assert_eq!(async_object, sync_object);
+        assert_eq!(signer.messages().len(), 1);
+    }
+
+    #[test]
+    fn sign_request_async_is_verified() {
+        let signer = RecordingSigner::new(key_pair());
+        let origin = server_name!("origin.local");
+        let destination = server_name!("destination.local");
+
+        let mut request = http::Request::builder()
+            .method("PUT")
+            .uri("/_matrix/federation/v1/send/1")
+            .body(br#"{"pdus":[]}"#.to_vec())
+            .unwrap();
+        block_on(sign_request_async(origin, destination, &signer, &mut request)).unwrap();
+
+        let mut public_key_map = PublicKeyMap::new();
+        public_key_map.insert(
+            origin.to_string(),
+            vec![(signer.inner().key_id().to_string(), signer.inner().public_key_base64())]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(verify_request(&public_key_map, destination, &request).unwrap(), origin);
+        assert_eq!(signer.messages().len(), 1);
+    }
+
+    #[test]
+    fn signer_error() {
+        let signer = RecordingSigner::new(FailingSigner);
+
+        let mut object = object();
+        let error =
+            block_on(sign_json_async("domain", &signer as &dyn Signer, &mut object)).unwrap_err();
+
+        assert!(matches!(error, Error::Signer(_)));
+        assert!(!object.contains_key("signatures"));
+        assert_eq!(signer.messages().len(), 1);
+    }
+}
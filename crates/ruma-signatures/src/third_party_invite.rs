@@ -0,0 +1,172 @@
+//! Signing and verification of third-party invites.
+
+use std::collections::BTreeMap;
+
+use ruma_common::{
+    serde::{base64::Standard, Base64, CanonicalJsonObject, CanonicalJsonValue},
+    UserId,
+};
+
+use crate::{
+    functions::canonical_json,
+    keys::{Ed25519KeyPair, KeyPair},
+    sign_json, split_id,
+    verification::{Ed25519Verifier, Verifier},
+    Error, JsonError, JsonType, ParseError, VerificationError,
+};
+
+/// The version of the ephemeral keys of third-party invites.
+const EPHEMERAL_KEY_VERSION: &str = "0";
+
+/// Generates a new ephemeral key pair for a third-party invite.
+///
+/// An identity server stores an invitation with a new ephemeral key pair, and publishes its public
+/// key, for it to be added to the `public_keys` of the `m.room.third_party_invite` event. When the
+/// 3PID is bound to a Matrix user, the invitation is signed with this key pair.
+///
+/// The seed of the key pair, returned by [`Ed25519KeyPair::seed()`], is the `private_key` sent to
+/// the `sign_invitation_ed25519` endpoint of the identity service API.
+pub fn generate_third_party_invite_key_pair() -> Ed25519KeyPair {
+    Ed25519KeyPair::random(EPHEMERAL_KEY_VERSION.to_owned())
+}
+
+/// Creates the `signed` object of the `third_party_invite` of an `m.room.member` event.
+///
+/// # Parameters
+///
+/// * entity_id: The identifier of the entity creating the signature, i.e. the server name of the
+///   identity server.
+/// * key_pair: The key pair of the invitation, usually an ephemeral key pair generated with
+///   [`generate_third_party_invite_key_pair`].
+/// * mxid: The Matrix user ID of the invitee.
+/// * sender: The Matrix user ID of the user who sent the invitation.
+/// * token: The token of the invitation, i.e. the state key of the `m.room.third_party_invite`
+///   event.
+///
+/// # Errors
+///
+/// Returns an error if signing fails.
+pub fn sign_third_party_invite<K: KeyPair>(
+    entity_id: &str,
+    key_pair: &K,
+    mxid: &UserId,
+    sender: &UserId,
+    token: &str,
+) -> Result<CanonicalJsonObject, Error> {
+    let mut signed = BTreeMap::new();
+    signed.insert("mxid".to_owned(), CanonicalJsonValue::String(mxid.to_string()));
+    signed.insert("sender".to_owned(), CanonicalJsonValue::String(sender.to_string()));
+    signed.insert("token".to_owned(), CanonicalJsonValue::String(token.to_owned()));
+
+    sign_json(entity_id, key_pair, &mut signed)?;
+
+    Ok(signed)
+}
+
+/// Verifies the `signed` object of the `third_party_invite` of an `m.room.member` event.
+///
+/// The invite is valid if any signature in `signed` is valid for any of the `public_keys`, which
+/// are the `public_key` and `public_keys` of the matching `m.room.third_party_invite` event.
+///
+/// This only checks the signatures. The `mxid` and `token` of `signed` must also match the state
+/// key of the `m.room.member` event and of the `m.room.third_party_invite` event, respectively.
+///
+/// # Errors
+///
+/// Returns an error if `signed` is invalid, or if none of its signatures matches a public key.
+pub fn verify_third_party_invite<'a, I>(
+    signed: &CanonicalJsonObject,
+    public_keys: I,
+) -> Result<(), Error>
+where
+    I: IntoIterator<Item = &'a Base64>,
+{
+    let signature_map = match signed.get("signatures") {
+        Some(CanonicalJsonValue::Object(signatures)) => signatures,
+        Some(_) => return Err(JsonError::not_of_type("signatures", JsonType::Object)),
+        None => return Err(JsonError::field_missing_from_object("signatures")),
+    };
+
+    let mut signatures = Vec::new();
+
+    for signature_set in signature_map.values() {
+        let signature_set = match signature_set {
+            CanonicalJsonValue::Object(set) => set,
+            _ => return Err(JsonError::not_multiples_of_type("signature sets", JsonType::Object)),
+        };
+
+        for (key_id, signature) in signature_set {
+            // Since only ed25519 is supported right now, skip the signatures with other
+            // algorithms.
+            if split_id(key_id).is_err() {
+                continue;
+            }
+
+            let signature = match signature {
+                CanonicalJsonValue::String(signature) => signature,
+                _ => return Err(JsonError::not_of_type("signature", JsonType::String)),
+            };
+
+            let signature = Base64::<Standard>::parse(signature)
+                .map_err(|e| ParseError::base64("signature", signature, e))?;
+            signatures.push(signature);
+        }
+    }
+
+    let message = canonical_json(signed)?;
+
+    for public_key in public_keys {
+        for signature in &signatures {
+            if Ed25519Verifier
+                .verify_json(public_key.as_bytes(), signature.as_bytes(), message.as_bytes())
+                .is_ok()
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(VerificationError::UnknownPublicKeysForSignature.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma_common::{serde::CanonicalJsonValue, user_id};
+
+    use super::{
+        generate_third_party_invite_key_pair, sign_third_party_invite, verify_third_party_invite,
+    };
+    use crate::{Error, VerificationError};
+
+    #[test]
+    fn sign_and_verify_third_party_invite() {
+        let key_pair = generate_third_party_invite_key_pair();
+        let other_key_pair = generate_third_party_invite_key_pair();
+
+        let signed = sign_third_party_invite(
+            "identity.local",
+            &key_pair,
+            user_id!("@alice:example.org"),
+            user_id!("@bob:example.org"),
+            "abc123",
+        )
+        .unwrap();
+
+        assert_eq!(signed["mxid"], CanonicalJsonValue::String("@alice:example.org".to_owned()));
+        assert_eq!(signed["token"], CanonicalJsonValue::String("abc123".to_owned()));
+
+        let public_key = key_pair.public_key_base64();
+        let other_public_key = other_key_pair.public_key_base64();
+
+        verify_third_party_invite(&signed, vec![&other_public_key, &public_key]).unwrap();
+        assert!(matches!(
+            verify_third_party_invite(&signed, vec![&other_public_key]),
+            Err(Error::Verification(VerificationError::UnknownPublicKeysForSignature))
+        ));
+
+        let mut tampered = signed;
+        tampered
+            .insert("mxid".to_owned(), CanonicalJsonValue::String("@eve:example.org".to_owned()));
+        assert!(verify_third_party_invite(&tampered, vec![&public_key]).is_err());
+    }
+}
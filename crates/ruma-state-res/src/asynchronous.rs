@@ -0,0 +1,272 @@
+//! Versions of the state resolution functions that fetch events asynchronously.
+//!
+//! The synchronous algorithms need to look up events over and over again. Instead of making all of
+//! them async, the functions in this module collect the IDs of the events that the algorithm could
+//! need up front, fetch them all concurrently and then run the synchronous algorithm on the
+//! fetched events.
+
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    future::Future,
+    hash::Hash,
+};
+
+use futures_util::future::{join_all, try_join_all};
+use js_int::Int;
+use ruma_common::{events::StateEventType, EventId, MilliSecondsSinceUnixEpoch, RoomVersionId};
+use tracing::info;
+
+use crate::{
+    auth_check, auth_types_for_event, lexicographical_topological_sort, resolve, separate, Event,
+    EventTypeExt, Result, RoomVersion, StateMap,
+};
+
+/// The types of state events that are used to authorize other events.
+const AUTH_EVENT_TYPES: &[StateEventType] = &[
+    StateEventType::RoomCreate,
+    StateEventType::RoomPowerLevels,
+    StateEventType::RoomJoinRules,
+    StateEventType::RoomMember,
+    StateEventType::RoomThirdPartyInvite,
+];
+
+/// Resolve sets of state events as they come in, fetching events asynchronously.
+///
+/// This is the same as [`resolve`], except that `fetch_event` returns a future.
+///
+/// Before running the state resolution algorithm, all the events it could need are fetched
+/// concurrently: the conflicted events, the state events used for authorization and the full
+/// auth chains of all state sets. Events for which `fetch_event` returns `None` are treated as
+/// missing, like with [`resolve`].
+pub async fn resolve_async<'a, E, SetIter, F, Fut>(
+    room_version: &RoomVersionId,
+    state_sets: impl IntoIterator<IntoIter = SetIter>,
+    auth_chain_sets: Vec<HashSet<E::Id>>,
+    fetch_event: F,
+) -> Result<StateMap<E::Id>>
+where
+    E: Event + Clone,
+    E::Id: 'a,
+    SetIter: Iterator<Item = &'a StateMap<E::Id>> + Clone,
+    F: Fn(E::Id) -> Fut,
+    Fut: Future<Output = Option<E>>,
+{
+    let state_sets = state_sets.into_iter();
+
+    let (clean, conflicting) = separate(state_sets.clone());
+    let event_ids: HashSet<_> = clean
+        .into_iter()
+        .filter(|((event_type, _), _)| AUTH_EVENT_TYPES.contains(event_type))
+        .map(|(_, id)| id)
+        .chain(conflicting.into_values().flatten())
+        .chain(auth_chain_sets.iter().flatten().cloned())
+        .collect();
+
+    info!("prefetching {} events for state resolution", event_ids.len());
+
+    let events = fetch_events(event_ids, &fetch_event).await;
+
+    resolve(room_version, state_sets, auth_chain_sets, |id| events.get(id).cloned())
+}
+
+/// Authenticate the incoming `event`, fetching state asynchronously.
+///
+/// This is the same as [`auth_check`], except that `fetch_state` returns a future.
+///
+/// All the state events that are needed to authenticate the event, as returned by
+/// [`auth_types_for_event`], are fetched concurrently before the checks are run.
+pub async fn auth_check_async<E, F, Fut>(
+    room_version: &RoomVersion,
+    incoming_event: impl Event,
+    current_third_party_invite: Option<impl Event>,
+    fetch_state: F,
+) -> Result<bool>
+where
+    E: Event,
+    F: Fn(StateEventType, String) -> Fut,
+    Fut: Future<Output = Option<E>>,
+{
+    let auth_types = auth_types_for_event(
+        incoming_event.event_type(),
+        incoming_event.sender(),
+        incoming_event.state_key(),
+        incoming_event.content(),
+    )?;
+
+    let fetch_state = &fetch_state;
+    let state: StateMap<E> = join_all(auth_types.into_iter().map(|key| async move {
+        let event = fetch_state(key.0.clone(), key.1.clone()).await;
+        event.map(|event| (key, event))
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect();
+
+    auth_check(room_version, incoming_event, current_third_party_invite, |ty, key| {
+        state.get(&ty.with_state_key(key))
+    })
+}
+
+/// Sorts the event graph based on number of outgoing/incoming edges, computing the sort keys
+/// asynchronously.
+///
+/// This is the same as [`lexicographical_topological_sort`], except that `key_fn` returns a
+/// future. The keys of all the events of the graph are computed concurrently before sorting it.
+pub async fn lexicographical_topological_sort_async<Id, F, Fut>(
+    graph: &HashMap<Id, HashSet<Id>>,
+    key_fn: F,
+) -> Result<Vec<Id>>
+where
+    F: Fn(Id) -> Fut,
+    Fut: Future<Output = Result<(Int, MilliSecondsSinceUnixEpoch)>>,
+    Id: Clone + Eq + Ord + Hash + Borrow<EventId>,
+{
+    let event_ids: HashSet<_> =
+        graph.iter().flat_map(|(id, edges)| Some(id).into_iter().chain(edges)).collect();
+
+    let key_fn = &key_fn;
+    let keys: HashMap<_, _> = try_join_all(
+        event_ids
+            .into_iter()
+            .map(|id| async move { key_fn(id.clone()).await.map(|key| (id.clone(), key)) }),
+    )
+    .await?
+    .into_iter()
+    .collect();
+
+    lexicographical_topological_sort(graph, |event_id| Ok(keys[event_id]))
+}
+
+/// Fetches the given events concurrently.
+///
+/// Events that could not be fetched are not part of the returned map.
+async fn fetch_events<E, F, Fut>(event_ids: HashSet<E::Id>, fetch_event: &F) -> HashMap<E::Id, E>
+where
+    E: Event,
+    F: Fn(E::Id) -> Fut,
+    Fut: Future<Output = Option<E>>,
+{
+    join_all(event_ids.into_iter().map(|id| async move {
+        let event = fetch_event(id.clone()).await;
+        event.map(|event| (id, event))
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use futures_executor::block_on;
+    use js_int::{int, uint};
+    use maplit::{hashmap, hashset};
+    use ruma_common::{events::RoomEventType, MilliSecondsSinceUnixEpoch, RoomVersionId};
+
+    use super::{auth_check_async, lexicographical_topological_sort_async, resolve_async};
+    use crate::{
+        test_utils::{
+            alice, charlie, event_id, member_content_ban, room_id, to_pdu_event, PduEvent,
+            TestStore, INITIAL_EVENTS,
+        },
+        Event, EventTypeExt, RoomVersion, StateMap,
+    };
+
+    #[test]
+    fn resolve_async_matches_sync() {
+        let mut store = TestStore::<PduEvent>(hashmap! {});
+        let (state_at_bob, state_at_charlie, expected) = store.set_up();
+
+        let ev_map = store.0.clone();
+        let state_sets = [state_at_bob, state_at_charlie];
+        let auth_chain_sets: Vec<_> = state_sets
+            .iter()
+            .map(|map| store.auth_event_ids(room_id(), map.values().cloned().collect()).unwrap())
+            .collect();
+
+        let resolved = block_on(resolve_async(
+            &RoomVersionId::V6,
+            &state_sets,
+            auth_chain_sets.clone(),
+            |id| {
+                let event = ev_map.get(&id).map(Arc::clone);
+                async move { event }
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(resolved, expected);
+        assert_eq!(
+            resolved,
+            crate::resolve(&RoomVersionId::V6, &state_sets, auth_chain_sets, |id| ev_map
+                .get(id)
+                .map(Arc::clone))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn auth_check_async_fetches_state() {
+        let events = INITIAL_EVENTS();
+        let auth_events: HashMap<_, _> = events
+            .values()
+            .map(|ev| (ev.event_type().with_state_key(ev.state_key().unwrap()), Arc::clone(ev)))
+            .collect::<StateMap<_>>();
+
+        let fetch_state = |ty, key| {
+            let event = auth_events.get(&(ty, key)).map(Arc::clone);
+            async move { event }
+        };
+
+        let ban = to_pdu_event(
+            "HELLO",
+            alice(),
+            RoomEventType::RoomMember,
+            Some(charlie().as_str()),
+            member_content_ban(),
+            &["CREATE", "IMA", "IPOWER"],
+            &["IMC"],
+        );
+        assert!(block_on(auth_check_async(&RoomVersion::V6, &ban, None::<PduEvent>, fetch_state))
+            .unwrap());
+
+        let ban_by_charlie = to_pdu_event(
+            "HELLO",
+            charlie(),
+            RoomEventType::RoomMember,
+            Some(alice().as_str()),
+            member_content_ban(),
+            &["CREATE", "IMA", "IPOWER"],
+            &["IMC"],
+        );
+        assert!(!block_on(auth_check_async(
+            &RoomVersion::V6,
+            &ban_by_charlie,
+            None::<PduEvent>,
+            fetch_state
+        ))
+        .unwrap());
+    }
+
+    #[test]
+    fn lexicographical_sort_async() {
+        let graph = hashmap! {
+            event_id("l") => hashset![event_id("o")],
+            event_id("m") => hashset![event_id("n"), event_id("o")],
+            event_id("n") => hashset![event_id("o")],
+            event_id("o") => hashset![],
+            event_id("p") => hashset![event_id("o")],
+        };
+
+        let res = block_on(lexicographical_topological_sort_async(&graph, |_id| async {
+            Ok((int!(0), MilliSecondsSinceUnixEpoch(uint!(0))))
+        }))
+        .unwrap();
+
+        assert_eq!(res, ["o", "l", "n", "m", "p"].map(event_id));
+    }
+}
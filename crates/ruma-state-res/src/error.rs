@@ -24,6 +24,11 @@ pub enum Error {
     #[error("Invalid PDU: {0}")]
     InvalidPdu(String),
 
+    /// An error while hashing or signing an event.
+    #[cfg(feature = "unstable-pdu")]
+    #[error(transparent)]
+    Signatures(#[from] ruma_signatures::Error),
+
     /// A custom error.
     #[error("{0}")]
     Custom(Box<dyn std::error::Error>),
@@ -12,14 +12,17 @@ use ruma_common::{
         },
         RoomEventType, StateEventType,
     },
-    serde::{Base64, Raw},
+    serde::{CanonicalJsonObject, Raw},
     OwnedUserId, RoomVersionId, UserId,
 };
 use serde::{de::IgnoredAny, Deserialize};
 use serde_json::{from_str as from_json_str, value::RawValue as RawJsonValue};
 use tracing::{debug, error, info, warn};
 
-use crate::{room_version::RoomVersion, Error, Event, PowerLevelsContentFields, Result};
+use crate::{
+    power_levels::deserialize_power_levels, room_version::RoomVersion, Error, Event,
+    PowerLevelsContentFields, Result,
+};
 
 // FIXME: field extracting could be bundled for `content`
 #[derive(Deserialize)]
@@ -319,7 +322,9 @@ pub fn auth_check<E: Event>(
 
     // If type is m.room.third_party_invite
     let sender_power_level = if let Some(pl) = &power_levels_event {
-        if let Ok(content) = from_json_str::<PowerLevelsContentFields>(pl.content().get()) {
+        if let Ok(content) =
+            deserialize_power_levels::<PowerLevelsContentFields>(pl.content().get(), room_version)
+        {
             if let Some(level) = content.users.get(sender) {
                 *level
             } else {
@@ -341,7 +346,11 @@ pub fn auth_check<E: Event>(
     if *incoming_event.event_type() == RoomEventType::RoomThirdPartyInvite {
         let invite_level = match &power_levels_event {
             Some(power_levels) => {
-                from_json_str::<PowerLevelsContentInvite>(power_levels.content().get())?.invite
+                deserialize_power_levels::<PowerLevelsContentInvite>(
+                    power_levels.content().get(),
+                    room_version,
+                )?
+                .invite
             }
             None => int!(50),
         };
@@ -354,7 +363,12 @@ pub fn auth_check<E: Event>(
 
     // If the event type's required power level is greater than the sender's power level, reject
     // If the event has a state_key that starts with an @ and does not match the sender, reject.
-    if !can_send_event(&incoming_event, power_levels_event.as_ref(), sender_power_level) {
+    if !can_send_event(
+        room_version,
+        &incoming_event,
+        power_levels_event.as_ref(),
+        sender_power_level,
+    ) {
         warn!("user cannot send event");
         return Ok(false);
     }
@@ -396,7 +410,13 @@ pub fn auth_check<E: Event>(
         }
 
         let redact_level = power_levels_event
-            .and_then(|pl| from_json_str::<PowerLevelsContentRedact>(pl.content().get()).ok())
+            .and_then(|pl| {
+                deserialize_power_levels::<PowerLevelsContentRedact>(
+                    pl.content().get(),
+                    room_version,
+                )
+                .ok()
+            })
             .map(|c| c.redact)
             .unwrap_or_else(|| int!(50));
 
@@ -455,7 +475,7 @@ fn valid_membership_change(
     };
 
     let power_levels: RoomPowerLevelsEventContent = match &power_levels_event {
-        Some(ev) => from_json_str(ev.content().get())?,
+        Some(ev) => deserialize_power_levels(ev.content().get(), room_version)?,
         None => RoomPowerLevelsEventContent::default(),
     };
 
@@ -481,13 +501,18 @@ fn valid_membership_change(
     let user_for_join_auth_is_valid = if let Some(user_for_join_auth) = user_for_join_auth {
         // Is the authorised user allowed to invite users into this room
         let (auth_user_pl, invite_level) = if let Some(pl) = &power_levels_event {
-            // TODO Refactor all powerlevel parsing
-            let invite = match from_json_str::<PowerLevelsContentInvite>(pl.content().get()) {
+            let invite = match deserialize_power_levels::<PowerLevelsContentInvite>(
+                pl.content().get(),
+                room_version,
+            ) {
                 Ok(power_levels) => power_levels.invite,
                 _ => int!(50),
             };
 
-            if let Ok(content) = from_json_str::<PowerLevelsContentFields>(pl.content().get()) {
+            if let Ok(content) = deserialize_power_levels::<PowerLevelsContentFields>(
+                pl.content().get(),
+                room_version,
+            ) {
                 let user_pl = if let Some(level) = content.users.get(user_for_join_auth) {
                     *level
                 } else {
@@ -545,8 +570,10 @@ fn valid_membership_change(
                 true
             } else if room_version.restricted_join_rules
                 && matches!(join_rules, JoinRule::Restricted(_))
+                || room_version.knock_restricted_join_rule
+                    && matches!(join_rules, JoinRule::KnockRestricted(_))
             {
-                // If the join_rule is restricted
+                // If the join_rule is restricted or knock_restricted
                 if matches!(
                     target_user_current_membership,
                     MembershipState::Invite | MembershipState::Join
@@ -567,7 +594,7 @@ fn valid_membership_change(
         }
         MembershipState::Invite => {
             // If content has third_party_invite key
-            if let Some(tp_id) = third_party_invite.and_then(|i| i.deserialize().ok()) {
+            if let Some(third_party_invite) = third_party_invite {
                 if target_user_current_membership == MembershipState::Ban {
                     warn!(?target_user_membership_event_id, "Can't invite banned user");
                     false
@@ -575,7 +602,7 @@ fn valid_membership_change(
                     let allow = verify_third_party_invite(
                         Some(target_user),
                         sender,
-                        &tp_id,
+                        &third_party_invite,
                         current_third_party_invite,
                     );
                     if !allow {
@@ -655,9 +682,12 @@ fn valid_membership_change(
             }
         }
         MembershipState::Knock if room_version.allow_knocking => {
-            // 1. If the `join_rule` is anything other than `knock`, reject.
-            if join_rules != JoinRule::Knock {
-                warn!("Join rule is not set to knock, knocking is not allowed");
+            // 1. If the `join_rule` is anything other than `knock` or `knock_restricted`, reject.
+            if join_rules != JoinRule::Knock
+                && !(room_version.knock_restricted_join_rule
+                    && matches!(join_rules, JoinRule::KnockRestricted(_)))
+            {
+                warn!("Join rule is not set to knock or knock_restricted, knocking is not allowed");
                 false
             } else {
                 // 2. If `sender` does not match `state_key`, reject.
@@ -694,8 +724,14 @@ fn valid_membership_change(
 /// Is the user allowed to send a specific event based on the rooms power levels.
 ///
 /// Does the event have the correct userId as its state_key if it's not the "" state_key.
-fn can_send_event(event: impl Event, ple: Option<impl Event>, user_level: Int) -> bool {
-    let event_type_power_level = get_send_level(event.event_type(), event.state_key(), ple);
+fn can_send_event(
+    room_version: &RoomVersion,
+    event: impl Event,
+    ple: Option<impl Event>,
+    user_level: Int,
+) -> bool {
+    let event_type_power_level =
+        get_send_level(room_version, event.event_type(), event.state_key(), ple);
 
     debug!("{} ev_type {} usr {}", event.event_id(), event_type_power_level, user_level);
 
@@ -731,19 +767,21 @@ fn check_power_levels(
         }
     }
 
+    // If users key in content is not a dictionary with keys that are valid user IDs
+    // with values that are integers (or a string that is an integer), reject.
+    //
+    // Since room version 10, any power level that is not an integer is rejected.
+    let user_content: RoomPowerLevelsEventContent =
+        deserialize_power_levels(power_event.content().get(), room_version).ok()?;
+
     let current_state = match previous_power_event {
         Some(current_state) => current_state,
         // If there is no previous m.room.power_levels event in the room, allow
         None => return Some(true),
     };
 
-    // If users key in content is not a dictionary with keys that are valid user IDs
-    // with values that are integers (or a string that is an integer), reject.
-    let user_content =
-        from_json_str::<RoomPowerLevelsEventContent>(power_event.content().get()).unwrap();
-
-    let current_content =
-        from_json_str::<RoomPowerLevelsEventContent>(current_state.content().get()).unwrap();
+    let current_content: RoomPowerLevelsEventContent =
+        deserialize_power_levels(current_state.content().get(), room_version).ok()?;
 
     // Validation of users is done in Ruma, synapse for loops validating user_ids and integers here
     info!("validation of power event finished");
@@ -888,23 +926,27 @@ fn check_redaction(
 /// Helper function to fetch the power level needed to send an event of type
 /// `e_type` based on the rooms "m.room.power_level" event.
 fn get_send_level(
+    room_version: &RoomVersion,
     e_type: &RoomEventType,
     state_key: Option<&str>,
     power_lvl: Option<impl Event>,
 ) -> Int {
     power_lvl
         .and_then(|ple| {
-            from_json_str::<RoomPowerLevelsEventContent>(ple.content().get())
-                .map(|content| {
-                    content.events.get(e_type).copied().unwrap_or_else(|| {
-                        if state_key.is_some() {
-                            content.state_default
-                        } else {
-                            content.events_default
-                        }
-                    })
+            deserialize_power_levels::<RoomPowerLevelsEventContent>(
+                ple.content().get(),
+                room_version,
+            )
+            .map(|content| {
+                content.events.get(e_type).copied().unwrap_or_else(|| {
+                    if state_key.is_some() {
+                        content.state_default
+                    } else {
+                        content.events_default
+                    }
                 })
-                .ok()
+            })
+            .ok()
         })
         .unwrap_or_else(|| if state_key.is_some() { int!(50) } else { int!(0) })
 }
@@ -912,11 +954,21 @@ fn get_send_level(
 fn verify_third_party_invite(
     target_user: Option<&UserId>,
     sender: &UserId,
-    tp_id: &ThirdPartyInvite,
+    third_party_invite: &Raw<ThirdPartyInvite>,
     current_third_party_invite: Option<impl Event>,
 ) -> bool {
+    /// The `signed` object of the `third_party_invite`, kept as-is to verify its signatures.
+    #[derive(Deserialize)]
+    struct ThirdPartyInviteSigned {
+        signed: CanonicalJsonObject,
+    }
+
     // 1. Check for user being banned happens before this is called
     // checking for mxid and token keys is done by ruma when deserializing
+    let tp_id = match third_party_invite.deserialize() {
+        Ok(tp_id) => tp_id,
+        Err(_) => return false,
+    };
 
     // The state key must match the invitee
     if target_user != Some(&tp_id.signed.mxid) {
@@ -946,27 +998,31 @@ fn verify_third_party_invite(
             Err(_) => return false,
         };
 
-    let decoded_invite_token = match Base64::parse(&tp_id.signed.token) {
-        Ok(tok) => tok,
-        // FIXME: Log a warning?
+    let signed = match third_party_invite.deserialize_as::<ThirdPartyInviteSigned>() {
+        Ok(tp_id) => tp_id.signed,
         Err(_) => return false,
     };
 
-    // A list of public keys in the public_keys field
-    for key in tpid_ev.public_keys.unwrap_or_default() {
-        if key.public_key == decoded_invite_token {
-            return true;
+    // A list of public keys in the public_keys field, and a single public key in the public_key
+    // field
+    let public_keys = tpid_ev.public_keys.unwrap_or_default();
+    let public_keys =
+        public_keys.iter().map(|key| &key.public_key).chain(Some(&tpid_ev.public_key));
+
+    match ruma_signatures::verify_third_party_invite(&signed, public_keys) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Third party invite signature verification failed: {}", e);
+            false
         }
     }
-
-    // A single public key in the public_key field
-    tpid_ev.public_key == decoded_invite_token
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
+    use js_int::int;
     use ruma_common::events::{
         room::{
             join_rules::{
@@ -976,10 +1032,11 @@ mod tests {
         },
         RoomEventType, StateEventType,
     };
-    use serde_json::value::to_raw_value as to_raw_json_value;
+    use ruma_signatures::{generate_third_party_invite_key_pair, sign_third_party_invite};
+    use serde_json::{json, value::to_raw_value as to_raw_json_value};
 
     use crate::{
-        event_auth::valid_membership_change,
+        event_auth::{check_power_levels, valid_membership_change},
         test_utils::{
             alice, charlie, ella, event_id, member_content_ban, member_content_join, room_id,
             to_pdu_event, PduEvent, INITIAL_EVENTS, INITIAL_EVENTS_CREATE_ROOM,
@@ -1280,4 +1337,282 @@ mod tests {
         )
         .unwrap());
     }
+
+    #[test]
+    fn test_knock_restricted() {
+        let _ =
+            tracing::subscriber::set_default(tracing_subscriber::fmt().with_test_writer().finish());
+        let mut events = INITIAL_EVENTS();
+        *events.get_mut(&event_id("IJR")).unwrap() = to_pdu_event(
+            "IJR",
+            alice(),
+            RoomEventType::RoomJoinRules,
+            Some(""),
+            to_raw_json_value(&RoomJoinRulesEventContent::knock_restricted(vec![
+                AllowRule::RoomMembership(RoomMembership::new(room_id().to_owned())),
+            ]))
+            .unwrap(),
+            &["CREATE", "IMA", "IPOWER"],
+            &["IPOWER"],
+        );
+
+        let auth_events = events
+            .values()
+            .map(|ev| (ev.event_type().with_state_key(ev.state_key().unwrap()), Arc::clone(ev)))
+            .collect::<StateMap<_>>();
+
+        let knock = to_pdu_event(
+            "HELLO",
+            ella(),
+            RoomEventType::RoomMember,
+            Some(ella().as_str()),
+            to_raw_json_value(&RoomMemberEventContent::new(MembershipState::Knock)).unwrap(),
+            &[],
+            &["IMC"],
+        );
+
+        let mut member = RoomMemberEventContent::new(MembershipState::Join);
+        member.join_authorized_via_users_server = Some(alice().to_owned());
+        let join = to_pdu_event(
+            "HELLO",
+            ella(),
+            RoomEventType::RoomMember,
+            Some(ella().as_str()),
+            to_raw_json_value(&member).unwrap(),
+            &["CREATE", "IJR", "IPOWER", "new"],
+            &["new"],
+        );
+
+        let fetch_state = |ty, key| auth_events.get(&(ty, key)).cloned();
+        let target_user = ella();
+        let sender = ella();
+
+        for (room_version, allowed) in [(RoomVersion::V9, false), (RoomVersion::V10, true)] {
+            assert_eq!(
+                valid_membership_change(
+                    &room_version,
+                    target_user,
+                    fetch_state(StateEventType::RoomMember, target_user.to_string()),
+                    sender,
+                    fetch_state(StateEventType::RoomMember, sender.to_string()),
+                    &knock,
+                    None::<PduEvent>,
+                    fetch_state(StateEventType::RoomPowerLevels, "".to_owned()),
+                    fetch_state(StateEventType::RoomJoinRules, "".to_owned()),
+                    None,
+                    &MembershipState::Leave,
+                    fetch_state(StateEventType::RoomCreate, "".to_owned()).unwrap(),
+                )
+                .unwrap(),
+                allowed
+            );
+
+            assert_eq!(
+                valid_membership_change(
+                    &room_version,
+                    target_user,
+                    fetch_state(StateEventType::RoomMember, target_user.to_string()),
+                    sender,
+                    fetch_state(StateEventType::RoomMember, sender.to_string()),
+                    &join,
+                    None::<PduEvent>,
+                    fetch_state(StateEventType::RoomPowerLevels, "".to_owned()),
+                    fetch_state(StateEventType::RoomJoinRules, "".to_owned()),
+                    Some(alice()),
+                    &MembershipState::Join,
+                    fetch_state(StateEventType::RoomCreate, "".to_owned()).unwrap(),
+                )
+                .unwrap(),
+                allowed
+            );
+        }
+    }
+
+    #[test]
+    fn test_third_party_invite() {
+        let _ =
+            tracing::subscriber::set_default(tracing_subscriber::fmt().with_test_writer().finish());
+        let events = INITIAL_EVENTS();
+
+        let auth_events = events
+            .values()
+            .map(|ev| (ev.event_type().with_state_key(ev.state_key().unwrap()), Arc::clone(ev)))
+            .collect::<StateMap<_>>();
+
+        let key_pair = generate_third_party_invite_key_pair();
+        let third_party_invite = to_pdu_event(
+            "THIRDPARTY",
+            alice(),
+            RoomEventType::RoomThirdPartyInvite,
+            Some("abc123"),
+            to_raw_json_value(&json!({
+                "display_name": "e...@example.org",
+                "key_validity_url": "https://identity.local/_matrix/identity/v2/pubkey/isvalid",
+                "public_key": key_pair.public_key_base64(),
+            }))
+            .unwrap(),
+            &["CREATE", "IMA", "IPOWER"],
+            &["IPOWER"],
+        );
+
+        let invite = |signed| {
+            to_pdu_event(
+                "HELLO",
+                alice(),
+                RoomEventType::RoomMember,
+                Some(ella().as_str()),
+                to_raw_json_value(&json!({
+                    "membership": "invite",
+                    "third_party_invite": {
+                        "display_name": "e...@example.org",
+                        "signed": signed,
+                    },
+                }))
+                .unwrap(),
+                &["CREATE", "IMA", "IPOWER", "THIRDPARTY"],
+                &["THIRDPARTY"],
+            )
+        };
+
+        let fetch_state = |ty, key| auth_events.get(&(ty, key)).cloned();
+        let target_user = ella();
+        let sender = alice();
+
+        let is_valid = |signed| {
+            valid_membership_change(
+                &RoomVersion::V6,
+                target_user,
+                fetch_state(StateEventType::RoomMember, target_user.to_string()),
+                sender,
+                fetch_state(StateEventType::RoomMember, sender.to_string()),
+                invite(signed),
+                Some(Arc::clone(&third_party_invite)),
+                fetch_state(StateEventType::RoomPowerLevels, "".to_owned()),
+                fetch_state(StateEventType::RoomJoinRules, "".to_owned()),
+                None,
+                &MembershipState::Leave,
+                fetch_state(StateEventType::RoomCreate, "".to_owned()).unwrap(),
+            )
+            .unwrap()
+        };
+
+        // Signed with the key of the m.room.third_party_invite event.
+        let signed =
+            sign_third_party_invite("identity.local", &key_pair, ella(), alice(), "abc123")
+                .unwrap();
+        assert!(is_valid(signed));
+
+        // Signed with another key.
+        let other_key_pair = generate_third_party_invite_key_pair();
+        let signed =
+            sign_third_party_invite("identity.local", &other_key_pair, ella(), alice(), "abc123")
+                .unwrap();
+        assert!(!is_valid(signed));
+
+        // The token is not a signature.
+        let signed = json!({
+            "mxid": ella(),
+            "token": "abc123",
+            "signatures": {},
+        });
+        assert!(!is_valid(serde_json::from_value(signed).unwrap()));
+    }
+
+    #[test]
+    fn test_integer_power_levels() {
+        let _ =
+            tracing::subscriber::set_default(tracing_subscriber::fmt().with_test_writer().finish());
+        let events = INITIAL_EVENTS();
+
+        let auth_events = events
+            .values()
+            .map(|ev| (ev.event_type().with_state_key(ev.state_key().unwrap()), Arc::clone(ev)))
+            .collect::<StateMap<_>>();
+
+        let fetch_state = |ty, key| auth_events.get(&(ty, key)).cloned();
+        let previous_power_levels = fetch_state(StateEventType::RoomPowerLevels, "".to_owned());
+
+        let int_power_levels = to_pdu_event(
+            "HELLO",
+            alice(),
+            RoomEventType::RoomPowerLevels,
+            Some(""),
+            to_raw_json_value(&json!({ "users": { alice(): 100, charlie(): 50 } })).unwrap(),
+            &["CREATE", "IMA", "IPOWER"],
+            &["IPOWER"],
+        );
+        let string_power_levels = to_pdu_event(
+            "HELLO",
+            alice(),
+            RoomEventType::RoomPowerLevels,
+            Some(""),
+            to_raw_json_value(&json!({ "users": { alice(): 100, charlie(): "50" } })).unwrap(),
+            &["CREATE", "IMA", "IPOWER"],
+            &["IPOWER"],
+        );
+
+        for room_version in [RoomVersion::V9, RoomVersion::V10] {
+            assert_eq!(
+                check_power_levels(
+                    &room_version,
+                    &int_power_levels,
+                    previous_power_levels.as_ref(),
+                    int!(100)
+                ),
+                Some(true)
+            );
+        }
+
+        assert_eq!(
+            check_power_levels(
+                &RoomVersion::V10,
+                &string_power_levels,
+                previous_power_levels.as_ref(),
+                int!(100)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_integer_power_levels_without_previous_event() {
+        let _ =
+            tracing::subscriber::set_default(tracing_subscriber::fmt().with_test_writer().finish());
+
+        let int_power_levels = to_pdu_event(
+            "HELLO",
+            alice(),
+            RoomEventType::RoomPowerLevels,
+            Some(""),
+            to_raw_json_value(&json!({ "ban": 50, "users": { alice(): 100 } })).unwrap(),
+            &["CREATE", "IMA"],
+            &["IMA"],
+        );
+        let string_power_levels = to_pdu_event(
+            "HELLO",
+            alice(),
+            RoomEventType::RoomPowerLevels,
+            Some(""),
+            to_raw_json_value(&json!({ "ban": "50", "users": { alice(): 100 } })).unwrap(),
+            &["CREATE", "IMA"],
+            &["IMA"],
+        );
+
+        for room_version in [RoomVersion::V9, RoomVersion::V10] {
+            assert_eq!(
+                check_power_levels(&room_version, &int_power_levels, None::<PduEvent>, int!(100)),
+                Some(true)
+            );
+        }
+
+        assert_eq!(
+            check_power_levels(
+                &RoomVersion::V10,
+                &string_power_levels,
+                None::<PduEvent>,
+                int!(100)
+            ),
+            None
+        );
+    }
 }
@@ -18,16 +18,26 @@ use serde::Deserialize;
 use serde_json::from_str as from_json_str;
 use tracing::{debug, info, trace, warn};
 
+mod asynchronous;
 mod error;
 pub mod event_auth;
+#[cfg(feature = "unstable-pdu")]
+mod pdu_builder;
+mod power_levels;
 pub mod room_version;
 mod state_event;
 #[cfg(test)]
 mod test_utils;
+mod v1;
 
+pub use asynchronous::{auth_check_async, lexicographical_topological_sort_async, resolve_async};
 pub use error::{Error, Result};
 pub use event_auth::{auth_check, auth_types_for_event};
+#[cfg(feature = "unstable-pdu")]
+pub use pdu_builder::PduBuilder;
+use power_levels::deserialize_power_levels;
 pub use room_version::RoomVersion;
+use room_version::StateResolutionVersion;
 pub use state_event::Event;
 
 /// A mapping of event type and state_key to some value `T`, usually an `EventId`.
@@ -35,8 +45,10 @@ pub type StateMap<T> = HashMap<(StateEventType, String), T>;
 
 /// Resolve sets of state events as they come in.
 ///
-/// Internally `StateResolution` builds a graph and an auth chain to allow for state conflict
-/// resolution.
+/// The algorithm used depends on the state resolution version of `room_version`. For room
+/// versions using state resolution v2, `StateResolution` internally builds a graph and an auth
+/// chain to allow for state conflict resolution. Room version 1 uses the simpler v1 algorithm,
+/// which only looks at the depth of conflicting events.
 ///
 /// ## Arguments
 ///
@@ -44,7 +56,7 @@ pub type StateMap<T> = HashMap<(StateEventType, String), T>;
 ///   the state of a room.
 ///
 /// * `auth_chain_sets` - The full recursive set of `auth_events` for each event in the
-///   `state_sets`.
+///   `state_sets`. Unused by state resolution v1.
 ///
 /// * `fetch_event` - Any event not found in the `event_map` will defer to this closure to find the
 ///   event.
@@ -64,6 +76,14 @@ where
     E::Id: 'a,
     SetIter: Iterator<Item = &'a StateMap<E::Id>> + Clone,
 {
+    let room_version = RoomVersion::new(room_version)?;
+    match room_version.state_res {
+        StateResolutionVersion::V1 => {
+            return v1::resolve(&room_version, state_sets.into_iter(), fetch_event);
+        }
+        StateResolutionVersion::V2 => {}
+    }
+
     info!("State resolution starting");
 
     // Split non-conflicting and conflicting state
@@ -102,13 +122,16 @@ where
         .collect::<Vec<_>>();
 
     // Sort the control events based on power_level/clock/event_id and outgoing/incoming edges
-    let sorted_control_levels =
-        reverse_topological_power_sort(control_events, &all_conflicted, &fetch_event)?;
+    let sorted_control_levels = reverse_topological_power_sort(
+        &room_version,
+        control_events,
+        &all_conflicted,
+        &fetch_event,
+    )?;
 
     debug!("sorted control events: {}", sorted_control_levels.len());
     trace!("{:?}", sorted_control_levels);
 
-    let room_version = RoomVersion::new(room_version)?;
     // Sequentially auth check each control event.
     let resolved_control =
         iterative_auth_check(&room_version, &sorted_control_levels, clean.clone(), &fetch_event)?;
@@ -211,6 +234,7 @@ where
 /// The power level is negative because a higher power level is equated to an earlier (further back
 /// in time) origin server timestamp.
 fn reverse_topological_power_sort<E: Event>(
+    room_version: &RoomVersion,
     events_to_sort: Vec<E::Id>,
     auth_diff: &HashSet<E::Id>,
     fetch_event: impl Fn(&EventId) -> Option<E>,
@@ -229,7 +253,7 @@ fn reverse_topological_power_sort<E: Event>(
     // This is used in the `key_fn` passed to the lexico_topo_sort fn
     let mut event_to_pl = HashMap::new();
     for event_id in graph.keys() {
-        let pl = get_power_level_for_sender(event_id.borrow(), &fetch_event)?;
+        let pl = get_power_level_for_sender(room_version, event_id.borrow(), &fetch_event)?;
         info!("{} power level {}", event_id, pl);
 
         event_to_pl.insert(event_id.clone(), pl);
@@ -358,6 +382,7 @@ struct PowerLevelsContentFields {
 /// at the eventId's generation (we walk backwards to `EventId`s most recent previous power level
 /// event).
 fn get_power_level_for_sender<E: Event>(
+    room_version: &RoomVersion,
     event_id: &EventId,
     fetch_event: impl Fn(&EventId) -> Option<E>,
 ) -> serde_json::Result<Int> {
@@ -377,7 +402,7 @@ fn get_power_level_for_sender<E: Event>(
 
     let content: PowerLevelsContentFields = match pl {
         None => return Ok(int!(0)),
-        Some(ev) => from_json_str(ev.content().get())?,
+        Some(ev) => deserialize_power_levels(ev.content().get(), room_version)?,
     };
 
     if let Some(ev) = event {
@@ -700,11 +725,13 @@ mod tests {
             .map(|pdu| pdu.event_id.clone())
             .collect::<Vec<_>>();
 
-        let sorted_power_events =
-            crate::reverse_topological_power_sort(power_events, &auth_chain, |id| {
-                events.get(id).map(Arc::clone)
-            })
-            .unwrap();
+        let sorted_power_events = crate::reverse_topological_power_sort(
+            &RoomVersion::V6,
+            power_events,
+            &auth_chain,
+            |id| events.get(id).map(Arc::clone),
+        )
+        .unwrap();
 
         let resolved_power = crate::iterative_auth_check(
             &RoomVersion::V6,
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+
+use js_int::{uint, UInt};
+use ruma_common::{
+    events::{
+        pdu::{EventHash, Pdu, RoomV1Pdu, RoomV3Pdu},
+        RoomEventType,
+    },
+    serde::{to_canonical_value, CanonicalJsonObject, CanonicalJsonValue},
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId, OwnedUserId, RoomVersionId,
+    ServerName,
+};
+use ruma_signatures::{hash_and_sign_event, reference_hash, KeyPair};
+use serde_json::value::{to_raw_value as to_raw_json_value, RawValue as RawJsonValue};
+
+use crate::{
+    auth_types_for_event, room_version::EventFormatVersion, Error, Result, RoomVersion, StateMap,
+};
+
+/// A builder for new PDUs.
+///
+/// It selects the `auth_events` of the event from the current state of the room, and then fills
+/// the hashes, signatures and event ID of the event, in the format of the room version.
+///
+/// The events in `prev_events` and in the state of the room are referenced with their event ID
+/// and their content hashes. The hashes are only used in room versions 1 and 2, whose PDUs
+/// include them.
+#[derive(Clone, Debug)]
+pub struct PduBuilder {
+    room_id: OwnedRoomId,
+    sender: OwnedUserId,
+    event_type: RoomEventType,
+    content: Box<RawJsonValue>,
+    state_key: Option<String>,
+    redacts: Option<OwnedEventId>,
+    prev_events: Vec<(OwnedEventId, EventHash)>,
+    depth: UInt,
+    origin_server_ts: Option<MilliSecondsSinceUnixEpoch>,
+    unsigned: BTreeMap<String, Box<RawJsonValue>>,
+}
+
+impl PduBuilder {
+    /// Creates a new `PduBuilder` for an event of the given type, sent in the given room.
+    pub fn new(
+        room_id: OwnedRoomId,
+        sender: OwnedUserId,
+        event_type: RoomEventType,
+        content: Box<RawJsonValue>,
+    ) -> Self {
+        Self {
+            room_id,
+            sender,
+            event_type,
+            content,
+            state_key: None,
+            redacts: None,
+            prev_events: Vec::new(),
+            depth: uint!(1),
+            origin_server_ts: None,
+            unsigned: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a new `PduBuilder` for an event with the given serializable content.
+    pub fn with_content<T: serde::Serialize>(
+        room_id: OwnedRoomId,
+        sender: OwnedUserId,
+        event_type: RoomEventType,
+        content: &T,
+    ) -> serde_json::Result<Self> {
+        Ok(Self::new(room_id, sender, event_type, to_raw_json_value(content)?))
+    }
+
+    /// Sets the state key of the event, making it a state event.
+    pub fn state_key(self, state_key: String) -> Self {
+        Self { state_key: Some(state_key), ..self }
+    }
+
+    /// Sets the ID of the event redacted by this event.
+    pub fn redacts(self, redacts: OwnedEventId) -> Self {
+        Self { redacts: Some(redacts), ..self }
+    }
+
+    /// Sets the most recent events of the room that this event follows, and the maximum depth of
+    /// these events.
+    ///
+    /// The depth of the new event will be `max_depth` plus one. If this is not called, the event
+    /// has no `prev_events` and a depth of 1, which is only correct for the `m.room.create` event.
+    pub fn prev_events(self, prev_events: Vec<(OwnedEventId, EventHash)>, max_depth: UInt) -> Self {
+        Self { prev_events, depth: max_depth + uint!(1), ..self }
+    }
+
+    /// Sets the time at which the event was created.
+    ///
+    /// Defaults to the current time.
+    pub fn origin_server_ts(self, origin_server_ts: MilliSecondsSinceUnixEpoch) -> Self {
+        Self { origin_server_ts: Some(origin_server_ts), ..self }
+    }
+
+    /// Sets the data that is not covered by the hashes and signatures of the event.
+    pub fn unsigned(self, unsigned: BTreeMap<String, Box<RawJsonValue>>) -> Self {
+        Self { unsigned, ..self }
+    }
+
+    /// Builds the PDU, signed by `origin` with `key_pair`.
+    ///
+    /// The `auth_events` are selected from `current_state` with [`auth_types_for_event`]. The
+    /// auth events that are missing from the state are skipped, so the event might not pass the
+    /// authorization rules.
+    ///
+    /// Returns the ID of the event and the PDU.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the room version is not supported, if the content of a membership event
+    /// is invalid, or if hashing or signing the event fails.
+    pub fn build<K: KeyPair>(
+        self,
+        current_state: &StateMap<(OwnedEventId, EventHash)>,
+        room_version_id: &RoomVersionId,
+        origin: &ServerName,
+        key_pair: &K,
+    ) -> Result<(OwnedEventId, Pdu)> {
+        let room_version = RoomVersion::new(room_version_id)?;
+
+        let auth_types = auth_types_for_event(
+            &self.event_type,
+            &self.sender,
+            self.state_key.as_deref(),
+            &self.content,
+        )?;
+        let auth_events: Vec<_> =
+            auth_types.iter().filter_map(|key| current_state.get(key)).cloned().collect();
+
+        let origin_server_ts =
+            self.origin_server_ts.unwrap_or_else(MilliSecondsSinceUnixEpoch::now);
+        // The hashes are computed below.
+        let hashes = EventHash::new(String::new());
+
+        let pdu = match room_version.event_format {
+            EventFormatVersion::V1 => Pdu::RoomV1Pdu(RoomV1Pdu {
+                event_id: EventId::new(origin),
+                room_id: self.room_id,
+                sender: self.sender,
+                origin_server_ts,
+                kind: self.event_type,
+                content: self.content,
+                state_key: self.state_key,
+                prev_events: self.prev_events,
+                depth: self.depth,
+                auth_events,
+                redacts: self.redacts,
+                unsigned: self.unsigned,
+                hashes,
+                signatures: BTreeMap::new(),
+            }),
+            _ => Pdu::RoomV3Pdu(RoomV3Pdu {
+                room_id: self.room_id,
+                sender: self.sender,
+                origin_server_ts,
+                kind: self.event_type,
+                content: self.content,
+                state_key: self.state_key,
+                prev_events: self.prev_events.into_iter().map(|(id, _)| id).collect(),
+                depth: self.depth,
+                auth_events: auth_events.into_iter().map(|(id, _)| id).collect(),
+                redacts: self.redacts,
+                unsigned: self.unsigned,
+                hashes,
+                signatures: BTreeMap::new(),
+            }),
+        };
+
+        let mut object = match to_canonical_value(&pdu).map_err(Error::custom)? {
+            CanonicalJsonValue::Object(object) => object,
+            _ => return Err(Error::InvalidPdu("PDU is not a JSON object".to_owned())),
+        };
+        object.remove("hashes");
+        object.remove("signatures");
+
+        hash_and_sign_event(origin.as_str(), key_pair, &mut object, room_version_id)?;
+
+        let event_id = match &pdu {
+            Pdu::RoomV1Pdu(pdu) => pdu.event_id.clone(),
+            _ => event_id_from_reference_hash(&object, room_version_id)?,
+        };
+
+        let pdu = serde_json::from_value(CanonicalJsonValue::Object(object).into())?;
+
+        Ok((event_id, pdu))
+    }
+}
+
+fn event_id_from_reference_hash(
+    object: &CanonicalJsonObject,
+    room_version_id: &RoomVersionId,
+) -> Result<OwnedEventId> {
+    let event_id = format!("${}", reference_hash(object, room_version_id)?);
+    EventId::parse(&event_id)
+        .map_err(|_| Error::InvalidPdu(format!("invalid event ID: {}", event_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use js_int::uint;
+    use ruma_common::{
+        event_id,
+        events::{
+            pdu::{EventHash, Pdu},
+            RoomEventType, StateEventType,
+        },
+        room_id,
+        serde::{to_canonical_value, CanonicalJsonValue},
+        server_name, user_id, MilliSecondsSinceUnixEpoch, OwnedEventId, RoomVersionId,
+    };
+    use ruma_signatures::{verify_event, Ed25519KeyPair, PublicKeyMap, Verified};
+    use serde_json::json;
+
+    use super::PduBuilder;
+    use crate::StateMap;
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::random("1".to_owned())
+    }
+
+    fn public_key_map(key_pair: &Ed25519KeyPair) -> PublicKeyMap {
+        let mut keys = BTreeMap::new();
+        keys.insert(key_pair.key_id().to_string(), key_pair.public_key_base64());
+        let mut map = PublicKeyMap::new();
+        map.insert("foo".to_owned(), keys);
+        map
+    }
+
+    fn verify(pdu: &Pdu, key_pair: &Ed25519KeyPair, version: &RoomVersionId) {
+        let object = match to_canonical_value(pdu).unwrap() {
+            CanonicalJsonValue::Object(object) => object,
+            _ => unreachable!(),
+        };
+        let verified = verify_event(&public_key_map(key_pair), &object, version).unwrap();
+        assert!(matches!(verified, Verified::All));
+    }
+
+    fn current_state() -> StateMap<(OwnedEventId, EventHash)> {
+        let mut state = StateMap::new();
+        state.insert(
+            (StateEventType::RoomCreate, "".to_owned()),
+            (event_id!("$create:foo").to_owned(), EventHash::new("create".to_owned())),
+        );
+        state.insert(
+            (StateEventType::RoomPowerLevels, "".to_owned()),
+            (event_id!("$power:foo").to_owned(), EventHash::new("power".to_owned())),
+        );
+        state.insert(
+            (StateEventType::RoomJoinRules, "".to_owned()),
+            (event_id!("$join_rules:foo").to_owned(), EventHash::new("join_rules".to_owned())),
+        );
+        state.insert(
+            (StateEventType::RoomMember, "@alice:foo".to_owned()),
+            (event_id!("$alice:foo").to_owned(), EventHash::new("alice".to_owned())),
+        );
+        state
+    }
+
+    fn join_event() -> PduBuilder {
+        PduBuilder::with_content(
+            room_id!("!room:foo").to_owned(),
+            user_id!("@bob:foo").to_owned(),
+            RoomEventType::RoomMember,
+            &json!({ "membership": "join" }),
+        )
+        .unwrap()
+        .state_key("@bob:foo".to_owned())
+        .prev_events(
+            vec![(event_id!("$alice:foo").to_owned(), EventHash::new("alice".to_owned()))],
+            uint!(4),
+        )
+        .origin_server_ts(MilliSecondsSinceUnixEpoch(uint!(1_000)))
+    }
+
+    #[test]
+    fn build_v1_pdu() {
+        let key_pair = key_pair();
+        let (event_id, pdu) = join_event()
+            .build(&current_state(), &RoomVersionId::V1, server_name!("foo"), &key_pair)
+            .unwrap();
+
+        assert_eq!(event_id.server_name(), Some(server_name!("foo")));
+        let v1_pdu = match &pdu {
+            Pdu::RoomV1Pdu(pdu) => pdu,
+            _ => panic!("expected a v1 PDU"),
+        };
+        assert_eq!(v1_pdu.event_id, event_id);
+        assert_eq!(v1_pdu.depth, uint!(5));
+        assert_eq!(v1_pdu.prev_events.len(), 1);
+        let auth_events: Vec<_> = v1_pdu.auth_events.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(auth_events, ["$power:foo", "$create:foo", "$join_rules:foo"]);
+        assert_eq!(v1_pdu.auth_events[0].1.sha256, "power");
+
+        verify(&pdu, &key_pair, &RoomVersionId::V1);
+    }
+
+    #[test]
+    fn build_v3_pdu() {
+        let key_pair = key_pair();
+        let (event_id, pdu) = join_event()
+            .build(&current_state(), &RoomVersionId::V9, server_name!("foo"), &key_pair)
+            .unwrap();
+
+        let v3_pdu = match &pdu {
+            Pdu::RoomV3Pdu(pdu) => pdu,
+            _ => panic!("expected a v3 PDU"),
+        };
+        assert_eq!(event_id.server_name(), None);
+        assert_eq!(v3_pdu.prev_events, [event_id!("$alice:foo")]);
+        assert_eq!(
+            v3_pdu.auth_events,
+            [event_id!("$power:foo"), event_id!("$create:foo"), event_id!("$join_rules:foo")]
+        );
+
+        verify(&pdu, &key_pair, &RoomVersionId::V9);
+
+        let object = match to_canonical_value(&pdu).unwrap() {
+            CanonicalJsonValue::Object(object) => object,
+            _ => unreachable!(),
+        };
+        let reference_hash = ruma_signatures::reference_hash(&object, &RoomVersionId::V9).unwrap();
+        assert_eq!(event_id.as_str(), format!("${}", reference_hash));
+        assert!(!reference_hash.contains('+') && !reference_hash.contains('/'));
+    }
+
+    #[test]
+    fn build_create_event() {
+        let key_pair = key_pair();
+        let (_, pdu) = PduBuilder::with_content(
+            room_id!("!room:foo").to_owned(),
+            user_id!("@alice:foo").to_owned(),
+            RoomEventType::RoomCreate,
+            &json!({ "creator": "@alice:foo" }),
+        )
+        .unwrap()
+        .state_key("".to_owned())
+        .build(&StateMap::new(), &RoomVersionId::V6, server_name!("foo"), &key_pair)
+        .unwrap();
+
+        let v3_pdu = match &pdu {
+            Pdu::RoomV3Pdu(pdu) => pdu,
+            _ => panic!("expected a v3 PDU"),
+        };
+        assert!(v3_pdu.auth_events.is_empty());
+        assert!(v3_pdu.prev_events.is_empty());
+        assert_eq!(v3_pdu.depth, uint!(1));
+
+        verify(&pdu, &key_pair, &RoomVersionId::V6);
+    }
+}
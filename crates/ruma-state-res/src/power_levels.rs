@@ -0,0 +1,122 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde_json::{
+    from_str as from_json_str, from_value as from_json_value, Error, Value as JsonValue,
+};
+use tracing::error;
+
+use crate::RoomVersion;
+
+/// The fields of the content of an `m.room.power_levels` event that contain a power level.
+const POWER_LEVEL_FIELDS: &[&str] =
+    &["ban", "events_default", "invite", "kick", "redact", "state_default", "users_default"];
+
+/// The fields of the content of an `m.room.power_levels` event that contain a map of power levels.
+const POWER_LEVEL_MAP_FIELDS: &[&str] = &["events", "notifications", "users"];
+
+/// Deserializes the content of an `m.room.power_levels` event, or a subset of its fields,
+/// according to the rules of the given room version.
+///
+/// Since room version 10, power levels must be integers. Stringified integers are then rejected
+/// even if the `compat` feature of `ruma-common` or of this crate is activated.
+pub(crate) fn deserialize_power_levels<T: DeserializeOwned>(
+    content: &str,
+    room_version: &RoomVersion,
+) -> Result<T, Error> {
+    if room_version.integer_power_levels {
+        let content: JsonValue = from_json_str(content)?;
+        check_integer_power_levels(&content).map_err(|error| {
+            error!("m.room.power_levels event is not valid with integer values");
+            error
+        })?;
+        from_json_value(content)
+    } else {
+        from_json_str(content)
+    }
+}
+
+/// Checks that all the power levels in the content of an `m.room.power_levels` event are
+/// integers.
+fn check_integer_power_levels(content: &JsonValue) -> Result<(), Error> {
+    let is_integer = |value: &JsonValue| value.is_i64() || value.is_u64();
+
+    for &field in POWER_LEVEL_FIELDS {
+        match content.get(field) {
+            Some(value) if !is_integer(value) => {
+                return Err(Error::custom(format!("power level `{}` is not an integer", field)));
+            }
+            _ => {}
+        }
+    }
+
+    for &field in POWER_LEVEL_MAP_FIELDS {
+        let map = match content.get(field) {
+            Some(JsonValue::Object(map)) => map,
+            // Other types are rejected by the deserialization.
+            _ => continue,
+        };
+
+        if let Some(key) = map.iter().find_map(|(key, value)| (!is_integer(value)).then(|| key)) {
+            return Err(Error::custom(format!(
+                "power level `{}` in `{}` is not an integer",
+                key, field
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use js_int::int;
+    use ruma_common::events::room::power_levels::RoomPowerLevelsEventContent;
+    use serde_json::{json, to_string as to_json_string};
+
+    use super::deserialize_power_levels;
+    use crate::{test_utils::alice, PowerLevelsContentFields, RoomVersion};
+
+    #[test]
+    fn integer_power_levels() {
+        let content = to_json_string(&json!({
+            "ban": 100,
+            "events": { "m.room.name": 50 },
+            "users": { alice(): 100 },
+            "notifications": { "room": 10 },
+        }))
+        .unwrap();
+
+        let pl: RoomPowerLevelsEventContent =
+            deserialize_power_levels(&content, &RoomVersion::V10).unwrap();
+        assert_eq!(pl.ban, int!(100));
+        assert_eq!(pl.kick, int!(50));
+        assert_eq!(pl.users[alice()], int!(100));
+        assert_eq!(pl.notifications.room, int!(10));
+    }
+
+    #[test]
+    fn stringified_power_levels_v10() {
+        let content = to_json_string(&json!({ "users": { alice(): "100" } })).unwrap();
+        deserialize_power_levels::<RoomPowerLevelsEventContent>(&content, &RoomVersion::V10)
+            .unwrap_err();
+
+        let content = to_json_string(&json!({ "ban": "100" })).unwrap();
+        deserialize_power_levels::<RoomPowerLevelsEventContent>(&content, &RoomVersion::V10)
+            .unwrap_err();
+
+        let content = to_json_string(&json!({ "notifications": { "room": 1.5 } })).unwrap();
+        deserialize_power_levels::<RoomPowerLevelsEventContent>(&content, &RoomVersion::V10)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn stringified_power_levels_v10_partial_content() {
+        let content = to_json_string(&json!({ "ban": "100", "users": { alice(): 100 } })).unwrap();
+
+        let fields: PowerLevelsContentFields =
+            deserialize_power_levels(&content, &RoomVersion::V9).unwrap();
+        assert_eq!(fields.users[alice()], int!(100));
+
+        assert!(deserialize_power_levels::<PowerLevelsContentFields>(&content, &RoomVersion::V10)
+            .is_err());
+    }
+}
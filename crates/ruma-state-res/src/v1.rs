@@ -0,0 +1,529 @@
+//! The state resolution algorithm of room version 1.
+//!
+//! See the [room version 1 specification] and Synapse's `synapse/state/v1.py` for the reference
+//! implementation this follows.
+//!
+//! [room version 1 specification]: https://spec.matrix.org/v1.2/rooms/v1/#state-resolution
+
+use std::{borrow::Borrow, cmp::Reverse, collections::HashSet};
+
+use ruma_common::{
+    events::{RoomEventType, StateEventType},
+    EventId,
+};
+use sha1::{Digest, Sha1};
+use tracing::{debug, info, trace, warn};
+
+use crate::{auth_check, auth_types_for_event, Event, EventTypeExt, Result, RoomVersion, StateMap};
+
+/// Resolves the given state sets with the state resolution algorithm of room version 1.
+///
+/// Unlike the version 2 algorithm, this doesn't use the auth chains of the state sets. Events
+/// that can't be fetched are ignored.
+pub(crate) fn resolve<'a, E>(
+    room_version: &RoomVersion,
+    state_sets: impl Iterator<Item = &'a StateMap<E::Id>>,
+    fetch_event: impl Fn(&EventId) -> Option<E>,
+) -> Result<StateMap<E::Id>>
+where
+    E: Event + Clone,
+    E::Id: 'a,
+{
+    info!("State resolution v1 starting");
+
+    let (mut unconflicted, conflicted) = separate(state_sets);
+
+    info!("non conflicting events: {}", unconflicted.len());
+    trace!("{:?}", unconflicted);
+
+    if conflicted.is_empty() {
+        info!("no conflicting state found");
+        return Ok(unconflicted);
+    }
+
+    info!("conflicting events: {}", conflicted.len());
+    debug!("{:?}", conflicted);
+
+    let conflicted: StateMap<Vec<E>> = conflicted
+        .into_iter()
+        .map(|(key, ids)| (key, ids.iter().filter_map(|id| fetch_event(id.borrow())).collect()))
+        .collect();
+
+    // The auth events of the conflicted events are taken from the unconflicted state.
+    let mut auth_events = StateMap::new();
+    for event in conflicted.values().flatten() {
+        for key in auth_types_for_event(
+            event.event_type(),
+            event.sender(),
+            event.state_key(),
+            event.content(),
+        )? {
+            if auth_events.contains_key(&key) {
+                continue;
+            }
+
+            if let Some(auth_event) =
+                unconflicted.get(&key).and_then(|id: &E::Id| fetch_event(id.borrow()))
+            {
+                auth_events.insert(key, auth_event);
+            }
+        }
+    }
+
+    // A conflict for which only one event could be fetched is not a conflict anymore.
+    let mut conflicted_events = StateMap::new();
+    for (key, mut events) in conflicted {
+        match events.len() {
+            0 => {}
+            1 => {
+                let event = events.pop().expect("events has one element");
+                unconflicted.insert(key, event.event_id().clone());
+            }
+            _ => {
+                conflicted_events.insert(key, events);
+            }
+        }
+    }
+
+    let resolved = resolve_state_events(room_version, &conflicted_events, auth_events)?;
+
+    let resolved: StateMap<_> =
+        resolved.into_iter().map(|(key, event)| (key, event.event_id().clone())).collect();
+
+    debug!("resolved conflicted events: {}", resolved.len());
+    trace!("{:?}", resolved);
+
+    let mut resolved_state = unconflicted;
+    resolved_state.extend(resolved);
+
+    Ok(resolved_state)
+}
+
+/// Split the events that have no conflicts from those that are conflicting.
+///
+/// The return tuple looks like `(unconflicted, conflicted)`.
+///
+/// Contrary to the version 2 algorithm, a key is only conflicted if at least two of the state
+/// sets have different events for it. A key missing from some state sets is not a conflict.
+fn separate<'a, Id>(
+    mut state_sets: impl Iterator<Item = &'a StateMap<Id>>,
+) -> (StateMap<Id>, StateMap<Vec<Id>>)
+where
+    Id: Clone + Eq + 'a,
+{
+    let mut unconflicted_state = state_sets.next().cloned().unwrap_or_default();
+    let mut conflicted_state: StateMap<Vec<Id>> = StateMap::new();
+
+    for state_set in state_sets {
+        for (key, id) in state_set {
+            if let Some(ids) = conflicted_state.get_mut(key) {
+                if !ids.contains(id) {
+                    ids.push(id.clone());
+                }
+                continue;
+            }
+
+            match unconflicted_state.get(key) {
+                Some(unconflicted_id) if unconflicted_id != id => {
+                    let unconflicted_id =
+                        unconflicted_state.remove(key).expect("key is in unconflicted state");
+                    conflicted_state.insert(key.clone(), vec![unconflicted_id, id.clone()]);
+                }
+                Some(_) => {}
+                None => {
+                    unconflicted_state.insert(key.clone(), id.clone());
+                }
+            }
+        }
+    }
+
+    (unconflicted_state, conflicted_state)
+}
+
+/// Picks one event for each conflicted key.
+///
+/// Conflicts are resolved in the following order, each step using the result of the previous ones
+/// as auth events:
+///
+/// 1. power levels
+/// 2. join rules
+/// 3. memberships
+/// 4. other events
+fn resolve_state_events<E: Event + Clone>(
+    room_version: &RoomVersion,
+    conflicted: &StateMap<Vec<E>>,
+    mut auth_events: StateMap<E>,
+) -> Result<StateMap<E>> {
+    let mut resolved_state = StateMap::new();
+
+    let power_key = (StateEventType::RoomPowerLevels, "".to_owned());
+    if let Some(events) = conflicted.get(&power_key) {
+        let event = resolve_auth_events(room_version, events, &auth_events)?;
+        resolved_state.insert(power_key, event);
+    }
+
+    for event_type in [StateEventType::RoomJoinRules, StateEventType::RoomMember] {
+        auth_events.extend(resolved_state.clone());
+
+        for (key, events) in conflicted.iter().filter(|(key, _)| key.0 == event_type) {
+            let event = resolve_auth_events(room_version, events, &auth_events)?;
+            resolved_state.insert(key.clone(), event);
+        }
+    }
+
+    auth_events.extend(resolved_state.clone());
+
+    for (key, events) in conflicted {
+        if !resolved_state.contains_key(key) {
+            let event = resolve_normal_events(room_version, events, &auth_events)?;
+            resolved_state.insert(key.clone(), event);
+        }
+    }
+
+    Ok(resolved_state)
+}
+
+/// Resolves a conflict between auth events.
+///
+/// Starting from the oldest event, each event is checked against the previous one. The last event
+/// in that chain that passes the auth checks wins.
+fn resolve_auth_events<E: Event + Clone>(
+    room_version: &RoomVersion,
+    events: &[E],
+    auth_events: &StateMap<E>,
+) -> Result<E> {
+    let mut auth_keys = HashSet::new();
+    for event in events {
+        auth_keys.extend(auth_types_for_event(
+            event.event_type(),
+            event.sender(),
+            event.state_key(),
+            event.content(),
+        )?);
+    }
+
+    let mut auth_events: StateMap<E> = auth_events
+        .iter()
+        .filter(|(key, _)| auth_keys.contains(*key))
+        .map(|(key, event)| (key.clone(), event.clone()))
+        .collect();
+
+    let mut events = ordered_events(events).into_iter().rev();
+    let mut prev_event = events.next().expect("conflicted events are never empty");
+
+    for event in events {
+        let key = prev_event
+            .event_type()
+            .with_state_key(prev_event.state_key().expect("conflicted events are state events"));
+        auth_events.insert(key, prev_event.clone());
+
+        if !passes_auth(room_version, &event, &auth_events)? {
+            warn!("event {} failed the authentication check", event.event_id());
+            return Ok(prev_event);
+        }
+
+        prev_event = event;
+    }
+
+    Ok(prev_event)
+}
+
+/// Resolves a conflict between events that are not auth events.
+///
+/// The most recent event that passes the auth checks wins. If none of them does, the oldest event
+/// is used.
+fn resolve_normal_events<E: Event + Clone>(
+    room_version: &RoomVersion,
+    events: &[E],
+    auth_events: &StateMap<E>,
+) -> Result<E> {
+    let events = ordered_events(events);
+
+    for event in &events {
+        if passes_auth(room_version, event, auth_events)? {
+            return Ok(event.clone());
+        }
+
+        warn!("event {} failed the authentication check", event.event_id());
+    }
+
+    Ok(events.last().expect("conflicted events are never empty").clone())
+}
+
+/// Sorts the events by descending depth, and by the SHA-1 hash of the event ID in case of a tie.
+fn ordered_events<E: Event + Clone>(events: &[E]) -> Vec<E> {
+    let mut events = events.to_vec();
+    events.sort_by_cached_key(|event| {
+        let event_id: &EventId = event.event_id().borrow();
+        (Reverse(event.depth()), Sha1::digest(event_id.as_bytes()))
+    });
+    events
+}
+
+fn passes_auth<E: Event>(
+    room_version: &RoomVersion,
+    event: &E,
+    auth_events: &StateMap<E>,
+) -> Result<bool> {
+    let current_third_party_invite = auth_events
+        .values()
+        .find(|event| *event.event_type() == RoomEventType::RoomThirdPartyInvite);
+
+    auth_check(room_version, event, current_third_party_invite, |ty, key| {
+        auth_events.get(&ty.with_state_key(key))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        convert::TryInto,
+        sync::Arc,
+    };
+
+    use js_int::uint;
+    use ruma_common::{
+        events::{
+            pdu::{EventHash, Pdu, RoomV1Pdu},
+            RoomEventType,
+        },
+        MilliSecondsSinceUnixEpoch, OwnedEventId, RoomVersionId, UserId,
+    };
+    use serde_json::{json, value::to_raw_value as to_raw_json_value, Value as JsonValue};
+
+    use crate::{
+        auth_types_for_event,
+        test_utils::{alice, bob, event_id, room_id, PduEvent},
+        Event, EventTypeExt, StateMap,
+    };
+
+    struct Node<'a> {
+        id: &'a str,
+        sender: &'a UserId,
+        kind: RoomEventType,
+        state_key: Option<&'a str>,
+        content: JsonValue,
+        prev_events: &'a [&'a str],
+    }
+
+    impl<'a> Node<'a> {
+        fn new(id: &'a str, kind: RoomEventType, state_key: Option<&'a str>) -> Self {
+            Self { id, sender: alice(), kind, state_key, content: json!({}), prev_events: &[] }
+        }
+
+        fn sender(self, sender: &'a UserId) -> Self {
+            Self { sender, ..self }
+        }
+
+        fn content(self, content: JsonValue) -> Self {
+            Self { content, ..self }
+        }
+
+        fn prev_events(self, prev_events: &'a [&'a str]) -> Self {
+            Self { prev_events, ..self }
+        }
+    }
+
+    /// Builds the event graph of the given nodes, which must be in topological order, and returns
+    /// the state after each event.
+    ///
+    /// Like in Synapse's tests, the state before an event is the resolved state after its
+    /// `prev_events`, and the depth of an event is one more than the depth of its deepest
+    /// `prev_event`.
+    fn state_after_events(nodes: Vec<Node<'_>>) -> HashMap<OwnedEventId, StateMap<OwnedEventId>> {
+        let mut events: HashMap<OwnedEventId, Arc<PduEvent>> = HashMap::new();
+        let mut states: HashMap<OwnedEventId, StateMap<OwnedEventId>> = HashMap::new();
+
+        for (ts, node) in nodes.into_iter().enumerate() {
+            let prev_events: Vec<_> = node.prev_events.iter().copied().map(event_id).collect();
+
+            let state_sets: Vec<_> = prev_events.iter().map(|id| &states[id]).collect();
+            let state_before = crate::resolve(&RoomVersionId::V1, state_sets, vec![], |id| {
+                events.get(id).map(Arc::clone)
+            })
+            .unwrap();
+
+            let depth = prev_events
+                .iter()
+                .map(|id| events[id].depth())
+                .max()
+                .map_or(uint!(1), |depth| depth + uint!(1));
+
+            let content = to_raw_json_value(&node.content).unwrap();
+            let auth_events =
+                auth_types_for_event(&node.kind, node.sender, node.state_key, &content)
+                    .unwrap()
+                    .into_iter()
+                    .filter_map(|key| state_before.get(&key).cloned())
+                    .map(|id| (id, EventHash::new("".to_owned())))
+                    .collect();
+
+            let event = Arc::new(PduEvent {
+                event_id: event_id(node.id),
+                rest: Pdu::RoomV1Pdu(RoomV1Pdu {
+                    event_id: event_id(node.id),
+                    room_id: room_id().to_owned(),
+                    sender: node.sender.to_owned(),
+                    origin_server_ts: MilliSecondsSinceUnixEpoch(ts.try_into().unwrap()),
+                    kind: node.kind,
+                    content,
+                    state_key: node.state_key.map(ToOwned::to_owned),
+                    prev_events: prev_events
+                        .into_iter()
+                        .map(|id| (id, EventHash::new("".to_owned())))
+                        .collect(),
+                    depth,
+                    auth_events,
+                    redacts: None,
+                    unsigned: BTreeMap::new(),
+                    hashes: EventHash::new("".to_owned()),
+                    signatures: BTreeMap::new(),
+                }),
+            });
+
+            let mut state_after = state_before;
+            if let Some(state_key) = event.state_key() {
+                state_after
+                    .insert(event.event_type().with_state_key(state_key), event.event_id.clone());
+            }
+
+            states.insert(event.event_id.clone(), state_after);
+            events.insert(event.event_id.clone(), event);
+        }
+
+        states
+    }
+
+    fn state_ids(state: &StateMap<OwnedEventId>) -> Vec<String> {
+        let mut ids: Vec<_> = state.values().map(|id| id.as_str().to_owned()).collect();
+        ids.sort();
+        ids
+    }
+
+    fn expected_ids(ids: &[&str]) -> Vec<String> {
+        let mut ids: Vec<_> = ids.iter().map(|id| event_id(id).as_str().to_owned()).collect();
+        ids.sort();
+        ids
+    }
+
+    fn create() -> Node<'static> {
+        Node::new("START", RoomEventType::RoomCreate, Some(""))
+            .content(json!({ "creator": alice() }))
+    }
+
+    fn join(id: &'static str, user: &'static UserId) -> Node<'static> {
+        Node::new(id, RoomEventType::RoomMember, Some(user.as_str()))
+            .sender(user)
+            .content(json!({ "membership": "join" }))
+    }
+
+    #[test]
+    fn branch_no_conflict() {
+        let states = state_after_events(vec![
+            create(),
+            join("A", alice()).prev_events(&["START"]),
+            Node::new("B", RoomEventType::RoomMessage, None).prev_events(&["A"]),
+            Node::new("C", RoomEventType::RoomName, Some("")).prev_events(&["B"]),
+            Node::new("D", RoomEventType::RoomMessage, None).prev_events(&["C", "B"]),
+        ]);
+
+        assert_eq!(state_ids(&states[&event_id("D")]), expected_ids(&["START", "A", "C"]));
+    }
+
+    #[test]
+    fn branch_basic_conflict() {
+        let states = state_after_events(vec![
+            create(),
+            join("A", alice()).prev_events(&["START"]),
+            Node::new("B", RoomEventType::RoomName, Some("")).prev_events(&["A"]),
+            Node::new("C", RoomEventType::RoomName, Some("")).prev_events(&["B"]),
+            Node::new("D", RoomEventType::RoomMessage, None).prev_events(&["B", "C"]),
+        ]);
+
+        // C is deeper than B.
+        assert_eq!(state_ids(&states[&event_id("D")]), expected_ids(&["START", "A", "C"]));
+    }
+
+    #[test]
+    fn branch_have_banned_conflict() {
+        let states = state_after_events(vec![
+            create(),
+            join("A", alice()).prev_events(&["START"]),
+            Node::new("P", RoomEventType::RoomPowerLevels, Some(""))
+                .content(json!({ "events": { "m.room.name": 0 }, "users": { alice(): 100 } }))
+                .prev_events(&["A"]),
+            join("B", bob()).prev_events(&["P"]),
+            Node::new("C", RoomEventType::RoomName, Some("")).prev_events(&["B"]),
+            Node::new("D", RoomEventType::RoomMember, Some(bob().as_str()))
+                .content(json!({ "membership": "ban" }))
+                .prev_events(&["C"]),
+            Node::new("E", RoomEventType::RoomName, Some("")).sender(bob()).prev_events(&["C"]),
+            Node::new("F", RoomEventType::RoomMessage, None).prev_events(&["D", "E"]),
+        ]);
+
+        // E is deeper than C but bob is banned.
+        assert_eq!(
+            state_ids(&states[&event_id("F")]),
+            expected_ids(&["START", "A", "P", "C", "D"])
+        );
+    }
+
+    #[test]
+    fn branch_have_perms_conflict() {
+        let states = state_after_events(vec![
+            create(),
+            join("A2", alice()).prev_events(&["START"]),
+            join("A3", bob()).prev_events(&["A2"]),
+            Node::new("A4", RoomEventType::RoomPowerLevels, Some(""))
+                .content(json!({
+                    "events": { "m.room.name": 50 },
+                    "users": { alice(): 100, bob(): 60 },
+                }))
+                .prev_events(&["A3"]),
+            Node::new("A5", RoomEventType::RoomName, Some("")).prev_events(&["A4"]),
+            Node::new("B", RoomEventType::RoomPowerLevels, Some(""))
+                .content(json!({
+                    "events": { "m.room.name": 50 },
+                    "users": { bob(): 30 },
+                }))
+                .prev_events(&["A5"]),
+            Node::new("C", RoomEventType::RoomName, Some("")).sender(bob()).prev_events(&["A5"]),
+            Node::new("D", RoomEventType::RoomMessage, None).prev_events(&["B", "C"]),
+        ]);
+
+        // B wins the power levels conflict, so bob can't set the name anymore.
+        assert_eq!(
+            state_ids(&states[&event_id("D")]),
+            expected_ids(&["START", "A2", "A3", "A5", "B"])
+        );
+    }
+
+    #[test]
+    fn branch_missing_key_is_not_conflict() {
+        let states = state_after_events(vec![
+            create(),
+            join("A", alice()).prev_events(&["START"]),
+            Node::new("B", RoomEventType::RoomTopic, Some("")).prev_events(&["A"]),
+            Node::new("C", RoomEventType::RoomName, Some("")).prev_events(&["A"]),
+            Node::new("D", RoomEventType::RoomMessage, None).prev_events(&["B", "C"]),
+        ]);
+
+        assert_eq!(state_ids(&states[&event_id("D")]), expected_ids(&["START", "A", "B", "C"]));
+    }
+
+    #[test]
+    fn ordered_events_tie_break() {
+        let states = state_after_events(vec![
+            create(),
+            join("A", alice()).prev_events(&["START"]),
+            Node::new("B", RoomEventType::RoomName, Some("")).prev_events(&["A"]),
+            Node::new("C", RoomEventType::RoomName, Some("")).prev_events(&["A"]),
+            Node::new("D", RoomEventType::RoomMessage, None).prev_events(&["B", "C"]),
+        ]);
+
+        // B and C have the same depth, so the one with the smallest SHA-1 hash of its event ID
+        // wins. sha1("$C:foo") < sha1("$B:foo").
+        assert_eq!(state_ids(&states[&event_id("D")]), expected_ids(&["START", "A", "C"]));
+    }
+}